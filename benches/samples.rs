@@ -0,0 +1,133 @@
+//! Throughput of `RecordingWriter::to_file` and `SigMF::samples` against the
+//! per-sample loop they replaced, which appended each component to a `Vec`
+//! and chose its byte order as it went.
+//!
+//! Both sides of each comparison do the same I/O — the new path through the
+//! public API, the old loop with `fs::write` and `fs::read` in its place — so
+//! the difference is the conversion. The checksum is off, since SHA-512 costs
+//! the same either way and would swamp it. Run with `cargo bench`, and with
+//! `--features rayon` to see the conversion spread over cores.
+
+use std::fs;
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use sigmf::num_complex::Complex;
+use sigmf::{Endianness, RecordingWriter};
+use tempfile::TempDir;
+
+/// Four mebisamples: large enough that the conversion, not the per-call
+/// overhead, is what is measured.
+const SAMPLES: usize = 1 << 22;
+
+/// The old loops, for one component type.
+macro_rules! per_sample {
+    ($encode:ident, $decode:ident, $component:ty) => {
+        fn $encode(samples: &[Complex<$component>], endianness: Endianness, path: &Path) {
+            let mut data = Vec::with_capacity(samples.len() * 2 * size_of::<$component>());
+            for sample in samples {
+                for component in [sample.re, sample.im] {
+                    match endianness {
+                        Endianness::LittleEndian => {
+                            data.extend_from_slice(&component.to_le_bytes())
+                        }
+                        Endianness::BigEndian => data.extend_from_slice(&component.to_be_bytes()),
+                    }
+                }
+            }
+            fs::write(path, data).expect("written");
+        }
+
+        fn $decode(endianness: Endianness, path: &Path) -> Vec<Complex<$component>> {
+            let data = fs::read(path).expect("read");
+            let width = size_of::<$component>();
+            let component = |bytes: &[u8]| {
+                let mut component = [0u8; size_of::<$component>()];
+                component.copy_from_slice(bytes);
+                match endianness {
+                    Endianness::LittleEndian => <$component>::from_le_bytes(component),
+                    Endianness::BigEndian => <$component>::from_be_bytes(component),
+                }
+            };
+            let mut samples = Vec::new();
+            samples.extend(data.chunks_exact(2 * width).map(|sample| {
+                let (re, im) = sample.split_at(width);
+                Complex::new(component(re), component(im))
+            }));
+            samples
+        }
+    };
+}
+
+per_sample!(encode_cf32, decode_cf32, f32);
+per_sample!(encode_ci16, decode_ci16, i16);
+
+fn cf32() -> Vec<Complex<f32>> {
+    (0..SAMPLES)
+        .map(|n| Complex::new(n as f32, -(n as f32)))
+        .collect()
+}
+
+fn ci16() -> Vec<Complex<i16>> {
+    (0..SAMPLES)
+        .map(|n| Complex::new(n as i16, !(n as i16)))
+        .collect()
+}
+
+const ORDERS: [(Endianness, &str); 2] = [
+    (Endianness::LittleEndian, "le"),
+    (Endianness::BigEndian, "be"),
+];
+
+macro_rules! bench_datatype {
+    ($name:ident, $datatype:literal, $component:ty, $samples:expr, $encode:ident, $decode:ident) => {
+        fn $name(c: &mut Criterion) {
+            let dir = TempDir::new().expect("a temp dir");
+            let samples = $samples;
+            let bytes = std::mem::size_of_val(&samples[..]) as u64;
+
+            let mut to_file = c.benchmark_group(concat!("to_file/", $datatype));
+            to_file.throughput(Throughput::Bytes(bytes));
+            for (endianness, order) in ORDERS {
+                let basename = dir.path().join(order);
+                to_file.bench_function(BenchmarkId::new("per_sample", order), |b| {
+                    b.iter(|| $encode(&samples, endianness, &basename))
+                });
+                to_file.bench_function(BenchmarkId::new("slice", order), |b| {
+                    b.iter(|| {
+                        RecordingWriter::new(&samples, 1e6)
+                            .endianness(endianness)
+                            .checksum(false)
+                            .to_file(&basename)
+                            .expect("written")
+                    })
+                });
+            }
+            to_file.finish();
+
+            let mut read = c.benchmark_group(concat!("samples/", $datatype));
+            read.throughput(Throughput::Bytes(bytes));
+            for (endianness, order) in ORDERS {
+                let recording = RecordingWriter::new(&samples, 1e6)
+                    .endianness(endianness)
+                    .checksum(false)
+                    .to_file(dir.path().join(order))
+                    .expect("written");
+                let dataset = dir.path().join(format!("{order}.sigmf-data"));
+                read.bench_function(BenchmarkId::new("per_sample", order), |b| {
+                    b.iter(|| $decode(endianness, &dataset))
+                });
+                read.bench_function(BenchmarkId::new("slice", order), |b| {
+                    b.iter(|| recording.samples::<Complex<$component>>().expect("read"))
+                });
+            }
+            read.finish();
+        }
+    };
+}
+
+bench_datatype!(bench_cf32, "cf32", f32, cf32(), encode_cf32, decode_cf32);
+bench_datatype!(bench_ci16, "ci16", i16, ci16(), encode_ci16, decode_ci16);
+
+criterion_group!(benches, bench_cf32, bench_ci16);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+//! `sigmf pack` and `sigmf unpack`: Recordings into and out of an Archive.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use crate::{metadata_path, report, COULD_NOT_RUN};
+
+pub fn pack(archive: &Path, recordings: &[PathBuf]) -> ExitCode {
+    let recordings: Vec<PathBuf> = recordings.iter().map(|r| metadata_path(r)).collect();
+    match report(archive, sigmf::archive::pack(archive, &recordings)) {
+        Some(()) => ExitCode::SUCCESS,
+        None => ExitCode::from(COULD_NOT_RUN),
+    }
+}
+
+pub fn unpack(archive: &Path, directory: &Path) -> ExitCode {
+    match report(archive, sigmf::archive::unpack(archive, directory)) {
+        Some(recordings) => {
+            println!(
+                "{}: {} Recordings unpacked into {}",
+                archive.display(),
+                recordings.len(),
+                directory.display()
+            );
+            ExitCode::SUCCESS
+        }
+        None => ExitCode::from(COULD_NOT_RUN),
+    }
+}
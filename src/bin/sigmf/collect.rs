@@ -0,0 +1,24 @@
+//! `sigmf collect`: Recordings grouped into a Collection.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use sigmf::{Collection, SIGMF_COLLECTION_EXT};
+
+use crate::{metadata_path, report, COULD_NOT_RUN};
+
+pub fn run(collection: &Path, recordings: &[PathBuf]) -> ExitCode {
+    let collection = if collection.to_string_lossy().ends_with(SIGMF_COLLECTION_EXT) {
+        collection.to_path_buf()
+    } else {
+        PathBuf::from(format!(
+            "{}{SIGMF_COLLECTION_EXT}",
+            collection.to_string_lossy()
+        ))
+    };
+    let recordings: Vec<PathBuf> = recordings.iter().map(|r| metadata_path(r)).collect();
+    match report(&collection, Collection::create(&collection, &recordings)) {
+        Some(_) => ExitCode::SUCCESS,
+        None => ExitCode::from(COULD_NOT_RUN),
+    }
+}
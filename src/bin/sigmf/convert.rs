@@ -0,0 +1,57 @@
+//! `sigmf convert`: a Recording's samples in another datatype.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use sigmf::num_complex::Complex;
+use sigmf::{DataFormat, DataType, Endianness, NumberType, Scaling};
+
+use crate::{basename, open, report, COULD_NOT_RUN};
+
+pub fn run(path: &Path, to: DataFormat, scaling: Scaling, output: &Path) -> ExitCode {
+    let Some(recording) = open(path) else {
+        return ExitCode::from(COULD_NOT_RUN);
+    };
+    let output = basename(output);
+
+    // `transcode` takes its target as a type, and the command line names it as
+    // a string: one arm per type the specification permits.
+    macro_rules! transcode {
+        ($($data_type:ident => $component:ty),* $(,)?) => {
+            match (to.number_type, to.data_type) {
+                $(
+                    (NumberType::Real, DataType::$data_type(endianness)) => recording
+                        .transcode::<$component, _>(&output, scaling, endianness),
+                    (NumberType::Complex, DataType::$data_type(endianness)) => recording
+                        .transcode::<Complex<$component>, _>(&output, scaling, endianness),
+                )*
+                (NumberType::Real, DataType::I8) => {
+                    recording.transcode::<i8, _>(&output, scaling, Endianness::LittleEndian)
+                }
+                (NumberType::Complex, DataType::I8) => recording
+                    .transcode::<Complex<i8>, _>(&output, scaling, Endianness::LittleEndian),
+                (NumberType::Real, DataType::U8) => {
+                    recording.transcode::<u8, _>(&output, scaling, Endianness::LittleEndian)
+                }
+                (NumberType::Complex, DataType::U8) => recording
+                    .transcode::<Complex<u8>, _>(&output, scaling, Endianness::LittleEndian),
+            }
+        };
+    }
+    let transcoded =
+        transcode!(F32 => f32, F64 => f64, I32 => i32, I16 => i16, U32 => u32, U16 => u16);
+
+    match report(path, transcoded) {
+        Some(transcoded) => {
+            if transcoded.clipped > 0 {
+                eprintln!(
+                    "sigmf: {}: {} components did not fit in {to} and were clipped",
+                    path.display(),
+                    transcoded.clipped
+                );
+            }
+            ExitCode::SUCCESS
+        }
+        None => ExitCode::from(COULD_NOT_RUN),
+    }
+}
@@ -0,0 +1,61 @@
+//! `sigmf info`: what a Recording holds, at a glance.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use crate::{open, COULD_NOT_RUN};
+
+pub fn run(path: &Path) -> ExitCode {
+    let Some(recording) = open(path) else {
+        return ExitCode::from(COULD_NOT_RUN);
+    };
+    let metadata = &recording.metadata;
+    let global = &metadata.global;
+
+    println!("{}", path.display());
+    row("version", &global.version);
+    row("datatype", &global.datatype.to_string());
+    row(
+        "sample rate",
+        &global
+            .sample_rate
+            .map_or("unknown".to_string(), |rate| format!("{rate} Hz")),
+    );
+
+    if global.metadata_only == Some(true) {
+        row("samples", "none: this Recording is metadata only");
+    } else {
+        match recording.sample_count() {
+            Ok(samples) => {
+                row("samples", &samples.to_string());
+                row(
+                    "duration",
+                    &global
+                        .sample_rate
+                        .map_or("unknown, without a sample rate".to_string(), |rate| {
+                            format!("{:.6} s", samples as f64 / rate)
+                        }),
+                );
+            }
+            Err(e) => {
+                eprintln!("sigmf: {}: {e}", path.display());
+                return ExitCode::from(COULD_NOT_RUN);
+            }
+        }
+    }
+
+    row("captures", &metadata.captures.len().to_string());
+    row("annotations", &metadata.annotations.len().to_string());
+    if let Some(extensions) = global.extensions.as_ref().filter(|e| !e.is_empty()) {
+        let names: Vec<String> = extensions
+            .iter()
+            .map(|e| format!("{} {}", e.name, e.version))
+            .collect();
+        row("extensions", &names.join(", "));
+    }
+    ExitCode::SUCCESS
+}
+
+fn row(label: &str, value: &str) {
+    println!("  {label:<12} {value}");
+}
@@ -0,0 +1,223 @@
+//! `sigmf`: inspect, check and rework SigMF Recordings from a shell.
+//!
+//! Every subcommand is a thin layer over the library's public API — if the tool
+//! can do something a caller of the crate cannot, the crate is missing a
+//! function. Each lives in a module of its own and returns an [`ExitCode`]:
+//!
+//! - 0: the question was asked and the answer is yes.
+//! - 1: the question was asked and the answer is no — a Recording breaks a
+//!   rule, or its Dataset does not match its hash.
+//! - 2: the question could not be asked — a file would not open, or there was
+//!   nothing to check. `clap` exits 2 for a usage error too, so a script can
+//!   treat every 2 alike.
+//!
+//! The subcommands that write something rather than answer a question exit 0
+//! or 2.
+//!
+//! Given several Recordings, a subcommand exits with the highest code any of
+//! them earned.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use sigmf::{DataFormat, Scaling, SigMF, SIGMF_DATASET_EXT, SIGMF_METADATA_EXT};
+
+mod archive;
+mod collect;
+mod convert;
+mod info;
+mod slice;
+mod validate;
+mod verify;
+
+/// The answer is no.
+const FAILED: u8 = 1;
+
+/// The question could not be asked.
+const COULD_NOT_RUN: u8 = 2;
+
+/// Inspect, check and rework SigMF Recordings.
+///
+/// A Recording may be named by its Metadata file, its Dataset file, or the
+/// basename the two share.
+#[derive(Parser)]
+#[command(name = "sigmf", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Summarise a Recording: datatype, sample rate, duration, and how many
+    /// Captures segments and annotations it has.
+    Info {
+        /// The Recording.
+        recording: PathBuf,
+    },
+    /// Check Recordings against the rules of the SigMF specification.
+    ///
+    /// Exits 1 if any Recording breaks a rule the specification states with
+    /// MUST, and 0 if the worst found is a warning.
+    Validate {
+        /// Fail on warnings too.
+        #[arg(long)]
+        strict: bool,
+        /// The Recordings.
+        #[arg(required = true)]
+        recordings: Vec<PathBuf>,
+    },
+    /// Check that each Recording's Dataset is the one its `core:sha512` was
+    /// computed over.
+    ///
+    /// Exits 1 if any Dataset does not match, and 2 if any could not be
+    /// checked — including a Recording that records no hash.
+    Verify {
+        /// The Recordings.
+        #[arg(required = true)]
+        recordings: Vec<PathBuf>,
+    },
+    /// Cut a span of samples out of a Recording into a Recording of its own.
+    Slice {
+        /// The Recording.
+        recording: PathBuf,
+        /// The first sample to keep, counting from 0.
+        #[arg(long, default_value_t = 0)]
+        start: u64,
+        /// One past the last sample to keep. The end of the Recording if
+        /// neither this nor `--count` is given.
+        #[arg(long, conflicts_with = "count")]
+        end: Option<u64>,
+        /// How many samples to keep.
+        #[arg(long)]
+        count: Option<u64>,
+        /// The basename to write the slice to.
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Rewrite a Recording's samples in another datatype or byte order.
+    Convert {
+        /// The Recording.
+        recording: PathBuf,
+        /// The datatype to write, as `core:datatype` spells it: `cf32_le`.
+        #[arg(long)]
+        to: DataFormat,
+        /// `full-scale` maps one type's full scale onto the other's,
+        /// `preserve` keeps each number as it is, and a number multiplies
+        /// every component by itself.
+        #[arg(long, default_value = "full-scale", value_parser = parse_scaling)]
+        scaling: Scaling,
+        /// The basename to write the converted Recording to.
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Bundle Recordings into a `.sigmf` Archive.
+    Pack {
+        /// The Archive to write.
+        archive: PathBuf,
+        /// The Recordings.
+        #[arg(required = true)]
+        recordings: Vec<PathBuf>,
+    },
+    /// Extract the Recordings in a `.sigmf` Archive.
+    Unpack {
+        /// The Archive.
+        archive: PathBuf,
+        /// The directory to extract into.
+        #[arg(short = 'C', long, default_value = ".")]
+        directory: PathBuf,
+    },
+    /// Group Recordings into a `.sigmf-collection`, and mark each as a member.
+    Collect {
+        /// The Collection file to write.
+        collection: PathBuf,
+        /// The Recordings, in the directory of the Collection or below it.
+        #[arg(required = true)]
+        recordings: Vec<PathBuf>,
+    },
+}
+
+fn main() -> ExitCode {
+    match Cli::parse().command {
+        Command::Info { recording } => info::run(&recording),
+        Command::Validate { strict, recordings } => validate::run(&recordings, strict),
+        Command::Verify { recordings } => verify::run(&recordings),
+        Command::Slice {
+            recording,
+            start,
+            end,
+            count,
+            output,
+        } => slice::run(&recording, start, end, count, &output),
+        Command::Convert {
+            recording,
+            to,
+            scaling,
+            output,
+        } => convert::run(&recording, to, scaling, &output),
+        Command::Pack {
+            archive,
+            recordings,
+        } => archive::pack(&archive, &recordings),
+        Command::Unpack { archive, directory } => archive::unpack(&archive, &directory),
+        Command::Collect {
+            collection,
+            recordings,
+        } => collect::run(&collection, &recordings),
+    }
+}
+
+/// The Metadata file of the Recording `path` names.
+///
+/// Operators tab-complete to whichever of the two files comes first, so
+/// either is accepted, as is the basename they share.
+fn metadata_path(path: &Path) -> PathBuf {
+    let name = path.to_string_lossy();
+    if name.ends_with(SIGMF_METADATA_EXT) {
+        path.to_path_buf()
+    } else if let Some(basename) = name.strip_suffix(SIGMF_DATASET_EXT) {
+        PathBuf::from(format!("{basename}{SIGMF_METADATA_EXT}"))
+    } else {
+        PathBuf::from(format!("{name}{SIGMF_METADATA_EXT}"))
+    }
+}
+
+/// The basename `path` names, so that an output may be given as either of the
+/// files it will become.
+fn basename(path: &Path) -> PathBuf {
+    let name = path.to_string_lossy();
+    [SIGMF_METADATA_EXT, SIGMF_DATASET_EXT]
+        .iter()
+        .find_map(|extension| name.strip_suffix(extension))
+        .map_or_else(|| path.to_path_buf(), PathBuf::from)
+}
+
+fn parse_scaling(s: &str) -> Result<Scaling, String> {
+    match s {
+        "full-scale" => Ok(Scaling::FullScale),
+        "preserve" => Ok(Scaling::Preserve),
+        factor => factor
+            .parse()
+            .map(Scaling::Factor)
+            .map_err(|_| format!("{factor:?} is not `full-scale`, `preserve`, or a number")),
+    }
+}
+
+/// Report a failure on stderr, and hand back what succeeded.
+fn report<T>(path: &Path, result: Result<T, sigmf::Error>) -> Option<T> {
+    result
+        .map_err(|e| eprintln!("sigmf: {}: {e}", path.display()))
+        .ok()
+}
+
+/// Open the Recording `path` names, or say why not on stderr.
+fn open(path: &Path) -> Option<SigMF> {
+    match SigMF::from_file(metadata_path(path)) {
+        Ok(recording) => Some(recording),
+        Err(e) => {
+            eprintln!("sigmf: {}: {e}", path.display());
+            None
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! `sigmf slice`: a span of samples as a Recording of its own.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use crate::{basename, open, report, COULD_NOT_RUN};
+
+pub fn run(
+    path: &Path,
+    start: u64,
+    end: Option<u64>,
+    count: Option<u64>,
+    output: &Path,
+) -> ExitCode {
+    let Some(recording) = open(path) else {
+        return ExitCode::from(COULD_NOT_RUN);
+    };
+    let end = match (end, count) {
+        (Some(end), _) => end,
+        (None, Some(count)) => start.saturating_add(count),
+        (None, None) => match report(path, recording.sample_count()) {
+            Some(sample_count) => sample_count,
+            None => return ExitCode::from(COULD_NOT_RUN),
+        },
+    };
+
+    let output = basename(output);
+    match report(path, recording.slice(start..end, &output)) {
+        Some(_) => ExitCode::SUCCESS,
+        None => ExitCode::from(COULD_NOT_RUN),
+    }
+}
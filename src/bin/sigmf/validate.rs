@@ -0,0 +1,36 @@
+//! `sigmf validate`: every rule each Recording breaks.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use sigmf::Severity;
+
+use crate::{open, COULD_NOT_RUN, FAILED};
+
+pub fn run(paths: &[PathBuf], strict: bool) -> ExitCode {
+    let mut could_not_run = false;
+    let mut failed = false;
+
+    for path in paths {
+        let Some(recording) = open(path) else {
+            could_not_run = true;
+            continue;
+        };
+        let findings = recording.validate();
+        if findings.is_empty() {
+            println!("{}: ok", path.display());
+        }
+        for finding in &findings {
+            println!("{}: {finding}", path.display());
+            failed |= finding.severity == Severity::Error || strict;
+        }
+    }
+
+    if could_not_run {
+        ExitCode::from(COULD_NOT_RUN)
+    } else if failed {
+        ExitCode::from(FAILED)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,39 @@
+//! `sigmf verify`: whether each Dataset is the one its hash describes.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use sigmf::{Error, MetadataError};
+
+use crate::{open, COULD_NOT_RUN, FAILED};
+
+pub fn run(paths: &[PathBuf]) -> ExitCode {
+    let mut could_not_run = false;
+    let mut failed = false;
+
+    for path in paths {
+        let Some(recording) = open(path) else {
+            could_not_run = true;
+            continue;
+        };
+        match recording.verify_sha512() {
+            Ok(()) => println!("{}: ok", path.display()),
+            Err(e @ Error::Metadata(MetadataError::Sha512Mismatch { .. })) => {
+                println!("{}: FAILED: {e}", path.display());
+                failed = true;
+            }
+            Err(e) => {
+                eprintln!("sigmf: {}: {e}", path.display());
+                could_not_run = true;
+            }
+        }
+    }
+
+    if could_not_run {
+        ExitCode::from(COULD_NOT_RUN)
+    } else if failed {
+        ExitCode::from(FAILED)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -110,12 +110,12 @@ pub const SIGMF_METADATA_EXT: &str = ".sigmf-meta";
 /// this.
 pub const SIGMF_DATASET_EXT: &str = ".sigmf-data";
 
-// `.sigmf` (an Archive) and `.sigmf-collection` (a Collection) sat here too, unread
-// by any code path for the crate's whole life. Deleted rather than silenced with an
-// `#[allow(dead_code)]`: this crate supports neither format, so exporting the names
-// would advertise what it cannot do, and keeping them private kept two string
-// literals nothing could reach. They are two lines in the specification, and git
-// remembers the spelling.
+/// The extension of an Archive, dot included: a tar file of Recordings, read and
+/// written by [`archive`].
+pub const SIGMF_ARCHIVE_EXT: &str = ".sigmf";
+
+/// The extension of a Collection file, dot included. See [`Collection`].
+pub const SIGMF_COLLECTION_EXT: &str = ".sigmf-collection";
 
 /// Every public item, which the crate root re-exports.
 ///
@@ -131,6 +131,7 @@ mod sigmf {
     use serde_json::Value;
     use sha2::{Digest, Sha512};
     use std::collections::BTreeMap as Map;
+    use std::collections::BTreeSet;
     use std::ffi::OsStr;
     use std::fmt::{self, Write as _};
     use std::ops::Range;
@@ -141,6 +142,44 @@ mod sigmf {
 
     use serde::{Deserialize, Serialize};
 
+    mod annotation_index;
+    pub mod annotations;
+    pub mod archive;
+    #[cfg(feature = "tokio")]
+    mod asynchronous;
+    pub mod bluefile;
+    pub mod catalog;
+    mod checksum;
+    mod collection;
+    mod concat;
+    mod dataset;
+    mod datetime;
+    mod discontinuity;
+    pub mod geo;
+    mod global_index;
+    pub mod gnuradio;
+    mod import;
+    pub mod npy;
+    mod parallel;
+    pub mod preview;
+    mod read_into;
+    mod slice;
+    mod statistics;
+    mod transcode;
+    mod validate;
+    pub mod vita49;
+    pub mod wav;
+
+    pub use annotation_index::AnnotationIndex;
+    #[cfg(feature = "tokio")]
+    pub use asynchronous::SampleStream;
+    pub use collection::{Collection, Stream};
+    pub use discontinuity::{Discontinuity, DiscontinuityKind};
+    pub use import::RawImport;
+    pub use statistics::{IqImbalance, SignalStatistics, Statistics};
+    pub use transcode::{Scaling, Transcoded};
+    pub use validate::{Finding, Severity};
+
     /// The version of the SigMF specification this crate implements, in the form
     /// `core:version` takes.
     ///
@@ -181,6 +220,14 @@ mod sigmf {
         /// size of its sidecar, and [`samples`](Self::samples) is the call that goes
         /// to disk for the rest.
         ///
+        /// # Extensions are not checked
+        ///
+        /// A Recording declaring a non-[`optional`](Extension::optional) extension
+        /// opens here whether or not the caller understands it. That suits a tool
+        /// that only displays or copies Recordings, and it is the wrong default for
+        /// one that interprets them: [`from_file_supporting`](Self::from_file_supporting)
+        /// is the same call with the check the specification asks for.
+        ///
         /// # Errors
         ///
         /// [`Error::Io`] if the Metadata file cannot be read, [`Error::Json`] if it
@@ -196,6 +243,51 @@ mod sigmf {
             Ok(Self { metadata, datafile })
         }
 
+        /// Open a Recording as [`from_file`](Self::from_file) does, refusing it if
+        /// it requires an extension `registry` does not list.
+        ///
+        /// An extension declared with `optional: false` is one the Recording
+        /// cannot be interpreted without — the specification's words are that a
+        /// reader MUST support it and SHOULD error otherwise. This is that error,
+        /// raised before the caller has a `SigMF` to misread. Optional extensions
+        /// are never a reason to refuse, listed or not.
+        ///
+        /// # Errors
+        ///
+        /// Everything [`from_file`](Self::from_file) can return, and
+        /// [`MetadataError::UnsupportedRequiredExtensions`] naming every required
+        /// namespace the registry does not list — all of them, not the first, so
+        /// that one error message is the whole list of work to do.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use sigmf::{AntennaGlobal, Error, ExtensionRegistry, MetadataError, SigMF};
+        ///
+        /// // Declares `antenna` as optional and `capture_details` as required.
+        /// let path = "tests/sigmf_test_files/extensions.sigmf-meta";
+        ///
+        /// let antenna_only = ExtensionRegistry::new().with::<AntennaGlobal>();
+        /// let err = SigMF::from_file_supporting(path, &antenna_only)
+        ///     .expect_err("capture_details is required and not understood");
+        /// assert!(matches!(
+        ///     err,
+        ///     Error::Metadata(MetadataError::UnsupportedRequiredExtensions(_))
+        /// ));
+        ///
+        /// let both = antenna_only.with_namespace("capture_details");
+        /// SigMF::from_file_supporting(path, &both)?;
+        /// # Ok::<(), sigmf::Error>(())
+        /// ```
+        pub fn from_file_supporting<T: AsRef<Path>>(
+            path: T,
+            registry: &ExtensionRegistry,
+        ) -> Result<Self, Error> {
+            let recording = Self::from_file(path)?;
+            recording.metadata.check_extensions(registry)?;
+            Ok(recording)
+        }
+
         /// Where each Captures segment's samples sit in the Dataset, as byte ranges.
         ///
         /// The Dataset is measured, not read: this needs its length and nothing
@@ -214,6 +306,34 @@ mod sigmf {
             Ok(self.metadata.capture_boundaries(dataset_len)?)
         }
 
+        /// How many samples the Dataset holds, measured rather than read.
+        ///
+        /// See [`Metadata::sample_count`].
+        ///
+        /// # Errors
+        ///
+        /// As for [`capture_boundaries`](Self::capture_boundaries), and
+        /// [`MetadataError::PartialSample`] if the last segment ends partway
+        /// through a sample.
+        pub fn sample_count(&self) -> Result<u64, Error> {
+            let path = self.datafile.as_ref().ok_or(MetadataError::NoDataset)?;
+            let dataset_len = fs::metadata(path).map_err(at(path))?.len();
+            Ok(self.metadata.sample_count(dataset_len)?)
+        }
+
+        /// An [`AnnotationIndex`] over this Recording's annotations.
+        ///
+        /// The Dataset is measured for the index's sake: an annotation without
+        /// `core:sample_count` runs to the end of the Recording, and the Metadata
+        /// alone cannot say where that is.
+        ///
+        /// # Errors
+        ///
+        /// As for [`sample_count`](Self::sample_count).
+        pub fn annotation_index(&self) -> Result<AnnotationIndex<'_>, Error> {
+            Ok(AnnotationIndex::new(&self.metadata, self.sample_count()?))
+        }
+
         /// Every sample in the Dataset, in order, decoded as `S`.
         ///
         /// # `S` is checked, not assumed
@@ -271,6 +391,36 @@ mod sigmf {
         /// # Ok::<(), sigmf::Error>(())
         /// ```
         pub fn samples<S: Sample>(&self) -> Result<Vec<S>, Error> {
+            let endianness = self.check_sample_type::<S>()?;
+            let datatype = self.metadata.global.datatype;
+
+            let path = self.datafile.as_ref().ok_or(MetadataError::NoDataset)?;
+            let data = fs::read(path).map_err(at(path))?;
+            let boundaries = self.metadata.capture_boundaries(data.len() as u64)?;
+
+            let sample_size = datatype.size() as usize;
+            let mut samples = Vec::with_capacity(data.len() / sample_size);
+            for range in boundaries {
+                // `capture_boundaries` has already established that this range lies
+                // within the Dataset, which is what makes both the cast and the
+                // index safe.
+                let bytes = &data[range.start as usize..range.end as usize];
+                if bytes.len() % sample_size != 0 {
+                    return Err(MetadataError::PartialSample {
+                        bytes: bytes.len() as u64,
+                        datatype,
+                    }
+                    .into());
+                }
+                parallel::decode_into(bytes, sample_size, endianness, &mut samples);
+            }
+            Ok(samples)
+        }
+
+        /// The byte order to decode `S` in, once it is established that `S` is
+        /// what `core:datatype` says and that the Dataset is one channel — the
+        /// two checks every typed read makes before it reads a byte.
+        fn check_sample_type<S: Sample>(&self) -> Result<Endianness, Error> {
             let datatype = self.metadata.global.datatype;
 
             // A one-byte component has no byte order, so for `ri8`/`ru8` the
@@ -296,28 +446,7 @@ mod sigmf {
                 }
             }
 
-            let path = self.datafile.as_ref().ok_or(MetadataError::NoDataset)?;
-            let data = fs::read(path).map_err(at(path))?;
-            let boundaries = self.metadata.capture_boundaries(data.len() as u64)?;
-
-            let sample_size = datatype.size() as usize;
-            let mut samples = Vec::new();
-            for range in boundaries {
-                // `capture_boundaries` has already established that this range lies
-                // within the Dataset, which is what makes both the cast and the
-                // index safe.
-                let bytes = &data[range.start as usize..range.end as usize];
-                let whole_samples = bytes.chunks_exact(sample_size);
-                if !whole_samples.remainder().is_empty() {
-                    return Err(MetadataError::PartialSample {
-                        bytes: bytes.len() as u64,
-                        datatype,
-                    }
-                    .into());
-                }
-                samples.extend(whole_samples.map(|sample| S::decode(endianness, sample)));
-            }
-            Ok(samples)
+            Ok(endianness)
         }
     }
 
@@ -413,6 +542,73 @@ mod sigmf {
         }
     }
 
+    /// An [`Error::Io`] naming a file that was read but is not what it claims
+    /// to be — a truncated tar header, a WAV file without samples — for the
+    /// container formats whose faults are not the specification's to name.
+    fn invalid(path: &Path, message: &str) -> Error {
+        Error::Io {
+            path: path.to_path_buf(),
+            source: std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string()),
+        }
+    }
+
+    /// Flush `out` and wait for the file beneath it to reach the disk, naming
+    /// `path` if either fails.
+    ///
+    /// Dropping a `BufWriter` flushes it too, but throws away the error, and a
+    /// file the kernel has accepted is not yet one the disk holds; a writer
+    /// that ends with this has done both before it returns `Ok`.
+    fn finish_file(out: std::io::BufWriter<fs::File>, path: &Path) -> Result<(), Error> {
+        out.into_inner()
+            .map_err(|e| e.into_error())
+            .and_then(|file| file.sync_all())
+            .map_err(at(path))
+    }
+
+    /// Every `.sigmf-meta` file in `dir` and the directories below it, sorted
+    /// by path so that a walk of the same tree always visits it in one order.
+    ///
+    /// Symbolic links to directories are not followed: a link back up the tree
+    /// would otherwise make the walk endless.
+    fn metadata_files_under(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+        files_under(dir, &[SIGMF_METADATA_EXT])
+    }
+
+    /// Every file in `dir` and the directories below it whose name ends with
+    /// one of `extensions`, sorted by path, walked as
+    /// [`metadata_files_under`] walks.
+    fn files_under(dir: &Path, extensions: &[&str]) -> Result<Vec<PathBuf>, Error> {
+        let mut found = Vec::new();
+        let mut pending = vec![dir.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            for entry in fs::read_dir(&dir).map_err(at(&dir))? {
+                let entry = entry.map_err(at(&dir))?;
+                let path = entry.path();
+                if entry.file_type().map_err(at(&path))?.is_dir() {
+                    pending.push(path);
+                } else if path
+                    .file_name()
+                    .and_then(OsStr::to_str)
+                    .is_some_and(|name| extensions.iter().any(|ext| name.ends_with(ext)))
+                {
+                    found.push(path);
+                }
+            }
+        }
+        found.sort();
+        Ok(found)
+    }
+
+    /// ``` `antenna` 1.0.0, `capture_details` 1.0.0 ```, for an error message
+    /// naming extensions.
+    fn list_extensions(extensions: &[Extension]) -> String {
+        let named: Vec<String> = extensions
+            .iter()
+            .map(|e| format!("`{}` {}", e.name, e.version))
+            .collect();
+        named.join(", ")
+    }
+
     /// What a JSON value is, in the specification's own vocabulary, for an error
     /// message that has to tell a caller what they handed over.
     fn json_type_name(value: &Value) -> &'static str {
@@ -601,7 +797,17 @@ mod sigmf {
         /// stated `core:sample_rate` is outside the schema's bounds — a positive
         /// number of at most 10¹² — or [`Error::Io`] if either file cannot be
         /// written. A refused write leaves no file behind.
-        pub fn to_file<P: AsRef<Path>>(mut self, basename: P) -> Result<SigMF, Error> {
+        pub fn to_file<P: AsRef<Path>>(self, basename: P) -> Result<SigMF, Error> {
+            let encoded = self.encode(basename.as_ref())?;
+            fs::write(&encoded.data_path, &encoded.data).map_err(at(&encoded.data_path))?;
+            fs::write(&encoded.metadata_path, &encoded.json).map_err(at(&encoded.metadata_path))?;
+            Ok(encoded.into_recording())
+        }
+
+        /// Everything [`to_file`](Self::to_file) writes, checked and encoded but
+        /// not yet written, so that a writer of another kind writes the same
+        /// bytes.
+        fn encode(mut self, basename: &Path) -> Result<Encoded, Error> {
             // A `&[S]` is one channel by construction: nothing in the slice can say
             // where one channel ends and the next begins, so honouring
             // `core:num_channels > 1` would mean writing a datatype that describes
@@ -624,24 +830,38 @@ mod sigmf {
             }
 
             let datatype = DataFormat::of::<S>(self.endianness);
-            let mut data = Vec::with_capacity(self.samples.len() * datatype.size() as usize);
-            for sample in self.samples {
-                sample.encode(self.endianness, &mut data);
-            }
+            let data = parallel::encode(self.samples, datatype.size() as usize, self.endianness);
 
             self.metadata.global.datatype = datatype;
             self.metadata.global.sha512 = self.checksum.then(|| hex_encode(&Sha512::digest(&data)));
 
-            let data_path = append_extension(basename.as_ref(), SIGMF_DATASET_EXT);
-            let metadata_path = append_extension(basename.as_ref(), SIGMF_METADATA_EXT);
+            Ok(Encoded {
+                data_path: append_extension(basename, SIGMF_DATASET_EXT),
+                metadata_path: append_extension(basename, SIGMF_METADATA_EXT),
+                data,
+                json: self.metadata.to_json()?,
+                metadata: self.metadata,
+            })
+        }
+    }
 
-            fs::write(&data_path, &data).map_err(at(&data_path))?;
-            fs::write(&metadata_path, self.metadata.to_json()?).map_err(at(&metadata_path))?;
+    /// A Recording ready to be written: both files' paths and contents, in the
+    /// order they are to be written.
+    struct Encoded {
+        data_path: PathBuf,
+        data: Vec<u8>,
+        metadata_path: PathBuf,
+        json: String,
+        metadata: Metadata,
+    }
 
-            Ok(SigMF {
+    impl Encoded {
+        /// The Recording, once both files are written.
+        fn into_recording(self) -> SigMF {
+            SigMF {
                 metadata: self.metadata,
-                datafile: Some(data_path),
-            })
+                datafile: Some(self.data_path),
+            }
         }
     }
 
@@ -659,7 +879,7 @@ mod sigmf {
     /// alongside [`SigMF`]. It is also the type that can lie: a Global's
     /// `core:datatype` is checked against reality only by [`RecordingWriter::to_file`]
     /// and [`SigMF::samples`], never by this type on its own.
-    #[derive(Debug, Deserialize, Serialize)]
+    #[derive(Debug, Clone, Deserialize, Serialize)]
     pub struct Metadata {
         /// What the samples are: format, rate, and provenance.
         pub global: GlobalMetadata,
@@ -714,6 +934,35 @@ mod sigmf {
             serde_json::to_string_pretty(self)
         }
 
+        /// Check that `registry` lists every extension this document requires.
+        ///
+        /// Only declarations in `core:extensions` with `optional: false` are
+        /// consulted. Extension data the document carries without declaring is not
+        /// a requirement — the specification makes the declaration, not the data,
+        /// the reader's cue — and [`GlobalMetadata::get_extension`] reads such data
+        /// regardless.
+        ///
+        /// # Errors
+        ///
+        /// [`MetadataError::UnsupportedRequiredExtensions`], carrying the
+        /// declaration of every required extension the registry does not list.
+        pub fn check_extensions(&self, registry: &ExtensionRegistry) -> Result<(), MetadataError> {
+            let unsupported: Vec<Extension> = self
+                .global
+                .extensions
+                .iter()
+                .flatten()
+                .filter(|declared| !declared.optional && !registry.supports(&declared.name))
+                .cloned()
+                .collect();
+
+            if unsupported.is_empty() {
+                Ok(())
+            } else {
+                Err(MetadataError::UnsupportedRequiredExtensions(unsupported))
+            }
+        }
+
         /// Where each Captures segment's samples sit in a Dataset `dataset_len`
         /// bytes long, as byte ranges.
         ///
@@ -737,6 +986,14 @@ mod sigmf {
         /// 0}]` — one implicit segment covering everything — and that is what this
         /// returns.
         ///
+        /// # `core:offset`
+        ///
+        /// Sample indices are absolute, so a segment's samples begin
+        /// `core:sample_start` minus [`GlobalMetadata::offset`] samples into the
+        /// Dataset. A `core:sample_start` below `core:offset`, which the
+        /// specification says there should not be, describes a segment already
+        /// in effect when the Dataset began, and is placed at its first sample.
+        ///
         /// # Errors
         ///
         /// [`MetadataError::CaptureOutOfBounds`] if a segment describes bytes the
@@ -800,7 +1057,7 @@ mod sigmf {
                 // `u64::MAX` with a small, plausible, wrong byte offset.
                 let byte_of = |sample_start: u64| -> Result<u64, MetadataError> {
                     sample_size
-                        .checked_mul(sample_start)
+                        .checked_mul(self.dataset_sample(sample_start))
                         .and_then(|offset| offset.checked_add(headers))
                         .ok_or(MetadataError::SampleStartOutOfRange {
                             index,
@@ -828,6 +1085,44 @@ mod sigmf {
             }
             Ok(boundaries)
         }
+
+        /// How many samples a Dataset `dataset_len` bytes long holds.
+        ///
+        /// Where the last Captures segment starts in the Dataset, plus however many
+        /// samples its bytes make. Sample indices are absolute, so an annotation
+        /// without `core:sample_count` runs up to this plus
+        /// [`GlobalMetadata::offset`].
+        ///
+        /// # Errors
+        ///
+        /// As for [`capture_boundaries`](Self::capture_boundaries), and
+        /// [`MetadataError::PartialSample`] if the last segment's bytes are not a
+        /// whole number of samples.
+        pub fn sample_count(&self, dataset_len: u64) -> Result<u64, MetadataError> {
+            let boundaries = self.capture_boundaries(dataset_len)?;
+            let sample_size = self.global.datatype.size();
+            let (first_sample, bytes) = match (self.captures.last(), boundaries.last()) {
+                (Some(capture), Some(range)) => (
+                    self.dataset_sample(capture.sample_start),
+                    range.end - range.start,
+                ),
+                // No captures means one implicit segment from sample 0.
+                (_, range) => (0, range.map_or(0, |range| range.end - range.start)),
+            };
+            if bytes % sample_size != 0 {
+                return Err(MetadataError::PartialSample {
+                    bytes,
+                    datatype: self.global.datatype,
+                });
+            }
+            Ok(first_sample + bytes / sample_size)
+        }
+
+        /// The Dataset index of sample index `index`: how far it is past
+        /// [`GlobalMetadata::offset`], or 0 for an index before it.
+        fn dataset_sample(&self, index: u64) -> u64 {
+            index.saturating_sub(self.global.offset.unwrap_or(0))
+        }
     }
 
     /// The `global` scope: what the samples are, and where they came from.
@@ -839,7 +1134,7 @@ mod sigmf {
     /// one exception: [`other`](Self::other) is the catch-all that makes this type
     /// lossless, because the schema does not close this object and extension
     /// namespaces live in it.
-    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
     pub struct GlobalMetadata {
         /// How to read every byte of the Dataset.
         ///
@@ -884,8 +1179,8 @@ mod sigmf {
         ///
         /// [`RecordingWriter::to_file`] computes and overwrites this, or clears it
         /// when [`RecordingWriter::checksum`] is off — a stale hash describing a
-        /// Dataset that no longer exists is worse than no hash. Nothing in this
-        /// crate verifies it on read.
+        /// Dataset that no longer exists is worse than no hash. Opening a Recording
+        /// does not verify it; [`SigMF::verify_sha512`] does, on request.
         #[serde(skip_serializing_if = "Option::is_none")]
         #[serde(rename = "core:sha512")]
         pub sha512: Option<String>,
@@ -1018,7 +1313,7 @@ mod sigmf {
     /// one frequency has a single segment; one that retunes or hops has a segment per
     /// change, and that is how a reader learns the centre frequency of any given
     /// sample. [`Metadata::capture_boundaries`] turns the array into byte ranges.
-    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
     pub struct CaptureMetadata {
         /// The sample index at which this segment takes effect.
         ///
@@ -1033,7 +1328,8 @@ mod sigmf {
         ///
         /// Absent means it is the same as `sample_start`. Present, it says samples
         /// were dropped or never captured before this point — a gap this Recording
-        /// cannot otherwise express.
+        /// cannot otherwise express. [`Metadata::global_index`] and
+        /// [`Metadata::dataset_index`] translate either way.
         #[serde(skip_serializing_if = "Option::is_none")]
         #[serde(rename = "core:global_index")]
         pub global_index: Option<u64>,
@@ -1131,7 +1427,7 @@ mod sigmf {
     /// need not agree with each other, and are not checked against the samples by
     /// this crate or by the format; [`generator`](Self::generator) exists precisely
     /// because a claim is worth only as much as its source.
-    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
     pub struct AnnotationMetadata {
         /// The sample index where the annotated feature begins.
         #[serde(rename = "core:sample_start")]
@@ -1379,7 +1675,8 @@ mod sigmf {
         /// Whether a reader may ignore this extension.
         ///
         /// `false` means an application MUST support the extension in order to
-        /// parse the Recording, and SHOULD report an error if it does not.
+        /// parse the Recording, and SHOULD report an error if it does not —
+        /// [`SigMF::from_file_supporting`] is that error.
         ///
         /// Read that direction carefully, because the specification states it both
         /// ways. The schema's description of *this property* says the inverse — "If
@@ -1412,6 +1709,63 @@ mod sigmf {
         }
     }
 
+    /// The extension namespaces a reader understands.
+    ///
+    /// Handed to [`SigMF::from_file_supporting`] or [`Metadata::check_extensions`],
+    /// which refuse a Recording that requires a namespace missing from it. Empty by
+    /// default, and that includes `antenna`: this crate shipping a type for a
+    /// namespace does not mean the caller does anything with it, and whether it
+    /// does is exactly the question a registry answers.
+    ///
+    /// Namespaces are matched by name alone. `core:extensions` also carries a
+    /// version, but the specification says nothing about how extension versions
+    /// relate, so there is no rule to enforce that would not be this crate's
+    /// invention.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sigmf::{AntennaGlobal, ExtensionRegistry};
+    ///
+    /// let registry = ExtensionRegistry::new()
+    ///     .with::<AntennaGlobal>()
+    ///     .with_namespace("capture_details");
+    /// assert!(registry.supports("antenna"));
+    /// assert!(!registry.supports("signal"));
+    /// ```
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct ExtensionRegistry {
+        namespaces: BTreeSet<String>,
+    }
+
+    impl ExtensionRegistry {
+        /// A registry that understands no extension at all.
+        pub fn new() -> ExtensionRegistry {
+            ExtensionRegistry::default()
+        }
+
+        /// Also understand the namespace `T` models.
+        pub fn with<T: GlobalExtension>(self) -> ExtensionRegistry {
+            self.with_namespace(T::namespace())
+        }
+
+        /// Also understand `namespace`, given without the colon — `antenna`, not
+        /// `antenna:`.
+        ///
+        /// For a namespace the caller handles without a [`GlobalExtension`] type,
+        /// which is the usual case: most extensions live in Captures segments and
+        /// annotations, where that trait does not reach.
+        pub fn with_namespace(mut self, namespace: impl Into<String>) -> ExtensionRegistry {
+            self.namespaces.insert(namespace.into());
+            self
+        }
+
+        /// Whether `namespace` is one this registry understands.
+        pub fn supports(&self, namespace: &str) -> bool {
+            self.namespaces.contains(namespace)
+        }
+    }
+
     /// The `antenna` extension's Global fields: what was on the end of the coax.
     ///
     /// The one extension this crate ships a type for, and the reference example of
@@ -1644,6 +1998,57 @@ mod sigmf {
             requested: DataFormat,
         },
 
+        /// A Dataset was asked to be rewritten as samples with a different number
+        /// of components.
+        #[error(
+            "cannot transcode `{from}` to `{to}`: one is real and the other complex, and \
+             there is no conversion between them that does not invent or discard a \
+             component"
+        )]
+        NumberTypeMismatch {
+            /// What the Recording's samples are.
+            from: DataFormat,
+            /// What they were asked to become.
+            to: DataFormat,
+        },
+
+        /// A Dataset's hash was asked to be checked, and the document records
+        /// none to check it against.
+        #[error("this Recording has no `core:sha512` to verify its Dataset against")]
+        NoSha512,
+
+        /// A Dataset is not the one its `core:sha512` was computed over.
+        #[error(
+            "the Dataset does not match `core:sha512`: the document records {recorded}, and \
+             the Dataset on disk hashes to {computed}"
+        )]
+        Sha512Mismatch {
+            /// The hash the document records.
+            recorded: String,
+            /// The hash of the Dataset as it is now.
+            computed: String,
+        },
+
+        /// A Recording's Metadata file is not the one its Collection recorded.
+        #[error(
+            "the Metadata file of `{name}` has changed since its Collection was made, and \
+             no longer matches the hash the Collection records for it"
+        )]
+        StreamHashMismatch {
+            /// The stream's name in the Collection.
+            name: String,
+        },
+
+        /// A Recording was asked to be written in another file format that has
+        /// no way to store its samples.
+        #[error("`{datatype}` samples have no equivalent in {format}")]
+        NoEquivalentFormat {
+            /// The Recording's datatype.
+            datatype: DataFormat,
+            /// The file format, by name.
+            format: &'static str,
+        },
+
         /// The samples of a Recording that has no Dataset file were asked for.
         #[error(
             "this Recording has no Dataset file: it is either `core:metadata_only`, or its \
@@ -1721,6 +2126,70 @@ mod sigmf {
             sample_start: u64,
         },
 
+        /// A Recording requires extensions the reader did not say it understands.
+        ///
+        /// Carries every such declaration from `core:extensions`, in the order the
+        /// document lists them.
+        #[error(
+            "the Recording requires extensions this reader does not support: {}. Each is \
+             declared with `optional: false`, which the specification defines as one a \
+             reader MUST understand to interpret the Recording", list_extensions(.0)
+        )]
+        UnsupportedRequiredExtensions(Vec<Extension>),
+
+        /// A span of samples was asked for that the Dataset does not hold.
+        #[error(
+            "samples {start}..{end} were asked for, but the Dataset holds {sample_count} \
+             samples"
+        )]
+        SampleRangeOutOfBounds {
+            /// First sample asked for.
+            start: u64,
+            /// One past the last sample asked for.
+            end: u64,
+            /// How many samples the Dataset holds.
+            sample_count: u64,
+        },
+
+        /// A sample count had to be converted to a time, and the Recording has no
+        /// `core:sample_rate` to do it with.
+        #[error(
+            "converting a number of samples to a time needs `core:sample_rate`, and this \
+             Recording does not state one"
+        )]
+        NoSampleRate,
+
+        /// A `core:datetime` had to be read, and is not the timestamp the
+        /// specification requires.
+        #[error(
+            "capture {index}: `core:datetime` is {datetime:?}, which is not an RFC 3339 \
+             timestamp in UTC such as `2026-07-16T09:14:22.000Z`"
+        )]
+        InvalidDatetime {
+            /// Position of the offending segment in the `captures` array.
+            index: usize,
+            /// The value as the document has it.
+            datetime: String,
+        },
+
+        /// Recordings to be joined into one disagree about a Global field, and a
+        /// Recording has only one Global to say it in.
+        #[error(
+            "recording {index} has `{key}` {found}, where the first has {expected}, and a \
+             joined Recording can say only one of them"
+        )]
+        ConflictingGlobals {
+            /// Position of the disagreeing Recording among those joined, counting
+            /// the first as 0.
+            index: usize,
+            /// The field, as the document spells it.
+            key: String,
+            /// The first Recording's value, as JSON, or `absent`.
+            expected: String,
+            /// The disagreeing Recording's value, as JSON, or `absent`.
+            found: String,
+        },
+
         /// An extension type could not be serialized into the Global object.
         #[error("extension data for the `{namespace}` namespace could not be serialized")]
         ExtensionNotSerializable {
@@ -2154,7 +2623,10 @@ mod sigmf {
         /// free to change: everything here is an implementation detail of the write
         /// path, and the public surface a caller needs is
         /// [`DataFormat::of`](super::DataFormat::of).
-        pub trait Sealed {
+        ///
+        /// `Send` and `Sync` so that a Dataset can be converted on several
+        /// threads at once, which every type here already is.
+        pub trait Sealed: Sized + Send + Sync {
             /// The `core:datatype` a Dataset of these samples carries.
             fn data_format(endianness: Endianness) -> DataFormat;
 
@@ -2177,6 +2649,37 @@ mod sigmf {
             ///
             /// If `bytes` is not exactly one sample wide.
             fn decode(endianness: Endianness, bytes: &[u8]) -> Self;
+
+            /// Write every one of `samples`, in `endianness`, into the start of
+            /// `out`, which is at least as long as their bytes; any bytes of
+            /// `out` beyond those are left as they were.
+            ///
+            /// [`encode`](Self::encode) over a whole buffer, for the paths that
+            /// convert a Dataset at once. The byte order is settled once rather
+            /// than per sample, leaving a loop of fixed-width copies that the
+            /// compiler turns into a `memcpy` when `endianness` is the host's and
+            /// into vectorised byte swaps when it is not — which is as close to
+            /// reinterpreting the slice as a crate that forbids `unsafe` gets.
+            ///
+            /// # Panics
+            ///
+            /// If `out` is shorter than the samples' bytes.
+            fn encode_slice(samples: &[Self], endianness: Endianness, out: &mut [u8]);
+
+            /// Append every sample in `bytes`, a whole number of them, to `out`:
+            /// [`decode`](Self::decode) over a whole buffer, and settled once in
+            /// the same way as [`encode_slice`](Self::encode_slice).
+            fn decode_slice(bytes: &[u8], endianness: Endianness, out: &mut Vec<Self>);
+
+            /// Overwrite `out` with the samples at the start of `bytes`, which
+            /// holds at least as many, the rest ignored:
+            /// [`decode_slice`](Self::decode_slice) for a buffer the caller
+            /// already owns.
+            ///
+            /// # Panics
+            ///
+            /// If `bytes` holds fewer samples than `out`.
+            fn decode_to_slice(bytes: &[u8], endianness: Endianness, out: &mut [Self]);
         }
     }
 
@@ -2212,6 +2715,54 @@ mod sigmf {
                         Endianness::BigEndian => Self::from_be_bytes(component),
                     }
                 }
+
+                fn encode_slice(samples: &[Self], endianness: Endianness, out: &mut [u8]) {
+                    let width = std::mem::size_of::<$component>();
+                    assert!(out.len() >= samples.len() * width, "`out` is too short");
+                    let out = out.chunks_exact_mut(width);
+                    match endianness {
+                        Endianness::LittleEndian => {
+                            for (sample, out) in samples.iter().zip(out) {
+                                out.copy_from_slice(&sample.to_le_bytes());
+                            }
+                        }
+                        Endianness::BigEndian => {
+                            for (sample, out) in samples.iter().zip(out) {
+                                out.copy_from_slice(&sample.to_be_bytes());
+                            }
+                        }
+                    }
+                }
+
+                fn decode_slice(bytes: &[u8], endianness: Endianness, out: &mut Vec<Self>) {
+                    let bytes = bytes
+                        .chunks_exact(std::mem::size_of::<$component>())
+                        .map(|bytes| bytes.try_into().expect("chunks of exactly one sample"));
+                    match endianness {
+                        Endianness::LittleEndian => out.extend(bytes.map(Self::from_le_bytes)),
+                        Endianness::BigEndian => out.extend(bytes.map(Self::from_be_bytes)),
+                    }
+                }
+
+                fn decode_to_slice(bytes: &[u8], endianness: Endianness, out: &mut [Self]) {
+                    let width = std::mem::size_of::<$component>();
+                    assert!(bytes.len() >= out.len() * width, "fewer samples than `out`");
+                    let bytes = bytes
+                        .chunks_exact(width)
+                        .map(|bytes| bytes.try_into().expect("chunks of exactly one sample"));
+                    match endianness {
+                        Endianness::LittleEndian => {
+                            for (sample, bytes) in out.iter_mut().zip(bytes) {
+                                *sample = Self::from_le_bytes(bytes);
+                            }
+                        }
+                        Endianness::BigEndian => {
+                            for (sample, bytes) in out.iter_mut().zip(bytes) {
+                                *sample = Self::from_be_bytes(bytes);
+                            }
+                        }
+                    }
+                }
             }
 
             impl Sample for $component {}
@@ -2237,6 +2788,84 @@ mod sigmf {
                         <$component as private::Sealed>::decode(endianness, im),
                     )
                 }
+
+                fn encode_slice(samples: &[Self], endianness: Endianness, out: &mut [u8]) {
+                    let width = std::mem::size_of::<$component>();
+                    assert!(out.len() >= samples.len() * 2 * width, "`out` is too short");
+                    let out = out.chunks_exact_mut(2 * width);
+                    match endianness {
+                        Endianness::LittleEndian => {
+                            for (sample, out) in samples.iter().zip(out) {
+                                out[..width].copy_from_slice(&sample.re.to_le_bytes());
+                                out[width..].copy_from_slice(&sample.im.to_le_bytes());
+                            }
+                        }
+                        Endianness::BigEndian => {
+                            for (sample, out) in samples.iter().zip(out) {
+                                out[..width].copy_from_slice(&sample.re.to_be_bytes());
+                                out[width..].copy_from_slice(&sample.im.to_be_bytes());
+                            }
+                        }
+                    }
+                }
+
+                fn decode_slice(bytes: &[u8], endianness: Endianness, out: &mut Vec<Self>) {
+                    let width = std::mem::size_of::<$component>();
+                    let pairs = bytes.chunks_exact(2 * width).map(|bytes| {
+                        let (re, im) = bytes.split_at(width);
+                        (
+                            re.try_into().expect("one component"),
+                            im.try_into().expect("one component"),
+                        )
+                    });
+                    match endianness {
+                        Endianness::LittleEndian => out.extend(pairs.map(|(re, im)| {
+                            Complex::new(
+                                <$component>::from_le_bytes(re),
+                                <$component>::from_le_bytes(im),
+                            )
+                        })),
+                        Endianness::BigEndian => out.extend(pairs.map(|(re, im)| {
+                            Complex::new(
+                                <$component>::from_be_bytes(re),
+                                <$component>::from_be_bytes(im),
+                            )
+                        })),
+                    }
+                }
+
+                fn decode_to_slice(bytes: &[u8], endianness: Endianness, out: &mut [Self]) {
+                    let width = std::mem::size_of::<$component>();
+                    assert!(
+                        bytes.len() >= out.len() * 2 * width,
+                        "fewer samples than `out`"
+                    );
+                    let pairs = bytes.chunks_exact(2 * width).map(|bytes| {
+                        let (re, im) = bytes.split_at(width);
+                        (
+                            re.try_into().expect("one component"),
+                            im.try_into().expect("one component"),
+                        )
+                    });
+                    match endianness {
+                        Endianness::LittleEndian => {
+                            for (sample, (re, im)) in out.iter_mut().zip(pairs) {
+                                *sample = Complex::new(
+                                    <$component>::from_le_bytes(re),
+                                    <$component>::from_le_bytes(im),
+                                );
+                            }
+                        }
+                        Endianness::BigEndian => {
+                            for (sample, (re, im)) in out.iter_mut().zip(pairs) {
+                                *sample = Complex::new(
+                                    <$component>::from_be_bytes(re),
+                                    <$component>::from_be_bytes(im),
+                                );
+                            }
+                        }
+                    }
+                }
             }
 
             impl Sample for Complex<$component> {}
@@ -0,0 +1,391 @@
+//! Which annotations cover which samples and frequencies, answered without a scan.
+
+use std::cmp::Ordering;
+use std::ops::{Range, RangeInclusive};
+
+use super::{AnnotationMetadata, Metadata};
+
+/// The annotations of one Recording, indexed by the samples and the band each
+/// one covers.
+///
+/// [`Metadata::annotations`] is a plain `Vec`, in whatever order its author
+/// wrote it, so "which annotations overlap these samples?" asked of it is a scan
+/// of every annotation, every time. A labelling tool asks that question on each
+/// redraw; this answers it from two interval trees, one over sample spans and
+/// one over frequency bands, built once.
+///
+/// # What an annotation covers
+///
+/// In time, `core:sample_start` up to `core:sample_start + core:sample_count`.
+/// An annotation without `core:sample_count` applies, in the specification's
+/// words, to the end of the Recording — which the Metadata cannot place on its
+/// own, and which is why [`new`](Self::new) asks for the Dataset's length in
+/// samples. [`SigMF::annotation_index`](super::SigMF::annotation_index)
+/// measures it for you.
+///
+/// In frequency, `core:freq_lower_edge` to `core:freq_upper_edge`, both edges
+/// included. An annotation that states no band makes no claim about frequency,
+/// so a query's band never excludes it: the index does not know it is outside
+/// the band, and guessing that it is would hide it from exactly the search that
+/// should turn it up. The schema requires both edges or neither; an annotation
+/// with only one is treated as stating neither.
+///
+/// Every query returns annotations in document order, whatever order the trees
+/// found them in.
+///
+/// # Examples
+///
+/// ```
+/// use sigmf::{AnnotationIndex, Metadata};
+///
+/// let metadata: Metadata = serde_json::from_str(r#"{
+///     "global": { "core:datatype": "cf32_le", "core:version": "1.2.6" },
+///     "captures": [],
+///     "annotations": [
+///         { "core:sample_start": 0, "core:sample_count": 100,
+///           "core:freq_lower_edge": 96.9e6, "core:freq_upper_edge": 97.1e6,
+///           "core:label": "broadcast FM" },
+///         { "core:sample_start": 500,
+///           "core:freq_lower_edge": 433.8e6, "core:freq_upper_edge": 434.0e6,
+///           "core:label": "ISM" }
+///     ]
+/// }"#)?;
+///
+/// // 1000 samples in the Dataset, so the ISM annotation runs from 500 to 1000.
+/// let index = AnnotationIndex::new(&metadata, 1000);
+///
+/// let fm = index.overlapping(50..600, 88e6..=108e6);
+/// assert_eq!(fm.len(), 1);
+/// assert_eq!(fm[0].label.as_deref(), Some("broadcast FM"));
+///
+/// let late = index.overlapping_samples(900..950);
+/// assert_eq!(late[0].label.as_deref(), Some("ISM"));
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct AnnotationIndex<'a> {
+    annotations: &'a [AnnotationMetadata],
+
+    /// Where an annotation without `core:sample_count` ends: the index one
+    /// past the Dataset's last sample.
+    end: u64,
+
+    /// The sample span of every annotation covering at least one sample, as
+    /// closed intervals.
+    samples: IntervalTree<u64>,
+
+    /// The band of every annotation that states one.
+    band: IntervalTree<f64>,
+
+    /// Annotations stating no band, which every band query includes.
+    unbanded: Vec<usize>,
+}
+
+impl<'a> AnnotationIndex<'a> {
+    /// Index `metadata`'s annotations, for a Dataset `dataset_samples` samples
+    /// long.
+    ///
+    /// An annotation without `core:sample_count` ends with the Dataset;
+    /// [`Metadata::sample_count`] computes `dataset_samples` from the Dataset's
+    /// length in bytes. An annotation covering no samples — a
+    /// `core:sample_count` of 0, or an open-ended one starting past the end of
+    /// the Dataset — is in no sample query's answer, but still in band
+    /// queries'.
+    ///
+    /// Queries ask in the document's own sample indices, which are absolute:
+    /// the Dataset's first sample is index [`GlobalMetadata::offset`], not 0.
+    ///
+    /// [`GlobalMetadata::offset`]: super::GlobalMetadata::offset
+    pub fn new(metadata: &'a Metadata, dataset_samples: u64) -> AnnotationIndex<'a> {
+        let annotations = &metadata.annotations[..];
+        let end = metadata
+            .global
+            .offset
+            .unwrap_or(0)
+            .saturating_add(dataset_samples);
+
+        let mut spans = Vec::with_capacity(annotations.len());
+        let mut bands = Vec::with_capacity(annotations.len());
+        let mut unbanded = Vec::new();
+        for (id, annotation) in annotations.iter().enumerate() {
+            let span = sample_span(annotation, end);
+            // Half-open to closed, so that both trees share one overlap rule. An
+            // empty span has no last sample and so no interval.
+            if span.start < span.end {
+                spans.push((span.start, span.end - 1, id));
+            }
+            match band_of(annotation) {
+                Some(band) => bands.push((*band.start(), *band.end(), id)),
+                None => unbanded.push(id),
+            }
+        }
+
+        AnnotationIndex {
+            annotations,
+            end,
+            samples: IntervalTree::new(spans),
+            band: IntervalTree::new(bands),
+            unbanded,
+        }
+    }
+
+    /// Every annotation covering at least one sample of `samples` and at least
+    /// one frequency of `band`.
+    pub fn overlapping(
+        &self,
+        samples: Range<u64>,
+        band: RangeInclusive<f64>,
+    ) -> Vec<&'a AnnotationMetadata> {
+        let mut found = self.ids_overlapping_samples(samples);
+        found.retain(|&id| {
+            band_of(&self.annotations[id])
+                .is_none_or(|edges| *edges.start() <= *band.end() && *band.start() <= *edges.end())
+        });
+        self.in_document_order(found)
+    }
+
+    /// Every annotation covering at least one sample of `samples`, at any
+    /// frequency.
+    pub fn overlapping_samples(&self, samples: Range<u64>) -> Vec<&'a AnnotationMetadata> {
+        let found = self.ids_overlapping_samples(samples);
+        self.in_document_order(found)
+    }
+
+    /// Every annotation whose band shares at least one frequency with `band`,
+    /// anywhere in the Recording — including those that state no band at all.
+    pub fn overlapping_band(&self, band: RangeInclusive<f64>) -> Vec<&'a AnnotationMetadata> {
+        let mut found = self.unbanded.clone();
+        if band.start() <= band.end() {
+            self.band
+                .overlapping(*band.start(), *band.end(), |id| found.push(id));
+        }
+        self.in_document_order(found)
+    }
+
+    /// Every annotation lying entirely inside `samples` and `band`.
+    ///
+    /// The question a tool asks when the user drags a box: not "what does this
+    /// touch?" but "what did I draw round?". An annotation covering no samples
+    /// is inside nothing.
+    pub fn within(
+        &self,
+        samples: Range<u64>,
+        band: RangeInclusive<f64>,
+    ) -> Vec<&'a AnnotationMetadata> {
+        let mut found = self.ids_overlapping_samples(samples.clone());
+        found.retain(|&id| {
+            let annotation = &self.annotations[id];
+            let span = sample_span(annotation, self.end);
+            let in_samples = samples.start <= span.start && span.end <= samples.end;
+            let in_band = band_of(annotation)
+                .is_none_or(|edges| band.start() <= edges.start() && edges.end() <= band.end());
+            in_samples && in_band
+        });
+        self.in_document_order(found)
+    }
+
+    /// The annotation closest to `sample`: one covering it if there is one,
+    /// otherwise the one ending or starting fewest samples away.
+    ///
+    /// Ties go to the annotation listed first, so the answer depends on the
+    /// document and not on how the index happened to be built. `None` only if no
+    /// annotation covers any sample at all.
+    pub fn nearest(&self, sample: u64) -> Option<&'a AnnotationMetadata> {
+        let mut covering = Vec::new();
+        self.samples
+            .overlapping(sample, sample, |id| covering.push(id));
+        if let Some(&id) = covering.iter().min() {
+            return Some(&self.annotations[id]);
+        }
+
+        let before = self.samples.last_ending_before(sample);
+        let after = self.samples.first_starting_after(sample);
+        let nearest = match (before, after) {
+            (Some((last, before)), Some((first, after))) => {
+                let (to_before, to_after) = (sample - last, first - sample);
+                if to_before < to_after || (to_before == to_after && before < after) {
+                    before
+                } else {
+                    after
+                }
+            }
+            (Some((_, id)), None) | (None, Some((_, id))) => id,
+            (None, None) => return None,
+        };
+        Some(&self.annotations[nearest])
+    }
+
+    fn ids_overlapping_samples(&self, samples: Range<u64>) -> Vec<usize> {
+        let mut found = Vec::new();
+        if samples.start < samples.end {
+            self.samples
+                .overlapping(samples.start, samples.end - 1, |id| found.push(id));
+        }
+        found
+    }
+
+    fn in_document_order(&self, mut ids: Vec<usize>) -> Vec<&'a AnnotationMetadata> {
+        ids.sort_unstable();
+        ids.dedup();
+        ids.into_iter().map(|id| &self.annotations[id]).collect()
+    }
+}
+
+/// The samples an annotation covers, as a half-open range.
+///
+/// Saturating rather than checked: a `core:sample_count` that overflows the
+/// index space runs to the end of it, which is as far as any query can ask.
+fn sample_span(annotation: &AnnotationMetadata, recording_end: u64) -> Range<u64> {
+    let start = annotation.sample_start;
+    let end = match annotation.sample_count {
+        Some(count) => start.saturating_add(count),
+        None => recording_end.max(start),
+    };
+    start..end
+}
+
+/// The band an annotation states, lower edge first, or `None` if it states
+/// none — which includes stating only one edge, a shape the schema forbids.
+fn band_of(annotation: &AnnotationMetadata) -> Option<RangeInclusive<f64>> {
+    match (annotation.freq_lower_edge, annotation.freq_upper_edge) {
+        // `min`/`max` rather than trusting the order: an inverted band is still
+        // a band, and the alternative is an interval that overlaps nothing.
+        (Some(lower), Some(upper)) => Some(lower.min(upper)..=lower.max(upper)),
+        _ => None,
+    }
+}
+
+/// A static interval tree over closed intervals, each tagged with an id.
+///
+/// Built once and never modified, which is what allows the tree to be implicit:
+/// the intervals are sorted by their low end, the node for `nodes[l..r]` is the
+/// middle one, and each node records the highest high end beneath it so a query
+/// can discard a subtree that ends before the query begins.
+#[derive(Debug)]
+struct IntervalTree<T> {
+    nodes: Vec<Node<T>>,
+
+    /// `prefix_max[i]` is the highest high end among `nodes[..=i]`, and the id
+    /// of the first interval listed that reaches it.
+    prefix_max: Vec<(T, usize)>,
+}
+
+#[derive(Debug)]
+struct Node<T> {
+    low: T,
+    high: T,
+    subtree_high: T,
+    id: usize,
+}
+
+/// An interval end, ordered totally: a NaN a caller wrote into an annotation
+/// sorts above every frequency, and is compared the same way by the sort,
+/// the maxima and the queries, rather than leaving the order of everything
+/// around it up to each of them.
+trait Bound: Copy {
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+impl Bound for u64 {
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.cmp(other)
+    }
+}
+
+impl Bound for f64 {
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+}
+
+impl<T: Bound> IntervalTree<T> {
+    fn new(mut intervals: Vec<(T, T, usize)>) -> IntervalTree<T> {
+        // Ties broken by id, so the answer to `first_starting_after` is the
+        // first-listed of several intervals starting together.
+        intervals.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.2.cmp(&b.2)));
+        let mut nodes: Vec<Node<T>> = intervals
+            .into_iter()
+            .map(|(low, high, id)| Node {
+                low,
+                high,
+                subtree_high: high,
+                id,
+            })
+            .collect();
+        augment(&mut nodes);
+
+        let mut prefix_max: Vec<(T, usize)> = Vec::with_capacity(nodes.len());
+        for node in &nodes {
+            let best = match prefix_max.last() {
+                Some(&(high, id)) if high.total_cmp(&node.high).then(node.id.cmp(&id)).is_gt() => {
+                    (high, id)
+                }
+                _ => (node.high, node.id),
+            };
+            prefix_max.push(best);
+        }
+
+        IntervalTree { nodes, prefix_max }
+    }
+
+    /// Call `visit` with the id of every interval sharing a point with
+    /// `low..=high`.
+    fn overlapping(&self, low: T, high: T, mut visit: impl FnMut(usize)) {
+        self.visit(&self.nodes, low, high, &mut visit);
+    }
+
+    fn visit(&self, nodes: &[Node<T>], low: T, high: T, visit: &mut impl FnMut(usize)) {
+        if nodes.is_empty() {
+            return;
+        }
+        let mid = nodes.len() / 2;
+        let node = &nodes[mid];
+        if node.subtree_high.total_cmp(&low).is_lt() {
+            return;
+        }
+        self.visit(&nodes[..mid], low, high, visit);
+        // Sorted by low end: if this one starts after the query, so does every
+        // interval to its right.
+        if node.low.total_cmp(&high).is_gt() {
+            return;
+        }
+        if node.high.total_cmp(&low).is_ge() {
+            visit(node.id);
+        }
+        self.visit(&nodes[mid + 1..], low, high, visit);
+    }
+
+    /// Among intervals starting at or before `point`, the highest high end and
+    /// the interval reaching it — which, when nothing covers `point`, is the
+    /// interval ending closest before it.
+    fn last_ending_before(&self, point: T) -> Option<(T, usize)> {
+        let starting_by = self
+            .nodes
+            .partition_point(|node| node.low.total_cmp(&point).is_le());
+        starting_by.checked_sub(1).map(|last| self.prefix_max[last])
+    }
+
+    /// The first-listed of the intervals starting soonest after `point`.
+    fn first_starting_after(&self, point: T) -> Option<(T, usize)> {
+        let first = self
+            .nodes
+            .partition_point(|node| node.low.total_cmp(&point).is_le());
+        self.nodes.get(first).map(|node| (node.low, node.id))
+    }
+}
+
+/// Fill in each node's `subtree_high`, bottom up over the implicit tree.
+fn augment<T: Bound>(nodes: &mut [Node<T>]) -> Option<T> {
+    if nodes.is_empty() {
+        return None;
+    }
+    let mid = nodes.len() / 2;
+    let (left, rest) = nodes.split_at_mut(mid);
+    let (node, right) = rest.split_first_mut().expect("mid is in bounds");
+    for high in [augment(left), augment(right)].into_iter().flatten() {
+        if high.total_cmp(&node.subtree_high).is_gt() {
+            node.subtree_high = high;
+        }
+    }
+    Some(node.subtree_high)
+}
@@ -0,0 +1,402 @@
+//! Annotations as tables: CSV for a spreadsheet, JSON Lines for a pipeline.
+//!
+//! Both formats hold one annotation per row, and both carry every field the
+//! annotation has — the `core` ones and every extension key in
+//! [`other`](AnnotationMetadata::other) — so that an exported file edited and
+//! imported again loses nothing it did not delete. What an annotation does not
+//! say but the document around it does is added as four derived fields, for
+//! the reader who wants times and frequencies rather than sample indices:
+//!
+//! | Field | Derived from |
+//! |---|---|
+//! | `start_time` | the Captures segment holding `core:sample_start`: its `core:datetime`, advanced by the samples between, at `core:sample_rate` |
+//! | `end_time` | likewise for `core:sample_start` plus `core:sample_count` — the instant just after the last sample |
+//! | `lower_frequency` | `core:freq_lower_edge`, or, for an annotation of the whole band of a complex Recording, that segment's `core:frequency` less half `core:sample_rate` |
+//! | `upper_frequency` | `core:freq_upper_edge`, or that `core:frequency` plus half `core:sample_rate` |
+//!
+//! A derived field is left empty where its inputs are missing. Their names
+//! have no namespace — the specification namespaces every key it or an
+//! extension defines — so they cannot collide with a field, and the importers
+//! drop them: they are a view of the document, not part of it.
+//!
+//! # CSV
+//!
+//! RFC 4180, with a header row. The columns are the eight `core` fields, the
+//! four derived ones, then one per extension key any annotation carries,
+//! sorted. A `core` cell is its field's value as text, and an empty cell an
+//! absent field. An extension cell is its value as JSON unless the value is a
+//! string that would not read back as JSON, which is written as it is — so a
+//! label a labeller types into an extension column arrives as a string, and
+//! `"42"`, written with its quotes, stays one.
+//!
+//! # JSON Lines
+//!
+//! One JSON object per line: the annotation as it appears in a Metadata file,
+//! with the derived fields added.
+
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use super::datetime::Timestamp;
+use super::{
+    at, finish_file, invalid, AnnotationMetadata, CaptureMetadata, Error, Metadata, NumberType,
+};
+
+/// The `core` fields of an annotation, in the order the CSV columns take.
+const CORE: [&str; 8] = [
+    "core:sample_start",
+    "core:sample_count",
+    "core:freq_lower_edge",
+    "core:freq_upper_edge",
+    "core:label",
+    "core:generator",
+    "core:comment",
+    "core:uuid",
+];
+
+/// The derived fields, in the order the CSV columns take.
+const DERIVED: [&str; 4] = [
+    "start_time",
+    "end_time",
+    "lower_frequency",
+    "upper_frequency",
+];
+
+/// Write the annotations of `metadata` to the CSV file `csv`.
+///
+/// # Errors
+///
+/// [`Error::Io`] if `csv` cannot be written.
+///
+/// # Examples
+///
+/// ```
+/// use serde_json::json;
+/// use sigmf::{annotations, CaptureMetadata, GlobalMetadata, Metadata};
+/// # let dir = tempfile::tempdir().expect("a temporary directory");
+///
+/// let mut global = GlobalMetadata::describing("cf32_le".parse()?);
+/// global.sample_rate = Some(32_000.0);
+/// let mut capture = CaptureMetadata::new(0);
+/// capture.datetime = Some("2026-07-16T09:14:22.000Z".to_string());
+/// let call = serde_json::from_value(json!({
+///     "core:sample_start": 16_000,
+///     "core:sample_count": 8_000,
+///     "core:label": "DSC distress",
+/// }))?;
+/// let metadata = Metadata { global, captures: vec![capture], annotations: vec![call] };
+///
+/// annotations::export_csv(&metadata, dir.path().join("calls.csv"))?;
+/// let csv = std::fs::read_to_string(dir.path().join("calls.csv"))?;
+/// assert!(csv.lines().nth(1).unwrap().contains("DSC distress,,,,2026-07-16T09:14:22.500Z"));
+///
+/// let back = annotations::import_csv(dir.path().join("calls.csv"))?;
+/// assert_eq!(back, metadata.annotations);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn export_csv<P: AsRef<Path>>(metadata: &Metadata, csv: P) -> Result<(), Error> {
+    let path = csv.as_ref();
+    let extensions: Vec<&String> = metadata
+        .annotations
+        .iter()
+        .flat_map(|annotation| annotation.other.keys())
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut out = BufWriter::new(fs::File::create(path).map_err(at(path))?);
+    let header = CORE
+        .iter()
+        .chain(&DERIVED)
+        .copied()
+        .chain(extensions.iter().map(|key| key.as_str()));
+    write_row(&mut out, header).map_err(at(path))?;
+
+    for annotation in &metadata.annotations {
+        let core = [
+            Some(annotation.sample_start.to_string()),
+            annotation.sample_count.map(|count| count.to_string()),
+            annotation.freq_lower_edge.map(|edge| edge.to_string()),
+            annotation.freq_upper_edge.map(|edge| edge.to_string()),
+            annotation.label.clone(),
+            annotation.generator.clone(),
+            annotation.comment.clone(),
+            annotation.uuid.clone(),
+        ];
+        let derived = derived(metadata, annotation).map(|value| {
+            value.map(|v| match v {
+                Value::String(text) => text,
+                other => other.to_string(),
+            })
+        });
+        let other = extensions
+            .iter()
+            .map(|key| annotation.other.get(*key).map(extension_cell));
+        let cells: Vec<String> = core
+            .into_iter()
+            .chain(derived)
+            .chain(other)
+            .map(Option::unwrap_or_default)
+            .collect();
+        write_row(&mut out, cells.iter().map(String::as_str)).map_err(at(path))?;
+    }
+    finish_file(out, path)
+}
+
+/// Read the annotations in the CSV file `csv`, as [`export_csv`] writes them.
+///
+/// Columns are found by the header row's names, in any order, and only
+/// `core:sample_start` must be present. A column that is neither a `core`
+/// field nor a derived one is an extension key, and its non-empty cells are
+/// kept in [`other`](AnnotationMetadata::other).
+///
+/// # Errors
+///
+/// [`Error::Io`] naming `csv` if it cannot be read, has no header row or a
+/// row of another length, or has a `core` cell that is not its field's type.
+pub fn import_csv<P: AsRef<Path>>(csv: P) -> Result<Vec<AnnotationMetadata>, Error> {
+    let path = csv.as_ref();
+    let text = fs::read_to_string(path).map_err(at(path))?;
+    let mut rows = parse_csv(&text).map_err(|message| invalid(path, &message))?;
+    if rows.is_empty() {
+        return Err(invalid(path, "a CSV file with no header row"));
+    }
+    let header = rows.remove(0);
+    if !header.iter().any(|name| name == "core:sample_start") {
+        return Err(invalid(
+            path,
+            "a CSV file with no `core:sample_start` column",
+        ));
+    }
+
+    rows.into_iter()
+        .enumerate()
+        .filter(|(_, row)| row != &[""])
+        .map(|(index, row)| {
+            // Counted as a spreadsheet counts them, the header being row 1.
+            let line = index + 2;
+            if row.len() != header.len() {
+                return Err(invalid(
+                    path,
+                    &format!(
+                        "row {line} has {} cells, where the header has {}",
+                        row.len(),
+                        header.len()
+                    ),
+                ));
+            }
+            let mut object = Map::new();
+            for (name, cell) in header.iter().zip(row) {
+                if cell.is_empty() || DERIVED.contains(&name.as_str()) {
+                    continue;
+                }
+                let value = match name.as_str() {
+                    "core:sample_start" | "core:sample_count" => cell
+                        .trim()
+                        .parse::<u64>()
+                        .map(Value::from)
+                        .map_err(|_| cell_error(path, line, name, &cell))?,
+                    "core:freq_lower_edge" | "core:freq_upper_edge" => cell
+                        .trim()
+                        .parse::<f64>()
+                        .map(Value::from)
+                        .map_err(|_| cell_error(path, line, name, &cell))?,
+                    name if CORE.contains(&name) => Value::String(cell),
+                    _ => serde_json::from_str(&cell).unwrap_or(Value::String(cell)),
+                };
+                object.insert(name.clone(), value);
+            }
+            annotation(object, path, &format!("row {line}"))
+        })
+        .collect()
+}
+
+/// Write the annotations of `metadata` to the JSON Lines file `jsonl`.
+///
+/// # Errors
+///
+/// [`Error::Io`] if `jsonl` cannot be written, or [`Error::Json`] if an
+/// annotation cannot be serialized.
+pub fn export_jsonl<P: AsRef<Path>>(metadata: &Metadata, jsonl: P) -> Result<(), Error> {
+    let path = jsonl.as_ref();
+    let mut out = BufWriter::new(fs::File::create(path).map_err(at(path))?);
+    for annotation in &metadata.annotations {
+        let Value::Object(mut object) = serde_json::to_value(annotation)? else {
+            unreachable!("an annotation serializes as an object");
+        };
+        for (name, value) in DERIVED.iter().zip(derived(metadata, annotation)) {
+            if let Some(value) = value {
+                object.insert(name.to_string(), value);
+            }
+        }
+        serde_json::to_writer(&mut out, &object)?;
+        out.write_all(b"\n").map_err(at(path))?;
+    }
+    finish_file(out, path)
+}
+
+/// Read the annotations in the JSON Lines file `jsonl`, as [`export_jsonl`]
+/// writes them. Blank lines are skipped.
+///
+/// # Errors
+///
+/// [`Error::Io`] naming `jsonl` if it cannot be read, or has a line that is
+/// not a JSON object or not an annotation.
+pub fn import_jsonl<P: AsRef<Path>>(jsonl: P) -> Result<Vec<AnnotationMetadata>, Error> {
+    let path = jsonl.as_ref();
+    let text = fs::read_to_string(path).map_err(at(path))?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let line_number = index + 1;
+            match serde_json::from_str(line) {
+                Ok(Value::Object(mut object)) => {
+                    for name in DERIVED {
+                        object.remove(name);
+                    }
+                    annotation(object, path, &format!("line {line_number}"))
+                }
+                _ => Err(invalid(
+                    path,
+                    &format!("line {line_number} is not a JSON object"),
+                )),
+            }
+        })
+        .collect()
+}
+
+/// The derived fields of `annotation`, in [`DERIVED`]'s order.
+fn derived(metadata: &Metadata, annotation: &AnnotationMetadata) -> [Option<Value>; 4] {
+    let rate = metadata.global.sample_rate;
+    // The segment holding the annotation's first sample: the last to start at
+    // or before it.
+    let capture: Option<&CaptureMetadata> = metadata
+        .captures
+        .iter()
+        .rev()
+        .find(|capture| capture.sample_start <= annotation.sample_start);
+    let time_of = |sample: u64| -> Option<Value> {
+        let capture = capture?;
+        let start = Timestamp::parse(capture.datetime.as_deref()?)?;
+        let rate = rate?;
+        let seconds = (sample - capture.sample_start) as f64 / rate;
+        Some(Value::from(start.add_seconds(seconds).to_string()))
+    };
+
+    let whole_band = annotation.freq_lower_edge.is_none() && annotation.freq_upper_edge.is_none();
+    let band_edge = |sign: f64| -> Option<Value> {
+        let centre = capture?.frequency?;
+        let rate = rate?;
+        (whole_band && metadata.global.datatype.number_type == NumberType::Complex)
+            .then(|| Value::from(centre + sign * rate / 2.0))
+    };
+
+    [
+        time_of(annotation.sample_start),
+        annotation
+            .sample_count
+            .and_then(|count| time_of(annotation.sample_start.saturating_add(count))),
+        annotation
+            .freq_lower_edge
+            .map(Value::from)
+            .or_else(|| band_edge(-1.0)),
+        annotation
+            .freq_upper_edge
+            .map(Value::from)
+            .or_else(|| band_edge(1.0)),
+    ]
+}
+
+/// An extension's value as a CSV cell: a string as it is, unless it would
+/// read back as some other JSON value, and anything else as JSON.
+fn extension_cell(value: &Value) -> String {
+    match value {
+        Value::String(text) if serde_json::from_str::<Value>(text).is_err() => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// The annotation `object` describes, read from `place` — a row or a line —
+/// of `path`.
+fn annotation(
+    object: Map<String, Value>,
+    path: &Path,
+    place: &str,
+) -> Result<AnnotationMetadata, Error> {
+    serde_json::from_value(Value::Object(object))
+        .map_err(|source| invalid(path, &format!("{place} is not an annotation: {source}")))
+}
+
+fn cell_error(path: &Path, line: usize, name: &str, cell: &str) -> Error {
+    invalid(
+        path,
+        &format!("row {line} has `{cell}` for `{name}`, which is not a number"),
+    )
+}
+
+/// Write one CSV record, quoting the cells that need it, ended by CRLF.
+fn write_row<'a>(
+    out: &mut impl Write,
+    cells: impl Iterator<Item = &'a str>,
+) -> std::io::Result<()> {
+    for (index, cell) in cells.enumerate() {
+        if index > 0 {
+            out.write_all(b",")?;
+        }
+        if cell.contains([',', '"', '\r', '\n']) {
+            write!(out, "\"{}\"", cell.replace('"', "\"\""))?;
+        } else {
+            out.write_all(cell.as_bytes())?;
+        }
+    }
+    out.write_all(b"\r\n")
+}
+
+/// The records of an RFC 4180 CSV file, with CRLF or LF line endings. A
+/// quoted cell may hold commas, line breaks and doubled quotes.
+fn parse_csv(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut chars = text.chars().peekable();
+    let mut quoted = false;
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            (true, '"') => quoted = false,
+            (true, c) => {
+                if c == '\n' {
+                    line += 1;
+                }
+                cell.push(c);
+            }
+            (false, '"') if cell.is_empty() => quoted = true,
+            (false, '"') => return Err(format!("line {line} has a quote inside an unquoted cell")),
+            (false, ',') => row.push(std::mem::take(&mut cell)),
+            (false, '\r') if chars.peek() == Some(&'\n') => {}
+            (false, '\n') => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+                line += 1;
+            }
+            (false, c) => cell.push(c),
+        }
+    }
+    if quoted {
+        return Err("the file ends inside a quoted cell".to_string());
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+    Ok(rows)
+}
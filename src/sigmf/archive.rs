@@ -0,0 +1,445 @@
+//! SigMF Archives: Recordings bundled into one `.sigmf` file.
+//!
+//! An Archive is a tar file holding a single directory, named for the Archive,
+//! that holds the Metadata and Dataset files of one or more Recordings. It is
+//! the shape for moving Recordings about — one file to copy, one file to lose —
+//! and not for reading samples from, which is why [`unpack`] extracts rather
+//! than opening in place.
+//!
+//! The tar handling is this module's own, and deliberately narrow: it writes
+//! POSIX ustar with the GNU extension for members of 8 GiB and more, and reads
+//! that plus the PAX `path` and `size` records that Python's `tarfile` — what
+//! the reference implementation packs with — emits for long names. Links,
+//! devices and the rest of tar's vocabulary have no place in an Archive, and
+//! are refused rather than half-supported.
+
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use super::{at, finish_file, invalid, Error, SigMF};
+use crate::{SIGMF_ARCHIVE_EXT, SIGMF_METADATA_EXT};
+
+/// Tar's unit of everything.
+const BLOCK: usize = 512;
+
+/// Write the Recordings whose Metadata files are `recordings` into a new
+/// Archive at `archive`.
+///
+/// The directory inside is named for the Archive, less its `.sigmf`
+/// extension, as the specification requires. Each Recording contributes its
+/// Metadata file and its Dataset — a Non-Conforming one under its own name —
+/// copied byte for byte, so every `core:sha512` still holds inside.
+///
+/// # Errors
+///
+/// Any error of [`SigMF::from_file`] for a Recording, or [`Error::Io`]: for a
+/// file that cannot be read or written, for two Recordings whose files share
+/// a name, or for a name too long for a tar header.
+///
+/// # Examples
+///
+/// ```
+/// use sigmf::{archive, RecordingWriter};
+/// # let dir = tempfile::tempdir().expect("a temporary directory");
+///
+/// RecordingWriter::new(&[1.0f32, 2.0], 32_000.0).to_file(dir.path().join("watch"))?;
+/// archive::pack(
+///     dir.path().join("walvisbay.sigmf"),
+///     &[dir.path().join("watch.sigmf-meta")],
+/// )?;
+///
+/// let unpacked = archive::unpack(dir.path().join("walvisbay.sigmf"), dir.path().join("out"))?;
+/// assert_eq!(unpacked[0].samples::<f32>()?, [1.0, 2.0]);
+/// assert!(dir.path().join("out/walvisbay/watch.sigmf-data").exists());
+/// # Ok::<(), sigmf::Error>(())
+/// ```
+pub fn pack<P: AsRef<Path>, Q: AsRef<Path>>(archive: P, recordings: &[Q]) -> Result<(), Error> {
+    let archive = archive.as_ref();
+    let directory = archive
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .map(|name| {
+            name.strip_suffix(SIGMF_ARCHIVE_EXT)
+                .unwrap_or(&name)
+                .to_string()
+        })
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| {
+            invalid(
+                archive,
+                "an Archive needs a file name to name its directory",
+            )
+        })?;
+
+    let mut members: Vec<(String, PathBuf)> = Vec::new();
+    for metadata_path in recordings {
+        let metadata_path = metadata_path.as_ref();
+        let recording = SigMF::from_file(metadata_path)?;
+        let files = std::iter::once(metadata_path).chain(recording.datafile.as_deref());
+        for file in files {
+            let name = file
+                .file_name()
+                .ok_or_else(|| invalid(file, "not a file"))?
+                .to_string_lossy()
+                .into_owned();
+            if members.iter().any(|(existing, _)| *existing == name) {
+                return Err(invalid(
+                    file,
+                    "another Recording in this Archive has a file of the same name",
+                ));
+            }
+            members.push((name, file.to_path_buf()));
+        }
+    }
+
+    let out = fs::File::create(archive).map_err(at(archive))?;
+    let mut out = BufWriter::new(out);
+
+    let header = Header::new(&format!("{directory}/"), 0, b'5', 0o755, 0)
+        .ok_or_else(|| invalid(archive, "the Archive's name is too long for a tar header"))?;
+    out.write_all(&header.0).map_err(at(archive))?;
+
+    for (name, path) in &members {
+        let mut file = fs::File::open(path).map_err(at(path))?;
+        let metadata = file.metadata().map_err(at(path))?;
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_secs());
+        let header = Header::new(
+            &format!("{directory}/{name}"),
+            metadata.len(),
+            b'0',
+            0o644,
+            mtime,
+        )
+        .ok_or_else(|| invalid(path, "the name is too long for a tar header"))?;
+        out.write_all(&header.0).map_err(at(archive))?;
+
+        let copied = io::copy(&mut (&mut file).take(metadata.len()), &mut out).map_err(at(path))?;
+        if copied != metadata.len() {
+            return Err(invalid(path, "the file shrank while it was being archived"));
+        }
+        out.write_all(&[0; BLOCK][..padding(copied)])
+            .map_err(at(archive))?;
+    }
+
+    // Two empty blocks end an archive.
+    out.write_all(&[0; 2 * BLOCK]).map_err(at(archive))?;
+    finish_file(out, archive)
+}
+
+/// Extract the Archive at `archive` into `directory`, and open every Recording
+/// it held, in the order they were archived.
+///
+/// Files land at their paths within the Archive, so a conforming Archive
+/// `walvisbay.sigmf` unpacks into `directory/walvisbay/`. Nothing is
+/// overwritten: a file that already exists is an error, as is a member whose
+/// path is absolute or climbs out of `directory` with `..` — an Archive is a
+/// file from elsewhere, and its paths are not to be trusted.
+///
+/// # Errors
+///
+/// [`Error::Io`] for a file that cannot be read or written, a member that is
+/// not a regular file or directory, or an Archive that is not a tar file;
+/// then any error of [`SigMF::from_file`] opening what was extracted.
+pub fn unpack<P: AsRef<Path>, Q: AsRef<Path>>(
+    archive: P,
+    directory: Q,
+) -> Result<Vec<SigMF>, Error> {
+    let archive = archive.as_ref();
+    let directory = directory.as_ref();
+
+    let mut metadata_files = Vec::new();
+    read_members(archive, |member, data| {
+        let target = directory.join(safe_path(&member.name).ok_or_else(|| {
+            invalid(
+                archive,
+                &format!(
+                    "{} would be extracted outside the target directory",
+                    member.name
+                ),
+            )
+        })?);
+        if member.directory {
+            return fs::create_dir_all(&target).map_err(at(&target));
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(at(parent))?;
+        }
+        let out = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&target)
+            .map_err(at(&target))?;
+        let mut out = BufWriter::new(out);
+        let copied = io::copy(data, &mut out).map_err(at(&target))?;
+        if copied != member.size {
+            return Err(invalid(archive, "the Archive ends partway through a file"));
+        }
+        finish_file(out, &target)?;
+
+        if member.name.ends_with(SIGMF_METADATA_EXT) {
+            metadata_files.push(target);
+        }
+        Ok(())
+    })?;
+
+    metadata_files.iter().map(SigMF::from_file).collect()
+}
+
+/// One file or directory in an Archive, as its header describes it.
+pub(super) struct Member {
+    /// The member's path within the Archive, as recorded.
+    pub(super) name: String,
+    /// How many bytes of data it has.
+    pub(super) size: u64,
+    /// Whether it is a directory rather than a file.
+    pub(super) directory: bool,
+}
+
+/// Walk the members of the Archive at `archive` in order, handing `visit`
+/// each one and a reader over its data.
+///
+/// `visit` may read as much or as little of the data as it likes; what it
+/// leaves is skipped. PAX headers are applied to the member they describe
+/// rather than visited, and a member of any type other than a file or a
+/// directory is refused.
+pub(super) fn read_members(
+    archive: &Path,
+    mut visit: impl FnMut(&Member, &mut dyn Read) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let file = fs::File::open(archive).map_err(at(archive))?;
+    let mut input = BufReader::new(file);
+
+    let mut pax = Pax::default();
+    let mut block = [0u8; BLOCK];
+    loop {
+        input.read_exact(&mut block).map_err(at(archive))?;
+        if block.iter().all(|&b| b == 0) {
+            return Ok(());
+        }
+        let header = Header(block);
+        if !header.checksum_matches() {
+            return Err(invalid(archive, "a tar header's checksum does not match"));
+        }
+        let size = pax
+            .size
+            .take()
+            .or_else(|| header.size())
+            .ok_or_else(|| invalid(archive, "a tar header's size is not a number"))?;
+        let name = pax.path.take().unwrap_or_else(|| header.name());
+
+        match header.0[156] {
+            b'x' => {
+                pax = Pax::parse(&read_to_vec(&mut input, size).map_err(at(archive))?);
+                skip(&mut input, padding(size) as u64).map_err(at(archive))?;
+                continue;
+            }
+            // A global PAX header describes nothing an Archive needs.
+            b'g' => {
+                skip(&mut input, size + padding(size) as u64).map_err(at(archive))?;
+                continue;
+            }
+            b'0' | 0 | b'5' => {}
+            other => {
+                return Err(invalid(
+                    archive,
+                    &format!(
+                        "{name} is a tar member of type {:?}, and an Archive holds only files \
+                         and directories",
+                        other as char
+                    ),
+                ))
+            }
+        }
+
+        let member = Member {
+            name,
+            size,
+            directory: header.0[156] == b'5',
+        };
+        let mut data = (&mut input).take(size);
+        visit(&member, &mut data)?;
+        let rest = data.limit();
+        skip(&mut input, rest)
+            .map_err(|_| invalid(archive, "the Archive ends partway through a file"))?;
+        skip(&mut input, padding(size) as u64).map_err(at(archive))?;
+    }
+}
+
+/// The zero bytes after `size` bytes of file data that complete its last block.
+fn padding(size: u64) -> usize {
+    (BLOCK - (size % BLOCK as u64) as usize) % BLOCK
+}
+
+fn skip(input: &mut impl Read, bytes: u64) -> io::Result<()> {
+    let skipped = io::copy(&mut input.take(bytes), &mut io::sink())?;
+    if skipped != bytes {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+fn read_to_vec(input: &mut impl Read, bytes: u64) -> io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    input.take(bytes).read_to_end(&mut out)?;
+    if out.len() as u64 != bytes {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(out)
+}
+
+/// `name` as a relative path with no `..`, or `None` if it is not one.
+pub(super) fn safe_path(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    let mut safe = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => safe.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!safe.as_os_str().is_empty()).then_some(safe)
+}
+
+/// One 512-byte ustar header.
+struct Header([u8; BLOCK]);
+
+impl Header {
+    /// A header for a member `name` of `size` bytes, or `None` if `name` is too
+    /// long for ustar's 100-byte name and 155-byte prefix.
+    fn new(name: &str, size: u64, kind: u8, mode: u32, mtime: u64) -> Option<Header> {
+        let mut block = [0u8; BLOCK];
+        let (prefix, name) = split_name(name)?;
+        block[..name.len()].copy_from_slice(name.as_bytes());
+        block[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+        octal(&mut block[100..108], u64::from(mode));
+        octal(&mut block[108..116], 0);
+        octal(&mut block[116..124], 0);
+        if size < 8u64.pow(11) {
+            octal(&mut block[124..136], size);
+        } else {
+            // GNU base-256: a set high bit, then the size big-endian.
+            block[124] = 0x80;
+            block[128..136].copy_from_slice(&size.to_be_bytes());
+        }
+        octal(&mut block[136..148], mtime.min(8u64.pow(11) - 1));
+        block[156] = kind;
+        block[257..263].copy_from_slice(b"ustar\0");
+        block[263..265].copy_from_slice(b"00");
+
+        // The checksum is computed with its own field read as spaces.
+        block[148..156].fill(b' ');
+        let sum: u64 = block.iter().map(|&b| u64::from(b)).sum();
+        octal(&mut block[148..155], sum);
+        block[155] = b' ';
+        Some(Header(block))
+    }
+
+    fn checksum_matches(&self) -> bool {
+        let mut block = self.0;
+        let recorded = parse_octal(&block[148..156]);
+        block[148..156].fill(b' ');
+        let unsigned: u64 = block.iter().map(|&b| u64::from(b)).sum();
+        // Some historical tars summed signed bytes.
+        let signed: i64 = block.iter().map(|&b| i64::from(b as i8)).sum();
+        recorded.is_some_and(|recorded| recorded == unsigned || recorded as i64 == signed)
+    }
+
+    fn size(&self) -> Option<u64> {
+        let field = &self.0[124..136];
+        if field[0] & 0x80 != 0 {
+            let mut size = 0u64;
+            for &byte in &field[4..] {
+                size = size << 8 | u64::from(byte);
+            }
+            Some(size)
+        } else {
+            parse_octal(field)
+        }
+    }
+
+    fn name(&self) -> String {
+        let field = |range: std::ops::Range<usize>| {
+            let bytes = &self.0[range];
+            let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..len]).into_owned()
+        };
+        let name = field(0..100);
+        match &self.0[257..263] {
+            b"ustar\0" | b"ustar " => match field(345..500) {
+                prefix if prefix.is_empty() => name,
+                prefix => format!("{prefix}/{name}"),
+            },
+            _ => name,
+        }
+    }
+}
+
+/// `name` split into ustar's prefix and name fields at a `/`.
+fn split_name(name: &str) -> Option<(&str, &str)> {
+    if name.len() <= 100 {
+        return Some(("", name));
+    }
+    name.char_indices()
+        .filter(|&(_, c)| c == '/')
+        .map(|(at, _)| (&name[..at], &name[at + 1..]))
+        .find(|(prefix, rest)| prefix.len() <= 155 && !rest.is_empty() && rest.len() <= 100)
+}
+
+/// Write `value` into `field` as zero-padded octal, NUL-terminated.
+fn octal(field: &mut [u8], value: u64) {
+    let digits = format!("{value:0width$o}", width = field.len() - 1);
+    field[..digits.len()].copy_from_slice(digits.as_bytes());
+    field[digits.len()] = 0;
+}
+
+fn parse_octal(field: &[u8]) -> Option<u64> {
+    let text = std::str::from_utf8(field).ok()?;
+    let text = text.trim_matches(|c: char| c == '\0' || c == ' ');
+    if text.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(text, 8).ok()
+}
+
+/// The PAX extended-header records that override the next member's header.
+#[derive(Default)]
+struct Pax {
+    path: Option<String>,
+    size: Option<u64>,
+}
+
+impl Pax {
+    /// Records are `<length> <key>=<value>\n`, the length counting the whole
+    /// record. Records other than `path` and `size` are ignored.
+    fn parse(mut data: &[u8]) -> Pax {
+        let mut pax = Pax::default();
+        while let Some(space) = data.iter().position(|&b| b == b' ') {
+            let Some(len) = std::str::from_utf8(&data[..space])
+                .ok()
+                .and_then(|len| len.parse::<usize>().ok())
+                .filter(|&len| len > space && len <= data.len())
+            else {
+                break;
+            };
+            let record = String::from_utf8_lossy(&data[space + 1..len]);
+            if let Some((key, value)) = record.trim_end_matches('\n').split_once('=') {
+                match key {
+                    "path" => pax.path = Some(value.to_string()),
+                    "size" => pax.size = value.parse().ok(),
+                    _ => {}
+                }
+            }
+            data = &data[len..];
+        }
+        pax
+    }
+}
@@ -0,0 +1,184 @@
+//! The async API, on tokio: opening a Recording, streaming its samples, and
+//! writing one, without blocking the runtime.
+//!
+//! Each call is the async twin of a blocking one, and differs from it only in
+//! how it waits for the disk. The datatype and channel checks, the rules that
+//! find a Dataset from its Metadata file, and the errors are the blocking
+//! calls' own — so a Recording either API refuses, the other refuses the same
+//! way.
+//!
+//! Reading is streamed: [`SampleStream`] hands the Dataset over a chunk at a
+//! time rather than as one `Vec`, which is the shape an ingest service that
+//! forwards samples as they arrive wants, and which keeps its memory flat
+//! however long the Recording.
+
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::vec;
+
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, SeekFrom};
+
+use super::dataset::CHUNK_BYTES;
+use super::parallel;
+use super::{at, dataset_path, Endianness, Error, Metadata, RecordingWriter, Sample, SigMF};
+
+impl SigMF {
+    /// Open a Recording, given the path of its `.sigmf-meta` file, as
+    /// [`from_file`](Self::from_file) does.
+    ///
+    /// # Errors
+    ///
+    /// As [`from_file`](Self::from_file).
+    pub async fn open_async<T: AsRef<Path>>(path: T) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let json = fs::read(path).await.map_err(at(path))?;
+        let metadata: Metadata = serde_json::from_slice(&json)?;
+        let datafile = dataset_path(path, &metadata)?;
+        Ok(Self { metadata, datafile })
+    }
+
+    /// A stream of the Dataset's samples, decoded as `S`, a chunk at a time.
+    ///
+    /// `S` is checked against `core:datatype` as [`samples`](Self::samples)
+    /// checks it, and the Dataset is measured here, so a Recording that does
+    /// not fit its Dataset is refused before a sample is read.
+    ///
+    /// # Errors
+    ///
+    /// As [`samples`](Self::samples).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sigmf::num_complex::Complex;
+    /// use sigmf::{RecordingWriter, SigMF};
+    /// # let dir = tempfile::tempdir().expect("a temporary directory");
+    /// # tokio::runtime::Builder::new_current_thread().build()?.block_on(async {
+    ///
+    /// let samples = [Complex::new(1.0f32, -1.0), Complex::new(0.5, 0.25)];
+    /// RecordingWriter::new(&samples, 32_000.0)
+    ///     .to_file_async(dir.path().join("watch"))
+    ///     .await?;
+    ///
+    /// let recording = SigMF::open_async(dir.path().join("watch.sigmf-meta")).await?;
+    /// let mut stream = recording.sample_stream::<Complex<f32>>().await?;
+    /// let mut read = Vec::new();
+    /// while let Some(chunk) = stream.next_chunk().await? {
+    ///     read.extend(chunk);
+    /// }
+    /// assert_eq!(read, samples);
+    /// # Ok::<(), sigmf::Error>(())
+    /// # })?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub async fn sample_stream<S: Sample>(&self) -> Result<SampleStream<S>, Error> {
+        let endianness = self.check_sample_type::<S>()?;
+        let path = self.dataset_file()?.to_path_buf();
+        let file = fs::File::open(&path).await.map_err(at(&path))?;
+        let dataset_len = file.metadata().await.map_err(at(&path))?.len();
+        let ranges: Vec<Range<u64>> = self
+            .metadata
+            .segments(dataset_len)?
+            .into_iter()
+            .map(|segment| segment.bytes)
+            .collect();
+
+        Ok(SampleStream {
+            file,
+            path,
+            ranges: ranges.into_iter(),
+            current: None,
+            endianness,
+            sample_size: self.metadata.global.datatype.size() as usize,
+            buffer: Vec::new(),
+            sample: PhantomData,
+        })
+    }
+}
+
+impl<S: Sample> RecordingWriter<'_, S> {
+    /// Write both files of the Recording, as [`to_file`](Self::to_file)
+    /// does: the same checks, the same bytes, and the Dataset first.
+    ///
+    /// # Errors
+    ///
+    /// As [`to_file`](Self::to_file).
+    pub async fn to_file_async<P: AsRef<Path>>(self, basename: P) -> Result<SigMF, Error> {
+        let encoded = self.encode(basename.as_ref())?;
+        fs::write(&encoded.data_path, &encoded.data)
+            .await
+            .map_err(at(&encoded.data_path))?;
+        fs::write(&encoded.metadata_path, &encoded.json)
+            .await
+            .map_err(at(&encoded.metadata_path))?;
+        Ok(encoded.into_recording())
+    }
+}
+
+/// A Recording's samples, read a chunk at a time from
+/// [`SigMF::sample_stream`].
+///
+/// The chunks follow the Dataset's segments in order, and skip what is not
+/// samples — a Non-Conforming Dataset's `core:header_bytes` and
+/// `core:trailing_bytes` — so that, end to end, they are what
+/// [`SigMF::samples`] returns.
+#[derive(Debug)]
+pub struct SampleStream<S> {
+    file: fs::File,
+    path: PathBuf,
+    ranges: vec::IntoIter<Range<u64>>,
+    current: Option<Range<u64>>,
+    endianness: Endianness,
+    sample_size: usize,
+    buffer: Vec<u8>,
+    sample: PhantomData<S>,
+}
+
+impl<S: Sample> SampleStream<S> {
+    /// The next chunk of samples — at most a mebibyte of Dataset, never part
+    /// of a sample, and never empty — or `None` once every sample has been
+    /// read.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Io`] if the Dataset cannot be read, including when it has
+    /// shrunk since the stream was opened.
+    pub async fn next_chunk(&mut self) -> Result<Option<Vec<S>>, Error> {
+        let range = loop {
+            match self.current.take() {
+                Some(range) if !range.is_empty() => break range,
+                _ => {
+                    let Some(next) = self.ranges.next() else {
+                        return Ok(None);
+                    };
+                    self.file
+                        .seek(SeekFrom::Start(next.start))
+                        .await
+                        .map_err(at(&self.path))?;
+                    self.current = Some(next);
+                }
+            }
+        };
+
+        // `CHUNK_BYTES` is a whole number of samples of every datatype, and a
+        // segment is a whole number of samples, so a chunk is too.
+        let len = CHUNK_BYTES.min((range.end - range.start) as usize);
+        self.buffer.resize(len, 0);
+        self.file
+            .read_exact(&mut self.buffer)
+            .await
+            .map_err(at(&self.path))?;
+        self.current = Some(range.start + len as u64..range.end);
+
+        let mut samples = Vec::with_capacity(len / self.sample_size);
+        parallel::decode_into(
+            &self.buffer,
+            self.sample_size,
+            self.endianness,
+            &mut samples,
+        );
+        Ok(Some(samples))
+    }
+}
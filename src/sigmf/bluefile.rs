@@ -0,0 +1,552 @@
+//! MIDAS Bluefiles: the one-dimensional (type 1000 and 1001) files of X-Midas
+//! and NextMidas.
+//!
+//! A Bluefile is a 512-byte header control block, the data, and an optional
+//! extended header of typed keywords after the data. The header says what the
+//! samples are in a two-letter format code — `CI` is complex 16-bit integers —
+//! and when they begin, in seconds since 1950; its adjunct block says how far
+//! apart they are, as `xdelta`. [`import`] and [`export`] map those onto
+//! `core:datatype`, `core:sample_rate` and the first Captures segment's
+//! `core:datetime`, and carry every keyword with no SigMF field of its own in
+//! the `bluefile` extension namespace, [`BluefileGlobal`], so a file that makes
+//! the trip in both directions keeps them.
+//!
+//! One keyword is given a SigMF field: `RF_FREQ`, the centre frequency in Hz,
+//! which becomes `core:frequency`. `TC_PREC`, the part of the start time too
+//! fine for the header's `timecode`, is folded into `core:datetime`.
+
+use std::fs;
+use std::io::{BufWriter, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+
+use super::dataset::{read_range, DatasetWriter};
+use super::datetime::Timestamp;
+use super::{
+    at, finish_file, invalid, CaptureMetadata, DataFormat, DataType, Endianness, Error,
+    GlobalExtension, GlobalMetadata, Map, Metadata, MetadataError, NumberType, SigMF,
+};
+
+/// The header control block's length, and the unit `ext_start` counts in.
+const BLOCK: usize = 512;
+
+/// The Midas epoch, 1950-01-01, in seconds since the Unix one.
+const MIDAS_EPOCH: i64 = -631_152_000;
+
+/// The keyword carrying the centre frequency.
+const RF_FREQ: &str = "RF_FREQ";
+/// The keyword carrying the start time's extra precision, in seconds.
+const TC_PREC: &str = "TC_PREC";
+
+/// The `bluefile` extension's Global fields: the keywords of a Bluefile that
+/// no SigMF field holds.
+///
+/// The namespace is this crate's, not one registered with the SigMF project;
+/// it exists so that a Bluefile's keywords survive a conversion and a
+/// conversion back. Declared optional, as it is descriptive: a reader that
+/// skips it loses nothing needed to read the samples.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct BluefileGlobal {
+    /// Every keyword from the main and extended headers, by name. Text is a
+    /// string, a single number a number, and several numbers an array.
+    #[serde(rename = "bluefile:keywords")]
+    pub keywords: Map<String, Value>,
+}
+
+impl GlobalExtension for BluefileGlobal {
+    fn namespace() -> String {
+        "bluefile".to_string()
+    }
+
+    fn version() -> String {
+        "1.0.0".to_string()
+    }
+}
+
+/// The parts of a header control block this reads.
+struct Header {
+    endianness: Endianness,
+    data_endianness: Endianness,
+    ext_start: u64,
+    ext_size: u64,
+    data_start: u64,
+    data_size: u64,
+    format: [u8; 2],
+    timecode: f64,
+    keywords: Vec<u8>,
+    xdelta: f64,
+}
+
+/// Write the samples of the Bluefile `blue` as a new Recording,
+/// `basename.sigmf-data` and `basename.sigmf-meta`, and return it opened.
+///
+/// The format code's first letter is `S` for real samples or `C` for complex,
+/// and its second one of `B`, `O`, `I`, `L`, `F` and `D`: `i8`, `u8`, `i16`,
+/// `i32`, `f32` and `f64`. The samples are copied as they are, in the byte
+/// order the header's `data_rep` gives.
+///
+/// # Errors
+///
+/// [`Error::Io`] naming `blue` if it cannot be read, or is not a type 1000 or
+/// 1001 Bluefile with its data attached and in one of those formats;
+/// [`MetadataError::PartialSample`] if its data ends partway through a sample;
+/// or [`Error::Io`] for a file that cannot be written.
+///
+/// # Examples
+///
+/// ```
+/// use sigmf::num_complex::Complex;
+/// use sigmf::{bluefile, RecordingWriter};
+/// # let dir = tempfile::tempdir().expect("a temporary directory");
+///
+/// let samples = [Complex::new(1i16, -1), Complex::new(2, -2)];
+/// let mut writer = RecordingWriter::new(&samples, 32_000.0);
+/// let mut capture = sigmf::CaptureMetadata::new(0);
+/// capture.frequency = Some(2_187_500.0);
+/// writer.captures_mut().push(capture);
+/// let recording = writer.to_file(dir.path().join("dsc"))?;
+///
+/// bluefile::export(&recording, dir.path().join("dsc.tmp"))?;
+/// let back = bluefile::import(dir.path().join("dsc.tmp"), dir.path().join("back"))?;
+/// assert_eq!(back.samples::<Complex<i16>>()?, samples);
+/// assert_eq!(back.metadata.captures[0].frequency, Some(2_187_500.0));
+/// # Ok::<(), sigmf::Error>(())
+/// ```
+pub fn import<P: AsRef<Path>, Q: AsRef<Path>>(blue: P, basename: Q) -> Result<SigMF, Error> {
+    let path = blue.as_ref();
+    let mut file = fs::File::open(path).map_err(at(path))?;
+    let header = read_header(&mut file, path)?;
+    let file_len = file.metadata().map_err(at(path))?.len();
+    let data = within(header.data_start, header.data_size, file_len)
+        .ok_or_else(|| invalid(path, "a Bluefile whose data runs past the end of the file"))?;
+
+    let datatype = data_format(header.format, header.data_endianness).ok_or_else(|| {
+        invalid(
+            path,
+            &format!(
+                "Bluefile format `{}` has no SigMF datatype",
+                String::from_utf8_lossy(&header.format)
+            ),
+        )
+    })?;
+    if header.data_size % datatype.size() != 0 {
+        return Err(MetadataError::PartialSample {
+            bytes: header.data_size,
+            datatype,
+        }
+        .into());
+    }
+
+    let mut keywords = main_keywords(&header.keywords);
+    if header.ext_size > 0 {
+        let extension = header
+            .ext_start
+            .checked_mul(BLOCK as u64)
+            .and_then(|start| within(start, header.ext_size, file_len))
+            .ok_or_else(|| {
+                invalid(
+                    path,
+                    "a Bluefile whose extended header runs past the end of the file",
+                )
+            })?;
+        let mut extended = Vec::new();
+        read_range(&mut file, path, extension, |bytes| {
+            extended.extend_from_slice(bytes);
+            Ok(())
+        })?;
+        extended_keywords(&extended, header.endianness, &mut keywords);
+    }
+
+    let mut global = GlobalMetadata::describing(datatype);
+    global.sample_rate = (header.xdelta > 0.0).then(|| 1.0 / header.xdelta);
+    let mut capture = CaptureMetadata::new(0);
+    capture.frequency = keywords.remove(RF_FREQ).as_ref().and_then(number);
+    let precision = keywords.remove(TC_PREC).as_ref().and_then(number);
+    if header.timecode != 0.0 {
+        let start = Timestamp::from_unix(MIDAS_EPOCH)
+            .add_seconds(header.timecode)
+            .add_seconds(precision.unwrap_or(0.0));
+        capture.datetime = Some(start.to_string());
+    }
+    if !keywords.is_empty() {
+        global.set_extension(BluefileGlobal { keywords })?;
+    }
+
+    let mut writer = DatasetWriter::create(basename.as_ref(), &[path])?;
+    read_range(&mut file, path, data, |bytes| writer.write(bytes))?;
+    writer.finish(Metadata {
+        global,
+        captures: vec![capture],
+        annotations: vec![],
+    })
+}
+
+/// The `size` bytes from `start`, if they all lie in a file of `file_len`
+/// bytes — which a header, being only numbers, does not promise.
+fn within(start: u64, size: u64, file_len: u64) -> Option<Range<u64>> {
+    let end = start.checked_add(size)?;
+    (end <= file_len).then_some(start..end)
+}
+
+/// Write the samples of `recording` to the Bluefile `blue`, type 1000, with
+/// its keywords in an extended header after them.
+///
+/// The keywords are those of the `bluefile` extension, if the Recording has
+/// it, with `RF_FREQ` from the first Captures segment's `core:frequency`. The
+/// header is little-endian, and the data in the Recording's own byte order.
+///
+/// # Errors
+///
+/// [`MetadataError::NoEquivalentFormat`] for `u16` and `u32` samples, which
+/// Bluefiles have no format for; [`MetadataError::MultiChannelDataset`],
+/// [`MetadataError::NoDataset`] or [`MetadataError::PartialSample`] for a
+/// Dataset that cannot be read as one channel; or [`Error::Io`].
+pub fn export<P: AsRef<Path>>(recording: &SigMF, blue: P) -> Result<(), Error> {
+    let path = blue.as_ref();
+    let global = &recording.metadata.global;
+    if let Some(channels) = global.num_channels {
+        if channels != 1 {
+            return Err(MetadataError::MultiChannelDataset(channels).into());
+        }
+    }
+    let format = format_code(global.datatype).ok_or(MetadataError::NoEquivalentFormat {
+        datatype: global.datatype,
+        format: "a Bluefile",
+    })?;
+    let (mut dataset, segments) = recording.open_dataset()?;
+    let source = recording.dataset_file()?;
+    let data_size: u64 = segments.iter().map(|s| s.bytes.end - s.bytes.start).sum();
+
+    let mut keywords = global
+        .get_extension::<BluefileGlobal>()
+        .ok()
+        .flatten()
+        .map(|extension| extension.keywords)
+        .unwrap_or_default();
+    let first = recording.metadata.captures.first();
+    if let Some(frequency) = first.and_then(|capture| capture.frequency) {
+        keywords.insert(RF_FREQ.to_string(), Value::from(frequency));
+    }
+    let start = first
+        .and_then(|capture| capture.datetime.as_deref())
+        .and_then(Timestamp::parse);
+    let timecode = start.map_or(0.0, |start| {
+        let since = start.seconds_since(Timestamp::from_unix(MIDAS_EPOCH));
+        // What a double holding ~2.4 × 10⁹ seconds cannot, TC_PREC carries.
+        let whole = since.floor();
+        if since != whole {
+            keywords.insert(TC_PREC.to_string(), Value::from(since - whole));
+        }
+        whole
+    });
+    let extended = extended_header(&keywords);
+
+    let ext_start = (BLOCK as u64 + data_size).div_ceil(BLOCK as u64);
+    let mut hcb = [0u8; BLOCK];
+    let mut put = |at: usize, bytes: &[u8]| hcb[at..at + bytes.len()].copy_from_slice(bytes);
+    put(0, b"BLUE");
+    put(4, b"EEEI");
+    put(
+        8,
+        match global.datatype.endianness() {
+            Some(Endianness::BigEndian) => b"IEEE",
+            _ => b"EEEI",
+        },
+    );
+    put(24, &(ext_start as i32).to_le_bytes());
+    put(28, &(extended.len() as i32).to_le_bytes());
+    put(32, &(BLOCK as f64).to_le_bytes());
+    put(40, &(data_size as f64).to_le_bytes());
+    put(48, &1000i32.to_le_bytes());
+    put(52, &format);
+    put(56, &timecode.to_le_bytes());
+    put(
+        264,
+        &global
+            .sample_rate
+            .map_or(1.0, |rate| 1.0 / rate)
+            .to_le_bytes(),
+    );
+    put(272, &1i32.to_le_bytes());
+
+    let mut out = BufWriter::new(fs::File::create(path).map_err(at(path))?);
+    let mut write = |bytes: &[u8]| out.write_all(bytes).map_err(at(path));
+    write(&hcb)?;
+    for segment in &segments {
+        read_range(&mut dataset, source, segment.bytes.clone(), &mut write)?;
+    }
+    write(&vec![
+        0;
+        (ext_start * BLOCK as u64 - BLOCK as u64 - data_size)
+            as usize
+    ])?;
+    write(&extended)?;
+    finish_file(out, path)
+}
+
+fn read_header(file: &mut fs::File, path: &Path) -> Result<Header, Error> {
+    let mut hcb = [0u8; BLOCK];
+    file.read_exact(&mut hcb)
+        .map_err(|_| invalid(path, "too short to be a Bluefile"))?;
+    if &hcb[..4] != b"BLUE" {
+        return Err(invalid(path, "not a Bluefile"));
+    }
+    let representation = |at: usize| match &hcb[at..at + 4] {
+        b"EEEI" => Ok(Endianness::LittleEndian),
+        b"IEEE" => Ok(Endianness::BigEndian),
+        _ => Err(invalid(
+            path,
+            "a Bluefile in neither IEEE nor EEEI representation",
+        )),
+    };
+    let endianness = representation(4)?;
+    let data_endianness = representation(8)?;
+
+    let field = |at: usize| -> [u8; 8] {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&hcb[at..at + 8]);
+        if endianness == Endianness::BigEndian {
+            bytes.reverse();
+        }
+        bytes
+    };
+    let int = |at: usize| {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&hcb[at..at + 4]);
+        match endianness {
+            Endianness::LittleEndian => i32::from_le_bytes(bytes),
+            Endianness::BigEndian => i32::from_be_bytes(bytes),
+        }
+    };
+    let double = |at: usize| f64::from_le_bytes(field(at));
+
+    if int(12) != 0 {
+        return Err(invalid(path, "a Bluefile whose data is detached from it"));
+    }
+    let file_type = int(48);
+    if file_type != 1000 && file_type != 1001 {
+        return Err(invalid(
+            path,
+            &format!(
+                "a type {file_type} Bluefile, where only types 1000 and 1001 are one-dimensional"
+            ),
+        ));
+    }
+    let size = |value: f64| {
+        (value >= 0.0 && value.fract() == 0.0)
+            .then_some(value as u64)
+            .ok_or_else(|| {
+                invalid(
+                    path,
+                    "a Bluefile with a data_start or data_size of no whole byte",
+                )
+            })
+    };
+    let keylength = usize::try_from(int(160)).unwrap_or(0).min(92);
+
+    Ok(Header {
+        endianness,
+        data_endianness,
+        ext_start: u64::try_from(int(24)).unwrap_or(0),
+        ext_size: u64::try_from(int(28)).unwrap_or(0),
+        data_start: size(double(32))?,
+        data_size: size(double(40))?,
+        format: [hcb[52], hcb[53]],
+        timecode: double(56),
+        keywords: hcb[164..164 + keylength].to_vec(),
+        xdelta: double(264),
+    })
+}
+
+/// The datatype of Bluefile format `code`, with samples in `endianness`.
+fn data_format(code: [u8; 2], endianness: Endianness) -> Option<DataFormat> {
+    let number_type = match code[0] {
+        b'S' => NumberType::Real,
+        b'C' => NumberType::Complex,
+        _ => return None,
+    };
+    let data_type = match code[1] {
+        b'B' => DataType::I8,
+        b'O' => DataType::U8,
+        b'I' => DataType::I16(endianness),
+        b'L' => DataType::I32(endianness),
+        b'F' => DataType::F32(endianness),
+        b'D' => DataType::F64(endianness),
+        _ => return None,
+    };
+    Some(DataFormat {
+        number_type,
+        data_type,
+    })
+}
+
+/// The inverse of [`data_format`], for the datatypes that have one.
+fn format_code(datatype: DataFormat) -> Option<[u8; 2]> {
+    let structure = match datatype.number_type {
+        NumberType::Real => b'S',
+        NumberType::Complex => b'C',
+    };
+    let element = match datatype.data_type {
+        DataType::I8 => b'B',
+        DataType::U8 => b'O',
+        DataType::I16(_) => b'I',
+        DataType::I32(_) => b'L',
+        DataType::F32(_) => b'F',
+        DataType::F64(_) => b'D',
+        DataType::U16(_) | DataType::U32(_) => return None,
+    };
+    Some([structure, element])
+}
+
+/// The main header's keywords: `NAME=VALUE`, separated by NULs, all text.
+fn main_keywords(bytes: &[u8]) -> Map<String, Value> {
+    bytes
+        .split(|&b| b == 0)
+        .filter_map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let (name, value) = entry.split_once('=')?;
+            Some((name.trim().to_string(), Value::from(value.trim())))
+        })
+        .collect()
+}
+
+/// Add the extended header's keywords to `keywords`.
+///
+/// Each is a record: its length `lkey` (4 bytes), the length `lext` of all but
+/// its value (2), the length of its name (1), its value's type code (1), its
+/// value, its name, and padding to a multiple of eight bytes. A record that
+/// does not fit ends the reading, as does one of a type this does not know.
+fn extended_keywords(mut bytes: &[u8], endianness: Endianness, keywords: &mut Map<String, Value>) {
+    while bytes.len() >= 8 {
+        let int = |b: &[u8]| match endianness {
+            Endianness::LittleEndian => u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            Endianness::BigEndian => u32::from_be_bytes([b[0], b[1], b[2], b[3]]),
+        } as usize;
+        let short = |b: &[u8]| match endianness {
+            Endianness::LittleEndian => u16::from_le_bytes([b[0], b[1]]),
+            Endianness::BigEndian => u16::from_be_bytes([b[0], b[1]]),
+        } as usize;
+        let (lkey, lext, ltag, code) = (
+            int(bytes),
+            short(&bytes[4..]),
+            usize::from(bytes[6]),
+            bytes[7],
+        );
+        if lkey < lext || lkey > bytes.len() || lext < 8 + ltag {
+            return;
+        }
+        let data = &bytes[8..8 + lkey - lext];
+        let name = String::from_utf8_lossy(&bytes[8 + lkey - lext..8 + lkey - lext + ltag])
+            .trim()
+            .to_string();
+        let value = match code {
+            b'A' => Value::from(
+                String::from_utf8_lossy(data)
+                    .trim_end_matches(['\0', ' '])
+                    .to_string(),
+            ),
+            _ => match decode_numbers(code, data, endianness) {
+                Some(mut values) if values.len() == 1 => values.remove(0),
+                Some(values) => Value::Array(values),
+                None => return,
+            },
+        };
+        keywords.insert(name, value);
+        bytes = &bytes[lkey..];
+    }
+}
+
+/// The numbers of a keyword value of type `code`.
+fn decode_numbers(code: u8, data: &[u8], endianness: Endianness) -> Option<Vec<Value>> {
+    let width = match code {
+        b'B' | b'O' => 1,
+        b'I' => 2,
+        b'L' | b'F' => 4,
+        b'X' | b'D' => 8,
+        _ => return None,
+    };
+    Some(
+        data.chunks_exact(width)
+            .map(|chunk| {
+                let mut bytes = [0u8; 8];
+                bytes[..width].copy_from_slice(chunk);
+                if endianness == Endianness::BigEndian {
+                    bytes[..width].reverse();
+                }
+                match code {
+                    b'B' => Value::from(bytes[0] as i8),
+                    b'O' => Value::from(bytes[0]),
+                    b'I' => Value::from(i16::from_le_bytes([bytes[0], bytes[1]])),
+                    b'L' => {
+                        Value::from(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                    }
+                    b'F' => Number::from_f64(f64::from(f32::from_le_bytes([
+                        bytes[0], bytes[1], bytes[2], bytes[3],
+                    ])))
+                    .map_or(Value::Null, Value::Number),
+                    b'X' => Value::from(i64::from_le_bytes(bytes)),
+                    _ => Number::from_f64(f64::from_le_bytes(bytes))
+                        .map_or(Value::Null, Value::Number),
+                }
+            })
+            .collect(),
+    )
+}
+
+/// The extended header holding `keywords`, little-endian.
+///
+/// A string is written as text, an integer or array of integers as 64-bit
+/// integers, and any other number or array of numbers as doubles. Anything
+/// else — an object, a boolean, a mixed array — is written as its JSON text.
+fn extended_header(keywords: &Map<String, Value>) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (name, value) in keywords {
+        let numbers: Option<Vec<&Number>> = match value {
+            Value::Number(number) => Some(vec![number]),
+            Value::Array(items) if !items.is_empty() => {
+                items.iter().map(Value::as_number).collect()
+            }
+            _ => None,
+        };
+        let (code, data) = match (value, numbers) {
+            (Value::String(text), _) => (b'A', text.as_bytes().to_vec()),
+            (_, Some(numbers)) if numbers.iter().all(|n| n.as_i64().is_some()) => (
+                b'X',
+                numbers
+                    .iter()
+                    .flat_map(|n| n.as_i64().unwrap_or(0).to_le_bytes())
+                    .collect(),
+            ),
+            (_, Some(numbers)) => (
+                b'D',
+                numbers
+                    .iter()
+                    .flat_map(|n| n.as_f64().unwrap_or(f64::NAN).to_le_bytes())
+                    .collect(),
+            ),
+            (other, None) => (b'A', other.to_string().into_bytes()),
+        };
+        let name = &name.as_bytes()[..name.len().min(255)];
+        let lkey = (8 + data.len() + name.len()).next_multiple_of(8);
+        out.extend_from_slice(&(lkey as u32).to_le_bytes());
+        out.extend_from_slice(&((lkey - data.len()) as u16).to_le_bytes());
+        out.push(name.len() as u8);
+        out.push(code);
+        out.extend_from_slice(&data);
+        out.extend_from_slice(name);
+        out.resize(out.len().next_multiple_of(8), 0);
+    }
+    out
+}
+
+/// A keyword's value as a number, from a number or from text holding one.
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::String(text) => text.trim().parse().ok(),
+        Value::Array(items) => items.first().and_then(Value::as_f64),
+        other => other.as_f64(),
+    }
+}
@@ -0,0 +1,713 @@
+//! A catalog of every Recording under a directory: what each one holds, in a
+//! JSON file that can be searched without opening a single Recording again.
+//!
+//! [`scan`] walks a tree and reads every Metadata file in it — loose, or
+//! inside an Archive — and every Collection. Datasets are measured, never
+//! read, so a scan costs a directory walk and the size of the sidecars,
+//! however many terabytes they describe. A tree of tens of thousands of
+//! Recordings scans in the time it takes to list it.
+//!
+//! A store that large has broken Recordings in it, and a scan does not stop
+//! at the first: each one becomes a [`Problem`] beside the [`Entry`] list,
+//! and the walk carries on. Only a directory that cannot be read ends it.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+use super::archive::read_members;
+use super::datetime::Timestamp;
+use super::{
+    at, dataset_path, files_under, hex_encode, CaptureMetadata, Collection, DataFormat, Error,
+    Geolocation, Metadata, MetadataError, SigMF,
+};
+use crate::{SIGMF_ARCHIVE_EXT, SIGMF_COLLECTION_EXT, SIGMF_DATASET_EXT, SIGMF_METADATA_EXT};
+
+/// What [`scan`] found.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Catalog {
+    /// One per Recording that could be read, in order of path.
+    pub recordings: Vec<Entry>,
+    /// One per `.sigmf-collection` file that could be read, in order of path.
+    pub collections: Vec<CollectionEntry>,
+    /// Everything that is wrong, in the order it was found.
+    pub problems: Vec<Problem>,
+}
+
+/// The summary of one Recording.
+///
+/// Everything here is read from the Metadata file, except the sample count,
+/// which is measured from the Dataset's length — and so is absent, with
+/// everything that depends on it, where the Dataset is.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Entry {
+    /// The Recording's `.sigmf-meta` file, or the Archive holding it.
+    pub path: PathBuf,
+    /// For a Recording in an Archive, its Metadata file's path within it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub member: Option<String>,
+    /// `core:datatype`.
+    pub datatype: DataFormat,
+    /// `core:sample_rate`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<f64>,
+    /// How many samples the Dataset holds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_count: Option<u64>,
+    /// How long the Recording is, in seconds: its samples at its rate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    /// The bottom of the band the Captures segments cover, in Hz: the lowest
+    /// `core:frequency`, less half the sample rate where there is one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lower_frequency: Option<f64>,
+    /// The top of that band: the highest `core:frequency`, plus half the
+    /// sample rate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upper_frequency: Option<f64>,
+    /// When the earliest timed Captures segment starts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    /// When the last timed segment ends: its start plus its samples at the
+    /// sample rate. Absent where the end of any timed segment cannot be worked
+    /// out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<String>,
+    /// The Global `core:geolocation`, or else the first segment's that has
+    /// one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geolocation: Option<Geolocation>,
+    /// `core:sha512`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha512: Option<String>,
+    /// Every distinct `core:label` among the annotations, sorted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// Where and when each Captures segment was recorded, and what band it
+    /// covers — one for the implicit segment of a document without captures.
+    pub segments: Vec<Segment>,
+}
+
+/// The summary of one Captures segment, for a [`Query`] to match against.
+///
+/// A segment without a `core:datetime` of its own is timed from the last one
+/// before it that has one, at the sample rate, across any gap its
+/// `core:global_index` records. One without a `core:geolocation` is placed at
+/// the Global one.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Segment {
+    /// `core:sample_start`.
+    pub sample_start: u64,
+    /// `core:frequency`, less half the sample rate where there is one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lower_frequency: Option<f64>,
+    /// `core:frequency`, plus half the sample rate.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upper_frequency: Option<f64>,
+    /// When the segment's first sample was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    /// When the segment's last sample ends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<String>,
+    /// Where the segment was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geolocation: Option<Geolocation>,
+}
+
+/// The summary of one Collection.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct CollectionEntry {
+    /// The `.sigmf-collection` file.
+    pub path: PathBuf,
+    /// `core:description`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The basename of each Recording in it, as the Collection names them.
+    pub streams: Vec<String>,
+}
+
+/// Something wrong with a file in a scanned tree.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Problem {
+    /// The file at fault, or the Archive holding it.
+    pub path: PathBuf,
+    /// For a file in an Archive, its path within it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub member: Option<String>,
+    /// What kind of fault it is.
+    pub kind: ProblemKind,
+    /// What exactly is wrong, for a person to read.
+    pub detail: String,
+}
+
+/// The kinds of [`Problem`] a scan reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ProblemKind {
+    /// A Metadata file whose Dataset is not there. The Recording is still
+    /// catalogued, without its sample count.
+    MissingDataset,
+    /// A `.sigmf-data` file that no Metadata file claims.
+    OrphanDataset,
+    /// A Metadata file whose `core:dataset` names a file outside its own
+    /// directory, which the specification forbids and this crate will not
+    /// follow.
+    DatasetPathEscapesDirectory,
+    /// A file that cannot be read as what its name says it is — not JSON, not
+    /// SigMF, not a tar file, or a Dataset the Metadata does not fit.
+    Unreadable,
+    /// A Collection stream whose Metadata file is missing, or has changed
+    /// since the Collection hashed it, or whose name points outside the
+    /// Collection's directory.
+    CollectionStream,
+}
+
+/// Catalog every Recording and Collection in `root` and the directories below
+/// it.
+///
+/// Three kinds of file are read: `.sigmf-meta` files, `.sigmf` Archives —
+/// whose Metadata members are read in place, not extracted — and
+/// `.sigmf-collection` files. `.sigmf-data` files are only measured, and one
+/// that no Metadata file in its directory claims, by name or by
+/// `core:dataset`, is reported as an orphan.
+///
+/// # Errors
+///
+/// [`Error::Io`] if `root`, or a directory below it, cannot be listed. A file
+/// that cannot be read is a [`Problem`], not an error.
+///
+/// # Examples
+///
+/// ```
+/// use sigmf::catalog::{self, ProblemKind};
+/// use sigmf::RecordingWriter;
+/// # let dir = tempfile::tempdir().expect("a temporary directory");
+///
+/// RecordingWriter::new(&[1.0f32, 2.0], 32_000.0).to_file(dir.path().join("watch"))?;
+/// std::fs::write(dir.path().join("stray.sigmf-data"), [0u8; 8])?;
+///
+/// let catalog = catalog::scan(dir.path())?;
+/// assert_eq!(catalog.recordings[0].duration, Some(2.0 / 32_000.0));
+/// assert_eq!(catalog.problems[0].kind, ProblemKind::OrphanDataset);
+///
+/// catalog.to_file(dir.path().join("catalog.json"))?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn scan<P: AsRef<Path>>(root: P) -> Result<Catalog, Error> {
+    let files = files_under(
+        root.as_ref(),
+        &[
+            SIGMF_METADATA_EXT,
+            SIGMF_DATASET_EXT,
+            SIGMF_ARCHIVE_EXT,
+            SIGMF_COLLECTION_EXT,
+        ],
+    )?;
+
+    let mut catalog = Catalog::default();
+    let mut claimed = HashSet::new();
+    for path in files.iter().filter(named(SIGMF_METADATA_EXT)) {
+        scan_metadata_file(path, &mut catalog, &mut claimed);
+    }
+    for path in files.iter().filter(named(SIGMF_DATASET_EXT)) {
+        if !claimed.contains(path) {
+            catalog.problems.push(Problem {
+                path: path.clone(),
+                member: None,
+                kind: ProblemKind::OrphanDataset,
+                detail: "no Metadata file in this directory names this Dataset".to_string(),
+            });
+        }
+    }
+    for path in files.iter().filter(named(SIGMF_ARCHIVE_EXT)) {
+        if let Err(err) = scan_archive(path, &mut catalog) {
+            catalog.problems.push(Problem {
+                path: path.clone(),
+                member: None,
+                kind: ProblemKind::Unreadable,
+                detail: err.to_string(),
+            });
+        }
+    }
+    for path in files.iter().filter(named(SIGMF_COLLECTION_EXT)) {
+        scan_collection(path, &mut catalog);
+    }
+    Ok(catalog)
+}
+
+/// Whether a path's file name ends with `ext`, as an iterator filter.
+fn named(ext: &'static str) -> impl Fn(&&PathBuf) -> bool {
+    move |path| path.to_string_lossy().ends_with(ext)
+}
+
+impl Catalog {
+    /// Read a catalog written by [`to_file`](Self::to_file).
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Io`] if the file cannot be read, [`Error::Json`] if it is not
+    /// a catalog.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Catalog, Error> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).map_err(at(path))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Write this catalog to `path` as JSON.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Io`] if the file cannot be written.
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json).map_err(at(path))
+    }
+
+    /// The Recordings `query` matches, in catalog order.
+    pub fn query(&self, query: &Query) -> Vec<&Entry> {
+        self.recordings
+            .iter()
+            .filter(|entry| query.matches(entry))
+            .collect()
+    }
+}
+
+/// The mean radius of the Earth, in metres: the IUGG's R₁, over which a
+/// great-circle distance is out by at most about half a percent anywhere.
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// A search of a [`Catalog`] by band, time, place and label.
+///
+/// Each condition narrows the search, and a Recording matches when one of its
+/// [`segments`](Entry::segments) meets every band, time and place condition
+/// at once — so a Recording that was at the right place in the morning and
+/// on the right frequency in the afternoon is not a Recording of that
+/// frequency at that place. A segment that does not say what a condition
+/// asks about — no `core:frequency`, no time, no position — does not meet
+/// it. Labels are a condition on the Recording as a whole, and every one
+/// asked for must be among its annotations.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// use sigmf::catalog::{self, Query};
+/// use sigmf::{CaptureMetadata, Geolocation, RecordingWriter};
+/// # let dir = tempfile::tempdir().expect("a temporary directory");
+///
+/// let mut writer = RecordingWriter::new(&[0.5f32; 1000], 1e6);
+/// writer.global_mut().geolocation = Some(Geolocation::new(14.5053, -22.9576));
+/// let mut capture = CaptureMetadata::new(0);
+/// capture.frequency = Some(433.5e6);
+/// capture.datetime = Some("2026-07-16T09:14:22.000Z".to_string());
+/// writer.captures_mut().push(capture);
+/// writer.to_file(dir.path().join("ism"))?;
+///
+/// let morning = UNIX_EPOCH + Duration::from_secs(1_784_192_400); // 09:00
+/// let noon = morning + Duration::from_secs(3 * 3600);
+/// let query = Query::new()
+///     .covering(433.92e6)
+///     .between(morning, noon)
+///     .within(10_000.0, &Geolocation::new(14.60, -22.95));
+///
+/// let catalog = catalog::scan(dir.path())?;
+/// assert_eq!(catalog.query(&query).len(), 1);
+/// assert!(catalog.query(&query.labelled("lora")).is_empty());
+/// # Ok::<(), sigmf::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    band: Option<(f64, f64)>,
+    period: Option<(Timestamp, Timestamp)>,
+    near: Option<(Geolocation, f64)>,
+    labels: Vec<String>,
+}
+
+impl Query {
+    /// A query every Recording matches.
+    pub fn new() -> Query {
+        Query::default()
+    }
+
+    /// Recordings of `frequency`, in Hz: a segment's band must include it.
+    pub fn covering(self, frequency: f64) -> Self {
+        self.overlapping(frequency, frequency)
+    }
+
+    /// Recordings of any of the band from `lower` to `upper`, in Hz: a
+    /// segment's band must overlap it.
+    pub fn overlapping(mut self, lower: f64, upper: f64) -> Self {
+        self.band = Some((lower, upper));
+        self
+    }
+
+    /// Recordings made between `start` and `end`: a segment's time must
+    /// overlap it. A segment whose end is unknown is taken as its first
+    /// instant.
+    pub fn between(mut self, start: SystemTime, end: SystemTime) -> Self {
+        self.period = Some((
+            Timestamp::from_system_time(start),
+            Timestamp::from_system_time(end),
+        ));
+        self
+    }
+
+    /// Recordings made within `metres` of `point`, along the ground: the
+    /// great-circle distance on a spherical Earth, with altitude ignored.
+    pub fn within(mut self, metres: f64, point: &Geolocation) -> Self {
+        self.near = Some((point.clone(), metres));
+        self
+    }
+
+    /// Recordings with an annotation labelled `label`. Labels accumulate:
+    /// every one asked for must be there.
+    pub fn labelled(mut self, label: &str) -> Self {
+        self.labels.push(label.to_string());
+        self
+    }
+
+    /// Whether `entry` matches.
+    pub fn matches(&self, entry: &Entry) -> bool {
+        self.labels.iter().all(|label| entry.labels.contains(label))
+            && entry
+                .segments
+                .iter()
+                .any(|segment| self.matches_segment(segment))
+    }
+
+    fn matches_segment(&self, segment: &Segment) -> bool {
+        let band = self.band.is_none_or(|(lower, upper)| {
+            match (segment.lower_frequency, segment.upper_frequency) {
+                (Some(low), Some(high)) => low <= upper && lower <= high,
+                _ => false,
+            }
+        });
+        let period = self.period.is_none_or(|(start, end)| {
+            let parse = |time: &Option<String>| time.as_deref().and_then(Timestamp::parse);
+            match parse(&segment.start_time) {
+                Some(first) => {
+                    let last = parse(&segment.end_time).unwrap_or(first);
+                    first <= end && start <= last
+                }
+                None => false,
+            }
+        });
+        let near = self.near.as_ref().is_none_or(|(point, metres)| {
+            segment
+                .geolocation
+                .as_ref()
+                .is_some_and(|position| distance(position, point) <= *metres)
+        });
+        band && period && near
+    }
+}
+
+/// The great-circle distance between `a` and `b`, in metres, by the
+/// haversine formula.
+fn distance(a: &Geolocation, b: &Geolocation) -> f64 {
+    let (phi_a, phi_b) = (a.latitude.to_radians(), b.latitude.to_radians());
+    let d_phi = phi_b - phi_a;
+    let d_lambda = (b.longitude - a.longitude).to_radians();
+    let h =
+        (d_phi / 2.0).sin().powi(2) + phi_a.cos() * phi_b.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * h.sqrt().min(1.0).asin()
+}
+
+/// Catalog the loose Recording whose Metadata file is `path`, noting the
+/// Dataset it claims in `claimed`.
+fn scan_metadata_file(path: &Path, catalog: &mut Catalog, claimed: &mut HashSet<PathBuf>) {
+    let problem = |kind, detail: String| Problem {
+        path: path.to_path_buf(),
+        member: None,
+        kind,
+        detail,
+    };
+    let recording = match SigMF::from_file(path) {
+        Ok(recording) => recording,
+        Err(err @ Error::Metadata(MetadataError::DatasetPathEscapesDirectory(_))) => {
+            let kind = ProblemKind::DatasetPathEscapesDirectory;
+            return catalog.problems.push(problem(kind, err.to_string()));
+        }
+        Err(err) => {
+            return catalog
+                .problems
+                .push(problem(ProblemKind::Unreadable, err.to_string()))
+        }
+    };
+
+    let dataset_len = match recording.datafile.as_deref() {
+        None => None,
+        Some(dataset) => {
+            claimed.insert(dataset.to_path_buf());
+            match fs::metadata(dataset) {
+                Ok(found) => Some(found.len()),
+                Err(err) => {
+                    let detail = format!("{}: {err}", dataset.display());
+                    catalog
+                        .problems
+                        .push(problem(ProblemKind::MissingDataset, detail));
+                    None
+                }
+            }
+        }
+    };
+    summarize(path, None, &recording.metadata, dataset_len, catalog);
+}
+
+/// Catalog the Recordings inside the Archive at `path`, reading their
+/// Metadata members and the sizes of everything else.
+fn scan_archive(path: &Path, catalog: &mut Catalog) -> Result<(), Error> {
+    let mut documents = Vec::new();
+    let mut sizes = BTreeMap::new();
+    read_members(path, |member, data| {
+        if member.directory {
+            return Ok(());
+        }
+        sizes.insert(member.name.clone(), member.size);
+        if member.name.ends_with(SIGMF_METADATA_EXT) {
+            let mut json = Vec::new();
+            data.read_to_end(&mut json).map_err(at(path))?;
+            documents.push((member.name.clone(), json));
+        }
+        Ok(())
+    })?;
+
+    let mut claimed = HashSet::new();
+    for (name, json) in documents {
+        let problem = |kind, detail: String| Problem {
+            path: path.to_path_buf(),
+            member: Some(name.clone()),
+            kind,
+            detail,
+        };
+        let metadata: Metadata = match serde_json::from_slice(&json) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                let detail = Error::from(err).to_string();
+                catalog
+                    .problems
+                    .push(problem(ProblemKind::Unreadable, detail));
+                continue;
+            }
+        };
+        let dataset = match dataset_path(Path::new(&name), &metadata) {
+            Ok(dataset) => dataset,
+            Err(err) => {
+                let kind = ProblemKind::DatasetPathEscapesDirectory;
+                catalog.problems.push(problem(kind, err.to_string()));
+                continue;
+            }
+        };
+        let dataset_len = dataset.and_then(|dataset| {
+            let dataset = dataset.to_string_lossy().into_owned();
+            let len = sizes.get(&dataset).copied();
+            if len.is_none() {
+                let detail = format!("{dataset} is not in the Archive");
+                catalog
+                    .problems
+                    .push(problem(ProblemKind::MissingDataset, detail));
+            }
+            claimed.insert(dataset);
+            len
+        });
+        summarize(path, Some(&name), &metadata, dataset_len, catalog);
+    }
+
+    for name in sizes.keys() {
+        if name.ends_with(SIGMF_DATASET_EXT) && !claimed.contains(name) {
+            catalog.problems.push(Problem {
+                path: path.to_path_buf(),
+                member: Some(name.clone()),
+                kind: ProblemKind::OrphanDataset,
+                detail: "no Metadata file in this Archive names this Dataset".to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Catalog the Collection at `path`, checking each stream against the hash
+/// it recorded.
+fn scan_collection(path: &Path, catalog: &mut Catalog) {
+    let collection = match Collection::from_file(path) {
+        Ok(collection) => collection,
+        Err(err) => {
+            return catalog.problems.push(Problem {
+                path: path.to_path_buf(),
+                member: None,
+                kind: ProblemKind::Unreadable,
+                detail: err.to_string(),
+            })
+        }
+    };
+
+    let directory = path.parent().unwrap_or(Path::new(""));
+    for stream in &collection.streams {
+        let Some(metadata_path) = stream.metadata_path(directory) else {
+            catalog.problems.push(Problem {
+                path: path.to_path_buf(),
+                member: None,
+                kind: ProblemKind::CollectionStream,
+                detail: format!(
+                    "the stream {:?} is named outside the Collection's directory",
+                    stream.name
+                ),
+            });
+            continue;
+        };
+        let detail = match fs::read(&metadata_path) {
+            Err(err) => format!("{}: {err}", metadata_path.display()),
+            Ok(bytes)
+                if !stream
+                    .hash
+                    .eq_ignore_ascii_case(&hex_encode(&Sha512::digest(&bytes))) =>
+            {
+                MetadataError::StreamHashMismatch {
+                    name: stream.name.clone(),
+                }
+                .to_string()
+            }
+            Ok(_) => continue,
+        };
+        catalog.problems.push(Problem {
+            path: path.to_path_buf(),
+            member: None,
+            kind: ProblemKind::CollectionStream,
+            detail,
+        });
+    }
+
+    catalog.collections.push(CollectionEntry {
+        path: path.to_path_buf(),
+        description: collection.description,
+        streams: collection.streams.into_iter().map(|s| s.name).collect(),
+    });
+}
+
+/// Add the entry for `metadata`, whose Dataset is `dataset_len` bytes long
+/// if it was found, to `catalog` — or a problem, if the two do not fit.
+fn summarize(
+    path: &Path,
+    member: Option<&str>,
+    metadata: &Metadata,
+    dataset_len: Option<u64>,
+    catalog: &mut Catalog,
+) {
+    let sample_count = match dataset_len.map(|len| metadata.sample_count(len)) {
+        Some(Err(err)) => {
+            return catalog.problems.push(Problem {
+                path: path.to_path_buf(),
+                member: member.map(str::to_string),
+                kind: ProblemKind::Unreadable,
+                detail: err.to_string(),
+            })
+        }
+        Some(Ok(count)) => Some(count),
+        None => None,
+    };
+    let global = &metadata.global;
+    let rate = global.sample_rate;
+    let half_band = rate.map_or(0.0, |rate| rate / 2.0);
+
+    // A document without captures has one implicit segment from the Dataset's
+    // first sample, whose index is `core:offset`.
+    let offset = global.offset.unwrap_or(0);
+    let implicit = [CaptureMetadata::new(offset)];
+    let captures = if metadata.captures.is_empty() {
+        &implicit[..]
+    } else {
+        &metadata.captures[..]
+    };
+
+    let mut segments = Vec::with_capacity(captures.len());
+    let mut times = Vec::with_capacity(captures.len());
+    let mut last_timed: Option<(Timestamp, u64)> = None;
+    for (index, capture) in captures.iter().enumerate() {
+        let start = match capture.datetime.as_deref().and_then(Timestamp::parse) {
+            Some(start) => Some(start),
+            None => last_timed.zip(rate).map(|((time, from), rate)| {
+                time.add_seconds(metadata.stream_start(index).saturating_sub(from) as f64 / rate)
+            }),
+        };
+        if let Some(start) = start {
+            last_timed = Some((start, metadata.stream_start(index)));
+        }
+        let next = captures.get(index + 1).map(|c| c.sample_start);
+        let samples = next
+            .or(sample_count.map(|count| offset.saturating_add(count)))
+            .map(|end| end.saturating_sub(capture.sample_start));
+        let end = start
+            .zip(samples.zip(rate))
+            .map(|(start, (samples, rate))| start.add_seconds(samples as f64 / rate));
+        segments.push(Segment {
+            sample_start: capture.sample_start,
+            lower_frequency: capture.frequency.map(|f| f - half_band),
+            upper_frequency: capture.frequency.map(|f| f + half_band),
+            start_time: start.map(|time| time.to_string()),
+            end_time: end.map(|time| time.to_string()),
+            geolocation: capture
+                .geolocation
+                .clone()
+                .or_else(|| global.geolocation.clone()),
+        });
+        times.push((start, end));
+    }
+
+    let lower_frequency = segments
+        .iter()
+        .filter_map(|s| s.lower_frequency)
+        .reduce(f64::min);
+    let upper_frequency = segments
+        .iter()
+        .filter_map(|s| s.upper_frequency)
+        .reduce(f64::max);
+    let timed = times.iter().filter(|(start, _)| start.is_some());
+    let start_time = timed.clone().filter_map(|(start, _)| *start).min();
+    // One timed segment whose end cannot be known leaves the Recording's
+    // unknown too, rather than understated.
+    let end_time = timed
+        .map(|(_, end)| *end)
+        .collect::<Option<Vec<Timestamp>>>()
+        .and_then(|ends| ends.into_iter().max());
+
+    let labels: BTreeSet<&String> = metadata
+        .annotations
+        .iter()
+        .filter_map(|a| a.label.as_ref())
+        .collect();
+
+    catalog.recordings.push(Entry {
+        path: path.to_path_buf(),
+        member: member.map(str::to_string),
+        datatype: global.datatype,
+        sample_rate: rate,
+        sample_count,
+        duration: sample_count
+            .zip(rate)
+            .map(|(count, rate)| count as f64 / rate),
+        lower_frequency,
+        upper_frequency,
+        start_time: start_time.map(|time| time.to_string()),
+        end_time: end_time.map(|time| time.to_string()),
+        geolocation: global
+            .geolocation
+            .clone()
+            .or_else(|| metadata.captures.iter().find_map(|c| c.geolocation.clone())),
+        sha512: global.sha512.clone(),
+        labels: labels.into_iter().cloned().collect(),
+        segments,
+    });
+}
@@ -0,0 +1,77 @@
+//! Checking `core:sha512` against the Dataset it claims to describe.
+
+use std::fs;
+
+use sha2::Digest;
+
+use super::{at, hex_encode, parallel, Error, MetadataError, SigMF};
+
+impl SigMF {
+    /// The SHA-512 of the Dataset file, as lowercase hex — the value
+    /// `core:sha512` should hold.
+    ///
+    /// Of the whole file, headers and trailing bytes of a Non-Conforming
+    /// Dataset included, because that is what the specification hashes. The
+    /// file is streamed, so this costs a read of the Dataset and no more
+    /// memory than a read buffer — two with the `rayon` feature, which reads
+    /// each buffer while hashing the last.
+    ///
+    /// # Errors
+    ///
+    /// [`MetadataError::NoDataset`], or [`Error::Io`] naming the Dataset.
+    pub fn dataset_sha512(&self) -> Result<String, Error> {
+        let path = self.datafile.as_ref().ok_or(MetadataError::NoDataset)?;
+        let file = fs::File::open(path).map_err(at(path))?;
+        let hasher = parallel::sha512_of(file).map_err(at(path))?;
+        Ok(hex_encode(&hasher.finalize()))
+    }
+
+    /// Check that the Dataset is the one `core:sha512` was computed over.
+    ///
+    /// Opening a Recording does not do this — it would read every byte of a
+    /// Dataset that may be gigabytes long, to answer a question most readers
+    /// never ask. Ask it here when it matters: after a transfer, or before
+    /// trusting an Archive from elsewhere. The comparison ignores case, as the
+    /// schema permits either.
+    ///
+    /// # Errors
+    ///
+    /// [`MetadataError::Sha512Mismatch`] if the hashes differ,
+    /// [`MetadataError::NoSha512`] if the document records none, and any error
+    /// of [`dataset_sha512`](Self::dataset_sha512).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sigmf::{Error, MetadataError, RecordingWriter};
+    /// # let dir = tempfile::tempdir().expect("a temporary directory");
+    ///
+    /// let recording = RecordingWriter::new(&[1.0f32, 2.0], 32_000.0)
+    ///     .to_file(dir.path().join("watch"))?;
+    /// recording.verify_sha512()?;
+    ///
+    /// std::fs::write(dir.path().join("watch.sigmf-data"), [0u8; 8])?;
+    /// assert!(matches!(
+    ///     recording.verify_sha512(),
+    ///     Err(Error::Metadata(MetadataError::Sha512Mismatch { .. }))
+    /// ));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn verify_sha512(&self) -> Result<(), Error> {
+        let recorded = self
+            .metadata
+            .global
+            .sha512
+            .as_ref()
+            .ok_or(MetadataError::NoSha512)?;
+        let computed = self.dataset_sha512()?;
+        if !recorded.eq_ignore_ascii_case(&computed) {
+            return Err(MetadataError::Sha512Mismatch {
+                recorded: recorded.clone(),
+                computed,
+            }
+            .into());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+//! SigMF Collections: Recordings that belong together, such as the channels of
+//! one multi-channel receiver.
+
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha512};
+
+use super::archive::safe_path;
+use super::{
+    append_extension, at, finish_file, hex_encode, invalid, Error, Extension, Map, MetadataError,
+    SigMF, SIGMF_VERSION,
+};
+use crate::SIGMF_METADATA_EXT;
+
+/// The contents of a `.sigmf-collection` file: which Recordings belong to the
+/// Collection, and a hash of each one's Metadata file.
+///
+/// The specification's answer to more than one channel. A Dataset with
+/// `core:num_channels` above 1 interleaves its channels into one file that
+/// most tools cannot read; a Collection keeps each channel a Recording of its
+/// own and says which ones go together. The hash pins each Recording's
+/// document as it was when the Collection was made, so a Metadata file edited
+/// since is caught by [`open_recordings`](Self::open_recordings) rather than
+/// trusted.
+///
+/// # Examples
+///
+/// ```
+/// use sigmf::{Collection, RecordingWriter};
+/// # let dir = tempfile::tempdir().expect("a temporary directory");
+///
+/// for band in ["hf8", "hf16"] {
+///     RecordingWriter::new(&[1.0f32, 2.0], 32_000.0).to_file(dir.path().join(band))?;
+/// }
+/// let path = dir.path().join("walvisbay.sigmf-collection");
+/// let collection = Collection::create(
+///     &path,
+///     &[dir.path().join("hf8.sigmf-meta"), dir.path().join("hf16.sigmf-meta")],
+/// )?;
+/// assert_eq!(collection.streams[1].name, "hf16");
+///
+/// let recordings = Collection::from_file(&path)?.open_recordings(&path)?;
+/// assert_eq!(recordings[0].metadata.global.collection.as_deref(), Some("walvisbay"));
+/// # Ok::<(), sigmf::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Collection {
+    /// The version of the SigMF specification this Collection is written to.
+    #[serde(rename = "core:version")]
+    pub version: String,
+
+    /// A human-readable description of the Collection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "core:description")]
+    pub description: Option<String>,
+
+    /// Who made the Collection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "core:author")]
+    pub author: Option<String>,
+
+    /// The DOI (ISO 26324) registered for this Collection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "core:collection_doi")]
+    pub collection_doi: Option<String>,
+
+    /// A URL for the license the Collection is offered under.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "core:license")]
+    pub license: Option<String>,
+
+    /// The SigMF extension namespaces this Collection uses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "core:extensions")]
+    pub extensions: Option<Vec<Extension>>,
+
+    /// The Recordings in the Collection, in order.
+    #[serde(default)]
+    #[serde(rename = "core:streams")]
+    pub streams: Vec<Stream>,
+
+    /// Every key in the `collection` object that the fields above do not model.
+    #[serde(flatten)]
+    pub other: Map<String, Value>,
+}
+
+/// One Recording in a [`Collection`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Stream {
+    /// The Recording's basename, relative to the directory holding the
+    /// `.sigmf-collection` file.
+    pub name: String,
+    /// SHA-512 of the Recording's Metadata file — not its Dataset, which its
+    /// own `core:sha512` covers — as lowercase hex.
+    pub hash: String,
+}
+
+/// The file's outer object, which holds nothing but the Collection.
+#[derive(Deserialize, Serialize)]
+struct CollectionFile {
+    collection: Collection,
+}
+
+impl Default for Collection {
+    fn default() -> Self {
+        Collection::new()
+    }
+}
+
+impl Collection {
+    /// An empty Collection, written to the version this crate implements.
+    pub fn new() -> Collection {
+        Collection {
+            version: SIGMF_VERSION.to_string(),
+            description: None,
+            author: None,
+            collection_doi: None,
+            license: None,
+            extensions: None,
+            streams: Vec::new(),
+            other: Map::new(),
+        }
+    }
+
+    /// Read a `.sigmf-collection` file.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Io`] if the file cannot be read, [`Error::Json`] if it is not a
+    /// Collection.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Collection, Error> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).map_err(at(path))?;
+        let file: CollectionFile = serde_json::from_str(&json)?;
+        Ok(file.collection)
+    }
+
+    /// Write this Collection to `path`, which should end `.sigmf-collection`.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Io`] if the file cannot be written.
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(&CollectionFile {
+            collection: self.clone(),
+        })?;
+        fs::write(path, json).map_err(at(path))
+    }
+
+    /// Make a Collection of the Recordings whose Metadata files are
+    /// `recordings`, and write it to `path`.
+    ///
+    /// Each Recording is first told it belongs: its `core:collection` is set
+    /// to the Collection's basename and its Metadata file rewritten, which is
+    /// why this takes paths rather than opened Recordings — the hash recorded
+    /// is of the file as it stands afterwards. Every Recording must be in the
+    /// directory holding `path`, or below it, because a stream is named
+    /// relative to it.
+    ///
+    /// Every Recording is opened and checked before any is rewritten, so one
+    /// that cannot be leaves the others as they were. Each rewrite goes to a
+    /// new file beside the old one, which replaces it only once it is on disk:
+    /// a Metadata file is never left half written.
+    ///
+    /// # Errors
+    ///
+    /// Any error of [`SigMF::from_file`] for a Recording, and [`Error::Io`] if
+    /// a file cannot be written or a Recording lies outside the Collection's
+    /// directory.
+    pub fn create<P: AsRef<Path>, Q: AsRef<Path>>(
+        path: P,
+        recordings: &[Q],
+    ) -> Result<Collection, Error> {
+        let path = path.as_ref();
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let basename = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .map(|name| {
+                name.strip_suffix(crate::SIGMF_COLLECTION_EXT)
+                    .unwrap_or(&name)
+                    .to_string()
+            })
+            .unwrap_or_default();
+
+        let mut rewritten = Vec::with_capacity(recordings.len());
+        for metadata_path in recordings {
+            let metadata_path = metadata_path.as_ref();
+            let name = stream_name(directory, metadata_path).ok_or_else(|| Error::Io {
+                path: metadata_path.to_path_buf(),
+                source: std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "a Recording in a Collection must be a `.sigmf-meta` file in the \
+                     Collection's directory or below it",
+                ),
+            })?;
+
+            let mut recording = SigMF::from_file(metadata_path)?;
+            recording.metadata.global.collection = Some(basename.clone());
+            rewritten.push((metadata_path, name, recording.metadata.to_json()?));
+        }
+
+        let mut collection = Collection::new();
+        for (metadata_path, name, json) in rewritten {
+            replace(metadata_path, json.as_bytes())?;
+            collection.streams.push(Stream {
+                name,
+                hash: hex_encode(&Sha512::digest(json.as_bytes())),
+            });
+        }
+        collection.to_file(path)?;
+        Ok(collection)
+    }
+
+    /// Open every Recording in this Collection, which was read from `path`,
+    /// checking each Metadata file against the hash the Collection recorded.
+    ///
+    /// # Errors
+    ///
+    /// [`MetadataError::StreamHashMismatch`] naming the first Recording whose
+    /// Metadata file has changed, [`Error::Io`] naming `path` if a stream's
+    /// name is absolute or climbs out of the Collection's directory with
+    /// `..`, and any error of [`SigMF::from_file`].
+    pub fn open_recordings<P: AsRef<Path>>(&self, path: P) -> Result<Vec<SigMF>, Error> {
+        let path = path.as_ref();
+        let directory = path.parent().unwrap_or(Path::new(""));
+        self.streams
+            .iter()
+            .map(|stream| {
+                let metadata_path = stream.metadata_path(directory).ok_or_else(|| {
+                    invalid(
+                        path,
+                        "a Collection stream named outside the Collection's directory",
+                    )
+                })?;
+                let bytes = fs::read(&metadata_path).map_err(at(&metadata_path))?;
+                if !stream
+                    .hash
+                    .eq_ignore_ascii_case(&hex_encode(&Sha512::digest(&bytes)))
+                {
+                    return Err(MetadataError::StreamHashMismatch {
+                        name: stream.name.clone(),
+                    }
+                    .into());
+                }
+                SigMF::from_file(&metadata_path)
+            })
+            .collect()
+    }
+}
+
+impl Stream {
+    /// The Metadata file of this stream of a Collection in `directory`, or
+    /// `None` if its name is absolute or climbs out of `directory` — a
+    /// Collection, possibly fetched from anywhere, directing a reader at a
+    /// path of its choosing.
+    pub(super) fn metadata_path(&self, directory: &Path) -> Option<PathBuf> {
+        let name = safe_path(&self.name)?;
+        Some(directory.join(append_extension(&name, SIGMF_METADATA_EXT)))
+    }
+}
+
+/// The name `metadata_path` is given as a stream of a Collection in
+/// `directory`: its path below `directory`, less the `.sigmf-meta` extension.
+fn stream_name(directory: &Path, metadata_path: &Path) -> Option<String> {
+    let absolute = |path: &Path| -> Option<PathBuf> {
+        let path = if path.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            path
+        };
+        fs::canonicalize(path).ok()
+    };
+    let relative = absolute(metadata_path)?
+        .strip_prefix(absolute(directory)?)
+        .ok()?
+        .to_path_buf();
+    let relative: Vec<String> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    relative
+        .join("/")
+        .strip_suffix(SIGMF_METADATA_EXT)
+        .map(str::to_string)
+}
+
+/// Replace the file at `path` with `contents`, all at once.
+///
+/// `fs::write` truncates first, so a crash or a full disk partway through
+/// leaves neither the old Metadata file nor the new one. The new contents go
+/// to a file in the same directory — so on the same file system, where a
+/// rename is atomic — and are synced before that file is renamed over `path`.
+fn replace(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let temporary = append_extension(path, ".tmp");
+    let written = fs::File::create(&temporary)
+        .map_err(at(&temporary))
+        .and_then(|file| {
+            let mut out = BufWriter::new(file);
+            out.write_all(contents).map_err(at(&temporary))?;
+            finish_file(out, &temporary)
+        })
+        .and_then(|()| fs::rename(&temporary, path).map_err(at(path)));
+    if written.is_err() {
+        // Best effort: the error being returned is the one worth reading.
+        let _ = fs::remove_file(&temporary);
+    }
+    written
+}
@@ -0,0 +1,214 @@
+//! Joining consecutive Recordings into one.
+
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use super::dataset::{read_range, stream_index, DatasetWriter, Segment};
+use super::{CaptureMetadata, Error, GlobalMetadata, Metadata, MetadataError, SigMF};
+
+impl SigMF {
+    /// Write this Recording followed by each of `rest` as one new Recording,
+    /// `basename.sigmf-data` and `basename.sigmf-meta`, and return it opened.
+    ///
+    /// The counterpart to [`slice`](Self::slice), for the recorder that rolls
+    /// over to a new file every few minutes. The Datasets are appended byte for
+    /// byte, streamed rather than loaded, and every index in each document is
+    /// moved to where its sample now sits — counted, as sample indices are,
+    /// from this Recording's `core:offset`:
+    ///
+    /// - **Captures.** Every segment of every input survives, with its
+    ///   `core:frequency`, `core:datetime` and the rest as they were, and its
+    ///   `core:sample_start` moved to where its samples now begin. An input that
+    ///   says where it sat in its stream, by `core:offset` or
+    ///   `core:global_index`, has its segments carry `core:global_index`
+    ///   wherever that is not where they now sit — so two files recorded with
+    ///   a gap between them still say so. One that says neither is taken to
+    ///   follow on from the input before it.
+    /// - **Annotations.** Every annotation survives, moved with its samples. One
+    ///   without `core:sample_count` ran to the end of its own Recording, and is
+    ///   given the count that says so unless that Recording is the last.
+    /// - **Global.** This Recording's, with `core:sha512` computed over the new
+    ///   Dataset and the fields that describe one particular file —
+    ///   `core:dataset`, `core:trailing_bytes`, `core:metadata_only` and the two
+    ///   DOIs — dropped. `core:offset` is this Recording's.
+    ///
+    /// # One Global for all of them
+    ///
+    /// A Recording has a single Global, so the inputs must agree on every field
+    /// of theirs except those listed above as describing one file. Sample rate
+    /// and datatype are the obvious ones, but an `antenna:model` or a
+    /// `core:hw` that changed between files changes what the samples mean as
+    /// surely. The inputs are `pub`; a field that differs harmlessly, such as a
+    /// per-file `core:description`, can be made to agree before joining.
+    ///
+    /// # Errors
+    ///
+    /// [`MetadataError::ConflictingGlobals`] naming the first field that
+    /// differs, [`MetadataError::MultiChannelDataset`],
+    /// [`MetadataError::NoDataset`], [`MetadataError::PartialSample`], or
+    /// [`Error::Io`] — including when `basename` names one of the inputs. Every
+    /// error but the last is raised before either file is created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sigmf::{CaptureMetadata, RecordingWriter};
+    /// # let dir = tempfile::tempdir().expect("a temporary directory");
+    ///
+    /// let write = |name: &str, samples: &[f32], datetime: &str| {
+    ///     let mut writer = RecordingWriter::new(samples, 32_000.0);
+    ///     let mut capture = CaptureMetadata::new(0);
+    ///     capture.datetime = Some(datetime.to_string());
+    ///     writer.captures_mut().push(capture);
+    ///     writer.to_file(dir.path().join(name))
+    /// };
+    /// let first = write("0914", &[1.0, 2.0, 3.0], "2026-07-16T09:14:00.000Z")?;
+    /// let second = write("0915", &[4.0, 5.0], "2026-07-16T09:15:00.000Z")?;
+    ///
+    /// let watch = first.concat(&[&second], dir.path().join("watch"))?;
+    /// assert_eq!(watch.samples::<f32>()?, [1.0, 2.0, 3.0, 4.0, 5.0]);
+    /// assert_eq!(watch.metadata.captures[1].sample_start, 3);
+    /// assert_eq!(
+    ///     watch.metadata.captures[1].datetime.as_deref(),
+    ///     Some("2026-07-16T09:15:00.000Z")
+    /// );
+    /// # Ok::<(), sigmf::Error>(())
+    /// ```
+    pub fn concat<P: AsRef<Path>>(&self, rest: &[&SigMF], basename: P) -> Result<SigMF, Error> {
+        let inputs: Vec<&SigMF> = std::iter::once(self).chain(rest.iter().copied()).collect();
+
+        let shared = shared_fields(&self.metadata.global)?;
+        for (index, input) in inputs.iter().enumerate() {
+            if let Some(channels) = input.metadata.global.num_channels {
+                if channels != 1 {
+                    return Err(MetadataError::MultiChannelDataset(channels).into());
+                }
+            }
+            agree(&shared, &shared_fields(&input.metadata.global)?, index)?;
+        }
+
+        let mut opened = Vec::with_capacity(inputs.len());
+        let mut sources = Vec::with_capacity(inputs.len());
+        for input in &inputs {
+            opened.push(input.open_dataset()?);
+            sources.push(input.dataset_file()?);
+        }
+        let segments: Vec<&[Segment]> = opened.iter().map(|(_, s)| s.as_slice()).collect();
+        let metadata = concatenated_metadata(&inputs, &segments);
+
+        let mut writer = DatasetWriter::create(basename.as_ref(), &sources)?;
+        for ((file, segments), source) in opened.iter_mut().zip(&sources) {
+            for segment in segments.iter() {
+                read_range(file, source, segment.bytes.clone(), |bytes| {
+                    writer.write(bytes)
+                })?;
+            }
+        }
+        writer.finish(metadata)
+    }
+}
+
+/// The document for `inputs` joined end to end, given each one's segments.
+fn concatenated_metadata(inputs: &[&SigMF], segments: &[&[Segment]]) -> Metadata {
+    let mut global = inputs[0].metadata.global.clone();
+    clear_per_file_fields(&mut global);
+    global.offset = inputs[0].metadata.global.offset;
+    let first_index = global.offset.unwrap_or(0);
+
+    let mut captures = Vec::new();
+    let mut annotations = Vec::new();
+    let mut shift = 0;
+    for (position, (input, segments)) in inputs.iter().zip(segments).enumerate() {
+        let source = &input.metadata;
+        let source_offset = source.global.offset.unwrap_or(0);
+        let sample_count = segments.last().map_or(0, |s| s.samples.end);
+        // Where this input's Dataset sample 0 now sits.
+        let base = first_index.saturating_add(shift);
+        // An input that never said where it was in its stream has no place in
+        // it to keep, and an index made up for it would read as an overlap.
+        let placed = source.global.offset.is_some()
+            || source.captures.iter().any(|c| c.global_index.is_some());
+
+        // A segment holding no samples has nothing left to describe.
+        for segment in segments.iter().filter(|s| !s.samples.is_empty()) {
+            let mut capture = source
+                .captures
+                .get(segment.index)
+                .cloned()
+                .unwrap_or_else(|| CaptureMetadata::new(0));
+            capture.sample_start = base.saturating_add(segment.samples.start);
+            capture.header_bytes = None;
+
+            let index = stream_index(source, segment, segment.samples.start);
+            capture.global_index =
+                Some(index).filter(|&index| placed && index != capture.sample_start);
+            captures.push(capture);
+        }
+
+        let last = position + 1 == inputs.len();
+        for annotation in &source.annotations {
+            let start = annotation.sample_start.saturating_sub(source_offset);
+            let mut moved = annotation.clone();
+            moved.sample_start = base.saturating_add(start);
+            if moved.sample_count.is_none() && !last {
+                moved.sample_count = Some(sample_count.saturating_sub(start));
+            }
+            annotations.push(moved);
+        }
+
+        shift += sample_count;
+    }
+
+    Metadata {
+        global,
+        captures,
+        annotations,
+    }
+}
+
+/// Clear the Global fields that describe one particular Dataset file rather
+/// than the samples in it.
+fn clear_per_file_fields(global: &mut GlobalMetadata) {
+    global.sha512 = None;
+    global.offset = None;
+    global.dataset = None;
+    global.trailing_bytes = None;
+    global.metadata_only = None;
+    global.meta_doi = None;
+    global.data_doi = None;
+}
+
+/// The Global fields every input to a concatenation must agree on, keyed as
+/// the document spells them.
+fn shared_fields(global: &GlobalMetadata) -> Result<Map<String, Value>, serde_json::Error> {
+    let mut global = global.clone();
+    clear_per_file_fields(&mut global);
+    match serde_json::to_value(global)? {
+        Value::Object(fields) => Ok(fields),
+        _ => unreachable!("GlobalMetadata serializes to a JSON object"),
+    }
+}
+
+/// `Ok` if the `index`th input's shared fields are the first's.
+fn agree(
+    first: &Map<String, Value>,
+    other: &Map<String, Value>,
+    index: usize,
+) -> Result<(), MetadataError> {
+    let keys: std::collections::BTreeSet<&String> = first.keys().chain(other.keys()).collect();
+    for key in keys {
+        let (expected, found) = (first.get(key), other.get(key));
+        if expected != found {
+            let describe =
+                |value: Option<&Value>| value.map_or("absent".to_string(), Value::to_string);
+            return Err(MetadataError::ConflictingGlobals {
+                index,
+                key: key.clone(),
+                expected: describe(expected),
+                found: describe(found),
+            });
+        }
+    }
+    Ok(())
+}
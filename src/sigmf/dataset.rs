@@ -0,0 +1,224 @@
+//! The plumbing every operation that streams a Dataset shares: where each
+//! segment's samples are, and how a new Dataset is written and hashed.
+//!
+//! [`SigMF::samples`] and [`RecordingWriter::to_file`] hold the whole Dataset in
+//! memory, which is what their signatures promise. The operations built on this
+//! module promise the opposite — a slice of an hour-long Recording should cost
+//! the slice, not the hour — so they read and write in pieces, and need the
+//! arithmetic `capture_boundaries` does in both index spaces at once.
+
+use std::fs;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha512};
+
+use super::{append_extension, at, finish_file, hex_encode, Error, Metadata, MetadataError, SigMF};
+use crate::{SIGMF_DATASET_EXT, SIGMF_METADATA_EXT};
+
+/// How many bytes to move per read while streaming a Dataset.
+///
+/// A whole number of samples for every datatype, so that a buffer never ends
+/// partway through one.
+pub(super) const CHUNK_BYTES: usize = 1 << 20;
+
+/// One Captures segment, located both by sample index and by byte offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Segment {
+    /// Position in the `captures` array — or 0 for the implicit segment of a
+    /// document whose array is empty.
+    pub(super) index: usize,
+    /// The Dataset-relative sample indices the segment holds.
+    pub(super) samples: Range<u64>,
+    /// Where those samples' bytes are in the Dataset file.
+    pub(super) bytes: Range<u64>,
+}
+
+impl Segment {
+    /// The bytes holding `samples`, which must lie within this segment.
+    pub(super) fn bytes_of(&self, samples: &Range<u64>, sample_size: u64) -> Range<u64> {
+        let start = self.bytes.start + (samples.start - self.samples.start) * sample_size;
+        start..start + (samples.end - samples.start) * sample_size
+    }
+}
+
+impl Metadata {
+    /// Every segment of a Dataset `dataset_len` bytes long, in both index
+    /// spaces.
+    ///
+    /// [`capture_boundaries`](Metadata::capture_boundaries) with the other half
+    /// of the answer filled in, and with the check [`SigMF::samples`] makes of
+    /// each segment: that its bytes are a whole number of samples.
+    pub(super) fn segments(&self, dataset_len: u64) -> Result<Vec<Segment>, MetadataError> {
+        let sample_size = self.global.datatype.size();
+        let boundaries = self.capture_boundaries(dataset_len)?;
+        boundaries
+            .into_iter()
+            .enumerate()
+            .map(|(index, bytes)| {
+                let len = bytes.end - bytes.start;
+                if len % sample_size != 0 {
+                    return Err(MetadataError::PartialSample {
+                        bytes: len,
+                        datatype: self.global.datatype,
+                    });
+                }
+                let first = self
+                    .captures
+                    .get(index)
+                    .map_or(0, |c| self.dataset_sample(c.sample_start));
+                Ok(Segment {
+                    index,
+                    samples: first..first + len / sample_size,
+                    bytes,
+                })
+            })
+            .collect()
+    }
+}
+
+impl SigMF {
+    /// The Dataset file, opened, with its segments.
+    pub(super) fn open_dataset(&self) -> Result<(fs::File, Vec<Segment>), Error> {
+        let path = self.datafile.as_ref().ok_or(MetadataError::NoDataset)?;
+        let file = fs::File::open(path).map_err(at(path))?;
+        let dataset_len = file.metadata().map_err(at(path))?.len();
+        Ok((file, self.metadata.segments(dataset_len)?))
+    }
+
+    /// The Dataset file's path, for naming it in an error.
+    pub(super) fn dataset_file(&self) -> Result<&Path, MetadataError> {
+        self.datafile.as_deref().ok_or(MetadataError::NoDataset)
+    }
+}
+
+/// The index of Dataset sample `sample`, which lies in `segment`, in the
+/// original stream.
+///
+/// The sample's absolute index is `core:offset` plus `sample`, and a segment
+/// without `core:global_index` starts where its `core:sample_start` says; an
+/// index past `u64::MAX` is `u64::MAX`, as [`Metadata::global_index`] gives it.
+pub(super) fn stream_index(metadata: &Metadata, segment: &Segment, sample: u64) -> u64 {
+    let offset = metadata.global.offset.unwrap_or(0);
+    let start = metadata
+        .captures
+        .get(segment.index)
+        .map_or(offset, |capture| capture.sample_start);
+    metadata
+        .stream_start(segment.index)
+        .saturating_add(offset.saturating_add(sample).saturating_sub(start))
+}
+
+/// Read `bytes` of `file`, in chunks of at most [`CHUNK_BYTES`], handing each to
+/// `visit`.
+pub(super) fn read_range(
+    file: &mut fs::File,
+    path: &Path,
+    bytes: Range<u64>,
+    mut visit: impl FnMut(&[u8]) -> Result<(), Error>,
+) -> Result<(), Error> {
+    file.seek(SeekFrom::Start(bytes.start)).map_err(at(path))?;
+    let mut remaining = bytes.end - bytes.start;
+    let mut buffer = vec![0u8; CHUNK_BYTES.min(remaining as usize)];
+    while remaining > 0 {
+        let chunk = &mut buffer[..CHUNK_BYTES.min(remaining as usize)];
+        file.read_exact(chunk).map_err(at(path))?;
+        visit(chunk)?;
+        remaining -= chunk.len() as u64;
+    }
+    Ok(())
+}
+
+/// A new `<basename>.sigmf-data`, hashed as it is written.
+///
+/// Written through once, front to back, and then handed to
+/// [`finish`](Self::finish) along with the document describing it — which
+/// writes the Metadata file *after* the Dataset, for the reason
+/// [`RecordingWriter::to_file`](super::RecordingWriter::to_file) gives.
+pub(super) struct DatasetWriter {
+    basename: PathBuf,
+    path: PathBuf,
+    file: BufWriter<fs::File>,
+    hasher: Sha512,
+}
+
+impl DatasetWriter {
+    /// Create `<basename>.sigmf-data`, refusing if it is one of the files in
+    /// `reading`.
+    ///
+    /// Creating a file truncates it, so writing a Recording over the Dataset it
+    /// is being streamed out of would destroy the source before a byte of it
+    /// was read. The same file under another name counts: a hard link, such as
+    /// [`RawImport::hard_link`](super::RawImport::hard_link) makes, has no
+    /// path in common with its source, only an inode.
+    pub(super) fn create(basename: &Path, reading: &[&Path]) -> Result<DatasetWriter, Error> {
+        let path = append_extension(basename, SIGMF_DATASET_EXT);
+        if reading.iter().any(|source| same_file(source, &path)) {
+            return Err(Error::Io {
+                source: std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    "this is the Dataset being read, and writing would truncate it",
+                ),
+                path,
+            });
+        }
+        let file = fs::File::create(&path).map_err(at(&path))?;
+        Ok(DatasetWriter {
+            basename: basename.to_path_buf(),
+            path,
+            file: BufWriter::new(file),
+            hasher: Sha512::new(),
+        })
+    }
+
+    pub(super) fn write(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.hasher.update(bytes);
+        self.file.write_all(bytes).map_err(at(&self.path))
+    }
+
+    /// Close the Dataset, and write `metadata` beside it with `core:sha512` set
+    /// to the hash of what was written.
+    ///
+    /// Every other field of `metadata` is the caller's to get right, including
+    /// `core:datatype`.
+    pub(super) fn finish(self, mut metadata: Metadata) -> Result<SigMF, Error> {
+        let DatasetWriter {
+            basename,
+            path,
+            file,
+            hasher,
+        } = self;
+        finish_file(file, &path)?;
+
+        metadata.global.sha512 = Some(hex_encode(&hasher.finalize()));
+        let metadata_path = append_extension(&basename, SIGMF_METADATA_EXT);
+        fs::write(&metadata_path, metadata.to_json()?).map_err(at(&metadata_path))?;
+
+        Ok(SigMF {
+            metadata,
+            datafile: Some(path),
+        })
+    }
+}
+
+/// Whether `a` and `b` both exist and are one file: the same device and inode
+/// on Unix, which sees through hard links as well as symbolic ones, and the
+/// same canonical path elsewhere.
+fn same_file(a: &Path, b: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (fs::metadata(a), fs::metadata(b)) {
+            (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+            _ => false,
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        match (fs::canonicalize(a), fs::canonicalize(b)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
+}
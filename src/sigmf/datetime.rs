@@ -0,0 +1,218 @@
+//! `core:datetime` arithmetic: parse, move by a number of samples, write back.
+//!
+//! The field stays a `String` on [`CaptureMetadata`](super::CaptureMetadata) —
+//! this is the minimum needed for the operations that must *move* a timestamp
+//! when they move a segment's first sample, and nothing here is public. The
+//! grammar is the specification's: RFC 3339, with `Z` as the only offset.
+
+use std::fmt::Write as _;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// An instant, as seconds and nanoseconds since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) struct Timestamp {
+    seconds: i64,
+    nanos: u32,
+}
+
+impl Timestamp {
+    /// Parse `YYYY-MM-DDTHH:MM:SS[.fraction]Z`.
+    ///
+    /// `None` for anything else, including an offset other than `Z`, which the
+    /// specification forbids. Fractional digits past the ninth are dropped: they
+    /// are below the resolution of any sample rate the schema permits.
+    pub(super) fn parse(s: &str) -> Option<Timestamp> {
+        let bytes = s.as_bytes();
+        let digits = |range: std::ops::Range<usize>| -> Option<i64> {
+            let field = bytes.get(range)?;
+            if !field.iter().all(u8::is_ascii_digit) {
+                return None;
+            }
+            std::str::from_utf8(field).ok()?.parse().ok()
+        };
+
+        let year = digits(0..4)?;
+        let month = digits(5..7)?;
+        let day = digits(8..10)?;
+        let hour = digits(11..13)?;
+        let minute = digits(14..16)?;
+        let second = digits(17..19)?;
+        let separators = [(4, b'-'), (7, b'-'), (13, b':'), (16, b':')];
+        if separators.iter().any(|&(at, byte)| bytes[at] != byte)
+            || !matches!(bytes[10], b'T' | b't')
+        {
+            return None;
+        }
+
+        let mut rest = &bytes[19..];
+        let mut nanos = 0u32;
+        if let Some(fraction) = rest.strip_prefix(b".") {
+            let len = fraction.iter().take_while(|b| b.is_ascii_digit()).count();
+            if len == 0 {
+                return None;
+            }
+            for (place, digit) in fraction[..len].iter().take(9).enumerate() {
+                nanos += u32::from(digit - b'0') * 10u32.pow(8 - place as u32);
+            }
+            rest = &fraction[len..];
+        }
+        if !matches!(rest, b"Z" | b"z") {
+            return None;
+        }
+
+        let days_in_month = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+            2 => 28,
+            _ => return None,
+        };
+        // Second 60 is a leap second, which RFC 3339 permits and Unix time
+        // cannot represent; it lands on the first second of the next minute.
+        if !(1..=days_in_month).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+            return None;
+        }
+
+        Some(Timestamp {
+            seconds: days_from_civil(year, month, day) * 86_400
+                + hour * 3_600
+                + minute * 60
+                + second,
+            nanos,
+        })
+    }
+
+    /// The instant `seconds` after the Unix epoch, for the formats that count
+    /// rather than spell their timestamps. A fraction is added with
+    /// [`add_seconds`](Self::add_seconds), to the nanosecond.
+    pub(super) fn from_unix(seconds: i64) -> Timestamp {
+        Timestamp { seconds, nanos: 0 }
+    }
+
+    /// `time`, to the nanosecond, on either side of the epoch.
+    pub(super) fn from_system_time(time: SystemTime) -> Timestamp {
+        let nanos = match time.duration_since(UNIX_EPOCH) {
+            Ok(after) => after.as_nanos() as i128,
+            Err(before) => -(before.duration().as_nanos() as i128),
+        };
+        Timestamp {
+            seconds: nanos.div_euclid(1_000_000_000) as i64,
+            nanos: nanos.rem_euclid(1_000_000_000) as u32,
+        }
+    }
+
+    /// How many seconds after `earlier` this instant is: negative if before.
+    pub(super) fn seconds_since(self, earlier: Timestamp) -> f64 {
+        (self.seconds - earlier.seconds) as f64
+            + (f64::from(self.nanos) - f64::from(earlier.nanos)) / 1e9
+    }
+
+    /// This instant moved by `seconds`, rounded to the nanosecond.
+    pub(super) fn add_seconds(self, seconds: f64) -> Timestamp {
+        let total = i128::from(self.seconds) * 1_000_000_000
+            + i128::from(self.nanos)
+            + (seconds * 1e9).round() as i128;
+        Timestamp {
+            seconds: total.div_euclid(1_000_000_000) as i64,
+            nanos: total.rem_euclid(1_000_000_000) as u32,
+        }
+    }
+}
+
+/// Written with at least millisecond precision, as the specification's own
+/// examples are, and as many more fractional digits as the instant needs.
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let civil = self.civil();
+        let mut fraction = String::with_capacity(9);
+        // Writing to a String is infallible.
+        let _ = write!(fraction, "{:09}", self.nanos);
+        let significant = fraction.trim_end_matches('0').len().max(3);
+        fraction.truncate(significant);
+
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{fraction}Z",
+            civil.year, civil.month, civil.day, civil.hour, civil.minute, civil.second,
+        )
+    }
+}
+
+/// A [`Timestamp`] as a calendar reads it, in UTC.
+///
+/// For the container formats that store a broken-down time rather than a
+/// count of seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Civil {
+    pub(super) year: i64,
+    pub(super) month: i64,
+    pub(super) day: i64,
+    /// Days since Sunday.
+    pub(super) weekday: i64,
+    pub(super) hour: i64,
+    pub(super) minute: i64,
+    pub(super) second: i64,
+    pub(super) nanos: u32,
+}
+
+impl Timestamp {
+    /// The instant a calendar shows as `civil`, or `None` if it shows no such
+    /// date. The weekday is not checked.
+    pub(super) fn from_civil(civil: Civil) -> Option<Timestamp> {
+        let text = format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:09}Z",
+            civil.year, civil.month, civil.day, civil.hour, civil.minute, civil.second, civil.nanos,
+        );
+        Timestamp::parse(&text)
+    }
+
+    /// This instant as a calendar shows it.
+    pub(super) fn civil(self) -> Civil {
+        let days = self.seconds.div_euclid(86_400);
+        let of_day = self.seconds.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+        Civil {
+            year,
+            month,
+            day,
+            // 1970-01-01 was a Thursday.
+            weekday: (days + 4).rem_euclid(7),
+            hour: of_day / 3_600,
+            minute: of_day % 3_600 / 60,
+            second: of_day % 60,
+            nanos: self.nanos,
+        }
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+///
+/// Howard Hinnant's `days_from_civil`, which counts in 400-year eras so that
+/// every division is of a non-negative number.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The inverse of [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,179 @@
+//! Finding where a Recording's stream is not continuous: samples dropped or
+//! repeated between Captures segments, and clocks that jumped.
+
+use super::datetime::Timestamp;
+use super::Metadata;
+
+/// One place where the stream breaks, between a Captures segment and the
+/// one before it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct Discontinuity {
+    /// Position in the `captures` array of the segment that starts after the
+    /// break.
+    pub capture: usize,
+    /// That segment's `core:sample_start`.
+    pub sample_start: u64,
+    /// What kind of break it is.
+    pub kind: DiscontinuityKind,
+}
+
+/// The kinds of [`Discontinuity`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum DiscontinuityKind {
+    /// The segment's `core:global_index` is ahead of where the samples
+    /// before it end: this many were dropped or never captured.
+    Gap {
+        /// How many stream samples are missing.
+        samples: u64,
+    },
+    /// The segment's `core:global_index` is behind where the samples before
+    /// it end: this many stream samples are in the Dataset twice.
+    Overlap {
+        /// How many stream samples are repeated.
+        samples: u64,
+    },
+    /// The segment's `core:datetime` is not when the samples before it, at
+    /// `core:sample_rate`, say it should be — later if positive, earlier if
+    /// negative.
+    ///
+    /// Either the receiver's clock was stepped, or, when no
+    /// [`Gap`](Self::Gap) is reported at the same segment, samples were lost
+    /// that the receiver did not count — `seconds` times `core:sample_rate`
+    /// of them. The document cannot say which.
+    ClockStep {
+        /// How far the declared time is from the expected one.
+        seconds: f64,
+    },
+}
+
+impl Metadata {
+    /// Every break in the stream this document describes, segment by
+    /// segment, in order.
+    ///
+    /// Each segment is held to what the segments before it imply. Its
+    /// samples follow on from theirs — a segment's sample count is the
+    /// distance to the next `core:sample_start`, the same arithmetic
+    /// [`capture_boundaries`](Self::capture_boundaries) does — so its stream
+    /// index should be where theirs end, and a `core:global_index` anywhere
+    /// else is a [`Gap`](DiscontinuityKind::Gap) or an
+    /// [`Overlap`](DiscontinuityKind::Overlap). Only the segments *before*
+    /// a break need counting, and those end where the next begins, so the
+    /// Dataset's length is never needed and neither is the Dataset. A segment
+    /// whose samples would run past stream index `u64::MAX` is taken to end
+    /// there, as [`global_index`](Self::global_index) places them.
+    ///
+    /// Its `core:datetime`, likewise, should be the last declared time plus
+    /// the stream samples since, at `core:sample_rate` — counted across any
+    /// gap `core:global_index` declares, so a receiver that counted what it
+    /// dropped and kept its clock is reported once, as a gap. A difference
+    /// is a [`ClockStep`](DiscontinuityKind::ClockStep) when it is more than
+    /// half a sample, plus the resolution of the coarser of the two
+    /// timestamps as written: `…:05.1Z` says nothing about the hundredths. A
+    /// document without a sample rate has its times left unchecked, and a
+    /// datetime that does not parse is passed over as if absent —
+    /// [`validate`](Self::validate) reports it.
+    ///
+    /// # Examples
+    ///
+    /// A 1 kS/s receiver that dropped 250 samples after its first 1000, and
+    /// counted them, and whose clock was then stepped half a second forward:
+    ///
+    /// ```
+    /// use sigmf::{DiscontinuityKind, Metadata};
+    ///
+    /// let metadata: Metadata = serde_json::from_str(r#"{
+    ///     "global": {
+    ///         "core:datatype": "cf32_le",
+    ///         "core:version": "1.2.6",
+    ///         "core:sample_rate": 1000
+    ///     },
+    ///     "captures": [
+    ///         { "core:sample_start": 0, "core:datetime": "2026-10-18T12:00:00.000Z" },
+    ///         {
+    ///             "core:sample_start": 1000,
+    ///             "core:global_index": 1250,
+    ///             "core:datetime": "2026-10-18T12:00:01.250Z"
+    ///         },
+    ///         {
+    ///             "core:sample_start": 2000,
+    ///             "core:global_index": 2250,
+    ///             "core:datetime": "2026-10-18T12:00:02.750Z"
+    ///         }
+    ///     ],
+    ///     "annotations": []
+    /// }"#)?;
+    ///
+    /// let kinds: Vec<_> = metadata.discontinuities().iter().map(|d| d.kind).collect();
+    /// assert_eq!(
+    ///     kinds,
+    ///     [
+    ///         DiscontinuityKind::Gap { samples: 250 },
+    ///         DiscontinuityKind::ClockStep { seconds: 0.5 },
+    ///     ]
+    /// );
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn discontinuities(&self) -> Vec<Discontinuity> {
+        let rate = self.global.sample_rate.filter(|&rate| rate > 0.0);
+        let mut found = Vec::new();
+        let mut last_timed: Option<(Timestamp, f64, u64)> = None;
+
+        for (index, capture) in self.captures.iter().enumerate() {
+            let stream_start = self.stream_start(index);
+
+            if let Some(previous) = index.checked_sub(1) {
+                let expected = self.stream_start(previous).saturating_add(
+                    capture
+                        .sample_start
+                        .saturating_sub(self.captures[previous].sample_start),
+                );
+                let kind = match stream_start.cmp(&expected) {
+                    std::cmp::Ordering::Greater => Some(DiscontinuityKind::Gap {
+                        samples: stream_start - expected,
+                    }),
+                    std::cmp::Ordering::Less => Some(DiscontinuityKind::Overlap {
+                        samples: expected - stream_start,
+                    }),
+                    std::cmp::Ordering::Equal => None,
+                };
+                found.extend(kind.map(|kind| Discontinuity {
+                    capture: index,
+                    sample_start: capture.sample_start,
+                    kind,
+                }));
+            }
+
+            let Some(datetime) = capture.datetime.as_deref() else {
+                continue;
+            };
+            let Some(time) = Timestamp::parse(datetime) else {
+                continue;
+            };
+            let resolution = resolution(datetime);
+            if let (Some((from, from_resolution, from_stream)), Some(rate)) = (last_timed, rate) {
+                let elapsed = (stream_start as f64 - from_stream as f64) / rate;
+                let step = time.seconds_since(from) - elapsed;
+                if step.abs() > 0.5 / rate + resolution.max(from_resolution) {
+                    found.push(Discontinuity {
+                        capture: index,
+                        sample_start: capture.sample_start,
+                        kind: DiscontinuityKind::ClockStep { seconds: step },
+                    });
+                }
+            }
+            last_timed = Some((time, resolution, stream_start));
+        }
+        found
+    }
+}
+
+/// The smallest difference `datetime` can express, in seconds: one unit of
+/// its last fractional digit, or a second if it has none.
+fn resolution(datetime: &str) -> f64 {
+    let digits = datetime.split_once('.').map_or(0, |(_, fraction)| {
+        fraction.bytes().take_while(u8::is_ascii_digit).count()
+    });
+    10f64.powi(-(digits.min(9) as i32))
+}
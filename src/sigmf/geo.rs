@@ -0,0 +1,269 @@
+//! Where Recordings were made, as a map layer: a GeoJSON FeatureCollection or
+//! a KML document with one point per geolocated Captures segment.
+//!
+//! A receiver that moves — a survey vehicle, a ship, an aircraft — records each
+//! segment somewhere else, and the specification gives each segment a
+//! `core:geolocation` of its own for exactly that. [`export_geojson`] and
+//! [`export_kml`] collect those positions from Recordings, or from every
+//! Recording under a directory, into a file any GIS tool or Google Earth
+//! opens.
+//!
+//! A segment without a `core:geolocation` of its own is placed at the Global
+//! one, the specification's fallback for a receiver that does not move; a
+//! segment with neither is left off the map. Every point carries these
+//! properties, each absent where the Recording does not say:
+//!
+//! | Property | From |
+//! |---|---|
+//! | `recording` | the path of the Metadata file |
+//! | `capture` | the segment's index in `captures` |
+//! | `sample_start` | `core:sample_start` |
+//! | `frequency` | `core:frequency`, in Hz |
+//! | `datetime` | `core:datetime` |
+//! | `recorder` | `core:recorder` |
+//! | `hw` | `core:hw` |
+//!
+//! In GeoJSON the point's geometry is the `core:geolocation` object as the
+//! Recording has it, foreign members and all — so the position quality data
+//! the specification invites there reaches the map. KML has no place for
+//! them, and they are dropped.
+//!
+//! A Metadata file found under a directory that cannot be read does not stop
+//! the export: it is left off the map and returned, with the error that kept
+//! it off.
+
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Map, Value};
+
+use super::{at, finish_file, metadata_files_under, CaptureMetadata, Error, Geolocation, SigMF};
+
+/// A Metadata file left off the map, and the error that kept it off.
+type Skipped = (PathBuf, Error);
+
+/// One geolocated Captures segment.
+struct Feature {
+    position: Geolocation,
+    properties: Map<String, Value>,
+}
+
+/// Write the geolocated segments of the Recordings at `sources` to `geojson`,
+/// as an RFC 7946 FeatureCollection of Points.
+///
+/// Each source is the `.sigmf-meta` file of a Recording, or a directory whose
+/// Recordings, at any depth, are taken in order of path. Only Metadata files
+/// are read: a Recording whose Dataset is elsewhere or gone still has a place
+/// on the map. A Metadata file found in a directory that cannot be read is
+/// skipped, and its path returned with the error [`SigMF::from_file`] gave
+/// for it; the map is written either way.
+///
+/// # Errors
+///
+/// [`Error::Io`] if a source, or a directory below one, cannot be read, or
+/// `geojson` cannot be written; or what [`SigMF::from_file`] returns for a
+/// Metadata file named as a source that it cannot open.
+///
+/// # Examples
+///
+/// ```
+/// use sigmf::{geo, CaptureMetadata, Geolocation, RecordingWriter};
+/// # let dir = tempfile::tempdir().expect("a temporary directory");
+///
+/// let mut writer = RecordingWriter::new(&[0.5f32, -0.5], 32_000.0);
+/// for (sample, longitude) in [(0, 14.50), (1, 14.52)] {
+///     let mut capture = CaptureMetadata::new(sample);
+///     capture.geolocation = Some(Geolocation::new(longitude, -22.95));
+///     writer.captures_mut().push(capture);
+/// }
+/// writer.to_file(dir.path().join("drive"))?;
+///
+/// let skipped = geo::export_geojson(&[dir.path()], dir.path().join("drive.geojson"))?;
+/// assert!(skipped.is_empty());
+/// let map: serde_json::Value =
+///     serde_json::from_str(&std::fs::read_to_string(dir.path().join("drive.geojson"))?)?;
+/// assert_eq!(map["features"][1]["geometry"]["coordinates"][0], 14.52);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn export_geojson<P: AsRef<Path>, Q: AsRef<Path>>(
+    sources: &[P],
+    geojson: Q,
+) -> Result<Vec<(PathBuf, Error)>, Error> {
+    let path = geojson.as_ref();
+    let (features, skipped) = features(sources)?;
+    let features: Vec<Value> = features
+        .into_iter()
+        .map(|feature| {
+            json!({
+                "type": "Feature",
+                "geometry": feature.position,
+                "properties": feature.properties,
+            })
+        })
+        .collect();
+    let collection = json!({"type": "FeatureCollection", "features": features});
+
+    let mut out = BufWriter::new(fs::File::create(path).map_err(at(path))?);
+    serde_json::to_writer_pretty(&mut out, &collection)?;
+    out.write_all(b"\n").map_err(at(path))?;
+    finish_file(out, path)?;
+    Ok(skipped)
+}
+
+/// Write the geolocated segments of the Recordings at `sources` to `kml`, as
+/// a KML 2.2 document of Placemarks.
+///
+/// The sources are read as [`export_geojson`] reads them. Each Placemark is
+/// named for its Recording and segment, carries the properties as
+/// `ExtendedData`, and has a `TimeStamp` where the segment has a
+/// `core:datetime`, so a time slider replays the survey.
+///
+/// An altitude is written with `altitudeMode` `absolute`. KML measures that
+/// from mean sea level and SigMF from the WGS84 ellipsoid, which differ by up
+/// to about a hundred metres; the number is written as the Recording has it.
+///
+/// # Errors
+///
+/// As [`export_geojson`].
+pub fn export_kml<P: AsRef<Path>, Q: AsRef<Path>>(
+    sources: &[P],
+    kml: Q,
+) -> Result<Vec<(PathBuf, Error)>, Error> {
+    let path = kml.as_ref();
+    let (features, skipped) = features(sources)?;
+    let mut document = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <kml xmlns=\"http://www.opengis.net/kml/2.2\">\n<Document>\n",
+    );
+    for feature in features {
+        let properties = &feature.properties;
+        let name = format!(
+            "{} #{}",
+            properties["recording"].as_str().unwrap_or_default(),
+            properties["capture"]
+        );
+        document.push_str(&format!(
+            "  <Placemark>\n    <name>{}</name>\n",
+            escape(&name)
+        ));
+        if let Some(datetime) = properties.get("datetime").and_then(Value::as_str) {
+            document.push_str(&format!(
+                "    <TimeStamp><when>{}</when></TimeStamp>\n",
+                escape(datetime)
+            ));
+        }
+        document.push_str("    <ExtendedData>\n");
+        for (key, value) in properties {
+            let value = match value {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            document.push_str(&format!(
+                "      <Data name=\"{}\"><value>{}</value></Data>\n",
+                escape(key),
+                escape(&value)
+            ));
+        }
+        document.push_str("    </ExtendedData>\n    <Point>\n");
+        let position = &feature.position;
+        match position.altitude {
+            Some(altitude) => document.push_str(&format!(
+                "      <altitudeMode>absolute</altitudeMode>\n      \
+                 <coordinates>{},{},{altitude}</coordinates>\n",
+                position.longitude, position.latitude
+            )),
+            None => document.push_str(&format!(
+                "      <coordinates>{},{}</coordinates>\n",
+                position.longitude, position.latitude
+            )),
+        }
+        document.push_str("    </Point>\n  </Placemark>\n");
+    }
+    document.push_str("</Document>\n</kml>\n");
+
+    let mut out = BufWriter::new(fs::File::create(path).map_err(at(path))?);
+    out.write_all(document.as_bytes()).map_err(at(path))?;
+    finish_file(out, path)?;
+    Ok(skipped)
+}
+
+/// The geolocated segments of every Recording at `sources`, in order, and the
+/// Metadata files found in directories that could not be read.
+fn features<P: AsRef<Path>>(sources: &[P]) -> Result<(Vec<Feature>, Vec<Skipped>), Error> {
+    // Each file, and whether it was found in a directory rather than named.
+    let mut metadata_files: Vec<(PathBuf, bool)> = Vec::new();
+    for source in sources {
+        let source = source.as_ref();
+        if fs::metadata(source).map_err(at(source))?.is_dir() {
+            let found = metadata_files_under(source)?;
+            metadata_files.extend(found.into_iter().map(|path| (path, true)));
+        } else {
+            metadata_files.push((source.to_path_buf(), false));
+        }
+    }
+
+    let mut features = Vec::new();
+    let mut skipped = Vec::new();
+    for (metadata_file, found) in metadata_files {
+        let recording = match SigMF::from_file(&metadata_file) {
+            Ok(recording) => recording,
+            Err(err) if found => {
+                skipped.push((metadata_file, err));
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        let global = &recording.metadata.global;
+
+        // A document without captures has one implicit segment from the
+        // Dataset's first sample, whose index is `core:offset`.
+        let implicit = [CaptureMetadata::new(global.offset.unwrap_or(0))];
+        let captures = if recording.metadata.captures.is_empty() {
+            &implicit[..]
+        } else {
+            &recording.metadata.captures[..]
+        };
+        for (index, capture) in captures.iter().enumerate() {
+            let Some(position) = capture.geolocation.as_ref().or(global.geolocation.as_ref())
+            else {
+                continue;
+            };
+            let mut properties = Map::new();
+            properties.insert(
+                "recording".to_string(),
+                Value::from(metadata_file.to_string_lossy()),
+            );
+            properties.insert("capture".to_string(), Value::from(index));
+            properties.insert(
+                "sample_start".to_string(),
+                Value::from(capture.sample_start),
+            );
+            let optional = [
+                ("frequency", capture.frequency.map(Value::from)),
+                ("datetime", capture.datetime.clone().map(Value::from)),
+                ("recorder", global.recorder.clone().map(Value::from)),
+                ("hw", global.hw.clone().map(Value::from)),
+            ];
+            for (key, value) in optional {
+                if let Some(value) = value {
+                    properties.insert(key.to_string(), value);
+                }
+            }
+            features.push(Feature {
+                position: position.clone(),
+                properties,
+            });
+        }
+    }
+    Ok((features, skipped))
+}
+
+/// `text` with XML's five special characters escaped.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
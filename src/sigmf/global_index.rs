@@ -0,0 +1,122 @@
+//! Translating between a sample's index in its Dataset and its index in the
+//! stream the Dataset was recorded from.
+//!
+//! The two coincide only for a Recording that began when its stream did and
+//! missed nothing. Sample indices in a SigMF document are absolute:
+//! `core:offset` is the index of the Dataset's first sample, so the Dataset
+//! index of a `core:sample_start` is how far it is past `core:offset`. Where
+//! a Captures segment's first sample sat in the stream is its
+//! `core:global_index`, which is how one that dropped samples says how many,
+//! and is its `core:sample_start` where it has none. Between them they place
+//! every sample in the stream: a segment's samples are consecutive from
+//! wherever it starts.
+
+use super::Metadata;
+
+impl Metadata {
+    /// The index in the original stream of Dataset sample `sample`.
+    ///
+    /// The sample's absolute index is `core:offset` plus `sample`. Its segment
+    /// starts at its `core:global_index`, or at its `core:sample_start` if it
+    /// has none, and the sample is as far beyond that as its absolute index is
+    /// beyond `core:sample_start`. A document without captures, or a sample
+    /// before the first of them, is where its absolute index puts it.
+    ///
+    /// The Metadata file does not record how long its Dataset is, so neither
+    /// does this check: the last segment runs on for as long as it is asked
+    /// about. A stream index past `u64::MAX`, which only a document placing
+    /// its samples there can ask for, is given as `u64::MAX`.
+    ///
+    /// # Examples
+    ///
+    /// A Recording that began at hardware sample 1 000 000, and lost 500
+    /// samples at Dataset sample 4096 — the segment starting there says where
+    /// the stream had got to when recording resumed:
+    ///
+    /// ```
+    /// use sigmf::Metadata;
+    ///
+    /// let metadata: Metadata = serde_json::from_str(r#"{
+    ///     "global": {
+    ///         "core:datatype": "ci16_le",
+    ///         "core:version": "1.2.6",
+    ///         "core:offset": 1000000
+    ///     },
+    ///     "captures": [
+    ///         { "core:sample_start": 1000000 },
+    ///         { "core:sample_start": 1004096, "core:global_index": 1004596 }
+    ///     ],
+    ///     "annotations": []
+    /// }"#)?;
+    ///
+    /// assert_eq!(metadata.global_index(0), 1_000_000);
+    /// assert_eq!(metadata.global_index(4095), 1_004_095);
+    /// assert_eq!(metadata.global_index(4096), 1_004_596);
+    ///
+    /// assert_eq!(metadata.dataset_index(1_004_095), Some(4095));
+    /// assert_eq!(metadata.dataset_index(1_004_100), None, "dropped");
+    /// assert_eq!(metadata.dataset_index(1_004_600), Some(4100));
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn global_index(&self, sample: u64) -> u64 {
+        let absolute = self.global.offset.unwrap_or(0).saturating_add(sample);
+        let following = self
+            .captures
+            .partition_point(|capture| capture.sample_start <= absolute);
+        match following.checked_sub(1) {
+            Some(index) => self
+                .stream_start(index)
+                .saturating_add(absolute - self.captures[index].sample_start),
+            None => absolute,
+        }
+    }
+
+    /// The Dataset sample recorded at index `global` of the original stream,
+    /// or `None` if the Recording does not hold it — because it was before
+    /// the Recording began, or in a gap `core:global_index` records.
+    ///
+    /// The inverse of [`global_index`](Self::global_index), and subject to the
+    /// same caveat: past the start of the last segment, every stream index is
+    /// answered as if the Dataset went on for ever, so check the result
+    /// against [`SigMF::sample_count`](super::SigMF::sample_count) where the
+    /// end matters. Were two segments to claim the same stream index, the
+    /// earlier in the Dataset answers.
+    pub fn dataset_index(&self, global: u64) -> Option<u64> {
+        let offset = self.global.offset.unwrap_or(0);
+
+        // Before the first segment, if the Dataset starts before it, a stream
+        // index is an absolute one, as `global_index` places it.
+        let first = self.captures.first().map_or(u64::MAX, |c| c.sample_start);
+        if global < first {
+            if let Some(sample) = global.checked_sub(offset) {
+                return Some(sample);
+            }
+        }
+
+        self.captures
+            .iter()
+            .enumerate()
+            .find_map(|(index, capture)| {
+                let beyond = global.checked_sub(self.stream_start(index))?;
+                let len = self.captures.get(index + 1).map_or(u64::MAX, |next| {
+                    next.sample_start.saturating_sub(capture.sample_start)
+                });
+                capture
+                    .sample_start
+                    .checked_add(beyond)
+                    .filter(|_| beyond < len)?
+                    .checked_sub(offset)
+            })
+    }
+
+    /// The stream index of the `core:sample_start` of Captures segment
+    /// `index`, or of the first sample of the implicit segment of a document
+    /// with none.
+    pub(super) fn stream_start(&self, index: usize) -> u64 {
+        self.captures
+            .get(index)
+            .map_or(self.global.offset.unwrap_or(0), |capture| {
+                capture.global_index.unwrap_or(capture.sample_start)
+            })
+    }
+}
@@ -0,0 +1,420 @@
+//! GNU Radio's metadata files: the headers its File Meta Sink writes.
+//!
+//! A File Meta Sink splits its output into segments, starting a new one when a
+//! stream tag changes the rate, the frequency or the time, or when a segment
+//! reaches its maximum size. Each segment has a header: a dictionary of
+//! [PMTs](https://wiki.gnuradio.org/index.php/Polymorphic_Types_(PMTs)),
+//! serialized, followed by a second dictionary of whatever else the stream
+//! tags said — `rx_freq`, usually. The headers are either *detached*, one after
+//! another in a `.hdr` file beside samples with nothing else in them, or
+//! *inline*, each written into the data file in front of the samples it
+//! describes.
+//!
+//! Either way the samples never move: [`detached_metadata`] and
+//! [`inline_metadata`] read the headers into a [`Metadata`] whose Captures
+//! segments are the sink's segments, and [`describe`] writes it beside the data
+//! file as the description of a Non-Conforming Dataset. An inline header
+//! becomes that segment's `core:header_bytes`, which is the field's purpose.
+//!
+//! GNU Radio writes samples in the byte order of the machine that ran it, and
+//! records nothing about it. The Metadata says little-endian, which every
+//! machine GNU Radio is commonly run on is.
+
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use super::datetime::Timestamp;
+use super::{
+    at, invalid, CaptureMetadata, DataFormat, DataType, Endianness, Error, GlobalMetadata,
+    Metadata, NumberType, SigMF,
+};
+use crate::SIGMF_METADATA_EXT;
+
+/// The length of the first dictionary of every header: GNU Radio's
+/// `METADATA_HEADER_SIZE`.
+const HEADER_LEN: u64 = 149;
+
+/// How deeply PMTs may nest before a header is taken to be hostile rather
+/// than merely unusual.
+const MAX_DEPTH: usize = 32;
+
+/// Read the detached headers in `header`, a File Meta Sink's `.hdr` file, into
+/// the Metadata of the samples they describe.
+///
+/// The document has `core:datatype` from the first header's item type,
+/// `core:sample_rate` from its `rx_rate`, and one Captures segment per header,
+/// each with `core:datetime` from its `rx_time` and `core:frequency` from its
+/// `rx_freq`, where the header has them. It does not have `core:dataset`,
+/// which [`describe`] sets.
+///
+/// # Errors
+///
+/// [`Error::Io`] naming `header` if it cannot be read, or if it is not a
+/// sequence of File Meta Sink headers, or if they describe samples a
+/// Recording cannot hold: a 64-bit integer item type, or segments that
+/// disagree on the item type or the sample rate.
+///
+/// # Examples
+///
+/// ```no_run
+/// use sigmf::gnuradio;
+///
+/// let metadata = gnuradio::detached_metadata("capture.dat.hdr")?;
+/// let recording = gnuradio::describe("capture.dat", metadata)?;
+/// # Ok::<(), sigmf::Error>(())
+/// ```
+pub fn detached_metadata<P: AsRef<Path>>(header: P) -> Result<Metadata, Error> {
+    read_headers(header.as_ref(), false)
+}
+
+/// Read the headers written inline in `dataset`, a File Meta Sink's data file,
+/// into the Metadata of the samples between them.
+///
+/// As [`detached_metadata`], and with each Captures segment's
+/// `core:header_bytes` set to the length of the header in front of it. Only
+/// the headers are read: each says how many bytes of samples follow it, and
+/// the rest of the file is skipped.
+///
+/// # Errors
+///
+/// As [`detached_metadata`], naming `dataset`.
+pub fn inline_metadata<P: AsRef<Path>>(dataset: P) -> Result<Metadata, Error> {
+    read_headers(dataset.as_ref(), true)
+}
+
+/// Write `metadata` beside `dataset` as the description of that file, and
+/// open the Recording.
+///
+/// The Metadata file is the data file's name with its extension replaced —
+/// `capture.dat` is described by `capture.sigmf-meta` — and `core:dataset`
+/// names the data file, so the samples are read where they lie. No
+/// `core:sha512` is computed: the Dataset was not written here, and hashing
+/// an archive of captures is a separate decision, made with
+/// [`SigMF::dataset_sha512`].
+///
+/// # Errors
+///
+/// [`Error::Io`] if the Metadata file cannot be written, and any error of
+/// [`SigMF::from_file`] reading it back — which, for a Dataset shorter than
+/// its headers claim, is the first a caller hears of it.
+pub fn describe<P: AsRef<Path>>(dataset: P, mut metadata: Metadata) -> Result<SigMF, Error> {
+    let dataset = dataset.as_ref();
+    let name = dataset
+        .file_name()
+        .ok_or_else(|| invalid(dataset, "a Dataset must be a file"))?;
+    metadata.global.dataset = Some(name.to_string_lossy().into_owned());
+
+    let stem = dataset.with_extension("");
+    let metadata_path = super::append_extension(&stem, SIGMF_METADATA_EXT);
+    fs::write(&metadata_path, metadata.to_json()?).map_err(at(&metadata_path))?;
+    SigMF::from_file(&metadata_path)
+}
+
+/// What one File Meta Sink header says.
+struct Header {
+    rate: f64,
+    /// `None` for the all-zero time a sink writes when it was never told one.
+    time: Option<Timestamp>,
+    item_size: u64,
+    file_type: i64,
+    complex: bool,
+    /// Both dictionaries together: `strt`.
+    len: u64,
+    /// The bytes of samples in the segment: 0 if the sink never finished it.
+    bytes: u64,
+    frequency: Option<f64>,
+}
+
+fn read_headers(path: &Path, inline: bool) -> Result<Metadata, Error> {
+    let mut file = fs::File::open(path).map_err(at(path))?;
+    let file_len = file.metadata().map_err(at(path))?.len();
+
+    let mut headers = Vec::new();
+    let mut position = 0u64;
+    while position < file_len {
+        file.seek(SeekFrom::Start(position)).map_err(at(path))?;
+        // `read_header` holds `strt` to what is left of the file, so this
+        // cannot pass its end, and each header moves on by at least its own
+        // 149 bytes.
+        let header = read_header(&mut file, path, file_len - position)?;
+        position += header.len;
+        if inline {
+            if header.bytes == 0 {
+                headers.push(header);
+                break;
+            }
+            if header.bytes > file_len - position {
+                return Err(invalid(
+                    path,
+                    "a File Meta Sink header claiming more samples than follow it",
+                ));
+            }
+            position += header.bytes;
+        }
+        headers.push(header);
+    }
+    let first = headers
+        .first()
+        .ok_or_else(|| invalid(path, "no File Meta Sink header"))?;
+
+    let datatype = data_format(first)
+        .ok_or_else(|| invalid(path, "an item type no SigMF datatype can hold"))?;
+    let sample_size = datatype.size();
+    let mut global = GlobalMetadata::describing(datatype);
+    global.sample_rate = (first.rate > 0.0).then_some(first.rate);
+
+    let mut captures = Vec::with_capacity(headers.len());
+    let mut sample_start = 0;
+    for header in &headers {
+        if data_format(header) != Some(datatype) {
+            return Err(invalid(path, "segments of more than one item type"));
+        }
+        if header.rate != first.rate {
+            return Err(invalid(
+                path,
+                "segments at more than one `rx_rate`, which one Recording cannot have",
+            ));
+        }
+        let mut capture = CaptureMetadata::new(sample_start);
+        capture.frequency = header.frequency;
+        capture.datetime = header.time.map(|time| time.to_string());
+        if inline {
+            capture.header_bytes = Some(header.len);
+        }
+        captures.push(capture);
+        sample_start += header.bytes / sample_size;
+    }
+
+    Ok(Metadata {
+        global,
+        captures,
+        annotations: vec![],
+    })
+}
+
+/// Read the two dictionaries of the header at the reader's position, which
+/// has `available` bytes of file after it.
+///
+/// The header's own `strt` says how long the two are, and is believed only
+/// as far as the file allows: no shorter than the first dictionary, which
+/// would leave a reader going round the same header for ever, and no longer
+/// than the file, which would have it allocate whatever a corrupt file says.
+fn read_header(file: &mut fs::File, path: &Path, available: u64) -> Result<Header, Error> {
+    let malformed = || invalid(path, "not a GNU Radio File Meta Sink header");
+    let mut first = vec![0u8; HEADER_LEN as usize];
+    file.read_exact(&mut first).map_err(|_| malformed())?;
+    let fields = Reader::new(&first).dictionary().ok_or_else(malformed)?;
+    let field = |key: &str| fields.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+
+    let len = field("strt")
+        .and_then(Pmt::as_u64)
+        .filter(|len| (HEADER_LEN..=available).contains(len))
+        .ok_or_else(malformed)?;
+    let extras = if len > HEADER_LEN {
+        let mut bytes = vec![0u8; (len - HEADER_LEN) as usize];
+        file.read_exact(&mut bytes).map_err(|_| malformed())?;
+        Reader::new(&bytes).dictionary().ok_or_else(malformed)?
+    } else {
+        Vec::new()
+    };
+    let extra = |key: &str| extras.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+
+    let time = match field("rx_time") {
+        Some(Pmt::Items(items)) => match items.as_slice() {
+            [seconds, fraction] => {
+                let seconds = seconds.as_u64().ok_or_else(malformed)?;
+                let fraction = fraction.as_f64().ok_or_else(malformed)?;
+                (seconds != 0 || fraction != 0.0).then(|| {
+                    Timestamp::from_unix(i64::try_from(seconds).unwrap_or(i64::MAX))
+                        .add_seconds(fraction)
+                })
+            }
+            _ => return Err(malformed()),
+        },
+        _ => None,
+    };
+
+    Ok(Header {
+        rate: field("rx_rate")
+            .and_then(Pmt::as_f64)
+            .ok_or_else(malformed)?,
+        time,
+        item_size: field("size").and_then(Pmt::as_u64).ok_or_else(malformed)?,
+        file_type: field("type")
+            .and_then(Pmt::as_u64)
+            .and_then(|t| i64::try_from(t).ok())
+            .ok_or_else(malformed)?,
+        complex: matches!(field("cplx"), Some(Pmt::Bool(true))),
+        len,
+        bytes: field("bytes").and_then(Pmt::as_u64).ok_or_else(malformed)?,
+        frequency: extra("rx_freq")
+            .or_else(|| field("rx_freq"))
+            .and_then(Pmt::as_f64),
+    })
+}
+
+/// The datatype of a segment's items: GNU Radio's `gr_file_types` and `size`.
+fn data_format(header: &Header) -> Option<DataFormat> {
+    let e = Endianness::LittleEndian;
+    let data_type = match header.file_type {
+        // GR_FILE_BYTE, which GNU Radio's `char` makes signed.
+        0 => DataType::I8,
+        1 => DataType::I16(e),
+        2 => DataType::I32(e),
+        // GR_FILE_LONG, which is 32 bits or 64 by platform: only the first
+        // has a SigMF datatype, and `size` says which was written.
+        3 => DataType::I32(e),
+        5 => DataType::F32(e),
+        6 => DataType::F64(e),
+        _ => return None,
+    };
+    let number_type = if header.complex {
+        NumberType::Complex
+    } else {
+        NumberType::Real
+    };
+    let format = DataFormat {
+        number_type,
+        data_type,
+    };
+    (format.size() == header.item_size).then_some(format)
+}
+
+/// A deserialized PMT, as far as a header needs one.
+enum Pmt {
+    Bool(bool),
+    Symbol(String),
+    Int(i64),
+    UInt(u64),
+    Double(f64),
+    Null,
+    Pair(Box<Pmt>, Box<Pmt>),
+    /// A vector or a tuple.
+    Items(Vec<Pmt>),
+    /// A complex number or a uniform vector, which no header field this reads
+    /// holds, but a stream tag might.
+    Other,
+}
+
+impl Pmt {
+    fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Pmt::UInt(value) => Some(value),
+            Pmt::Int(value) => u64::try_from(value).ok(),
+            _ => None,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Pmt::Double(value) => Some(value),
+            Pmt::Int(value) => Some(value as f64),
+            Pmt::UInt(value) => Some(value as f64),
+            _ => None,
+        }
+    }
+}
+
+/// PMT deserialization, from GNU Radio's `pmt_serialize.cc`: a type tag, then
+/// the value, big-endian.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes }
+    }
+
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (taken, rest) = self.bytes.split_first_chunk::<N>()?;
+        self.bytes = rest;
+        Some(*taken)
+    }
+
+    fn skip(&mut self, n: usize) -> Option<()> {
+        self.bytes = self.bytes.get(n..)?;
+        Some(())
+    }
+
+    /// A dictionary, which GNU Radio serializes as the association list it
+    /// is: pairs of a key-value pair and the rest of the list, ending in null.
+    fn dictionary(&mut self) -> Option<Vec<(String, Pmt)>> {
+        let mut list = self.pmt(0)?;
+        let mut entries = Vec::new();
+        loop {
+            match list {
+                Pmt::Null => return Some(entries),
+                Pmt::Pair(entry, rest) => {
+                    let Pmt::Pair(key, value) = *entry else {
+                        return None;
+                    };
+                    let Pmt::Symbol(key) = *key else {
+                        return None;
+                    };
+                    entries.push((key, *value));
+                    list = *rest;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    fn pmt(&mut self, depth: usize) -> Option<Pmt> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let [tag] = self.take::<1>()?;
+        Some(match tag {
+            0x00 => Pmt::Bool(true),
+            0x01 => Pmt::Bool(false),
+            0x02 => {
+                let len = u16::from_be_bytes(self.take()?);
+                let symbol = self.bytes.get(..usize::from(len))?;
+                let symbol = String::from_utf8_lossy(symbol).into_owned();
+                self.skip(usize::from(len))?;
+                Pmt::Symbol(symbol)
+            }
+            0x03 => Pmt::Int(i32::from_be_bytes(self.take()?).into()),
+            0x04 => Pmt::Double(f64::from_be_bytes(self.take()?)),
+            0x05 => {
+                self.skip(16)?;
+                Pmt::Other
+            }
+            0x06 => Pmt::Null,
+            0x07 => {
+                let car = self.pmt(depth + 1)?;
+                let cdr = self.pmt(depth + 1)?;
+                Pmt::Pair(Box::new(car), Box::new(cdr))
+            }
+            0x08 | 0x0c => {
+                let len = u32::from_be_bytes(self.take()?);
+                let mut items = Vec::new();
+                for _ in 0..len {
+                    items.push(self.pmt(depth + 1)?);
+                }
+                Pmt::Items(items)
+            }
+            0x0a => {
+                let [item_type] = self.take::<1>()?;
+                let len = u32::from_be_bytes(self.take()?) as usize;
+                let [padding] = self.take::<1>()?;
+                self.skip(usize::from(padding))?;
+                let width = match item_type {
+                    0x00 | 0x01 => 1,
+                    0x02 | 0x03 => 2,
+                    0x04 | 0x05 | 0x08 => 4,
+                    0x06 | 0x07 | 0x09 | 0x0a => 8,
+                    0x0b => 16,
+                    _ => return None,
+                };
+                self.skip(len.checked_mul(width)?)?;
+                Pmt::Other
+            }
+            0x0b => Pmt::UInt(u64::from_be_bytes(self.take()?)),
+            0x0d => Pmt::Int(i64::from_be_bytes(self.take()?)),
+            _ => return None,
+        })
+    }
+}
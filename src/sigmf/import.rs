@@ -0,0 +1,205 @@
+//! Turning a raw sample file some other tool wrote into a conforming Recording.
+
+use std::fs;
+use std::path::Path;
+
+use super::dataset::{read_range, DatasetWriter};
+use super::{
+    append_extension, at, CaptureMetadata, DataFormat, Error, GlobalMetadata, Metadata,
+    MetadataError, SigMF,
+};
+use crate::{SIGMF_DATASET_EXT, SIGMF_METADATA_EXT};
+
+/// An import of a raw sample file — a vendor capture, the output of
+/// `rtl_sdr`, a GNU Radio file sink — as a conforming Recording.
+///
+/// [`GlobalMetadata::describing`] with `core:dataset` can already describe such
+/// a file where it lies, as a Non-Conforming Dataset. That leaves the file as
+/// it was: any header still in front of the samples, no hash, and a name that
+/// only readers of `core:dataset` will find. An import instead writes
+/// `<basename>.sigmf-data` holding the samples and nothing else, and a
+/// `<basename>.sigmf-meta` beside it with `core:sha512` computed over them —
+/// the Recording the specification means when it does not say otherwise.
+///
+/// The datatype is stated, not derived, for the reason
+/// [`describing`](GlobalMetadata::describing) gives: the bytes cannot say what
+/// they are. What this does check is the one thing the bytes *can* say — that
+/// once the header and trailer are set aside, what remains is a whole number
+/// of samples. A file that fails that is described by the wrong datatype, or
+/// the wrong header size, or was cut short; importing it would turn a mistake
+/// into a Recording that looks correct.
+///
+/// # Examples
+///
+/// An `rtl_sdr` capture, which is unsigned 8-bit IQ with no header:
+///
+/// ```
+/// use sigmf::num_complex::Complex;
+/// use sigmf::RawImport;
+/// # let dir = tempfile::tempdir().expect("a temporary directory");
+/// # std::fs::write(dir.path().join("capture.bin"), [127u8, 128, 0, 255])?;
+///
+/// let mut import = RawImport::new("cu8".parse()?, 2_048_000.0);
+/// import.captures_mut()[0].frequency = Some(162_025_000.0);
+/// let recording = import.import(dir.path().join("capture.bin"), dir.path().join("ais"))?;
+///
+/// assert_eq!(
+///     recording.samples::<Complex<u8>>()?,
+///     [Complex::new(127, 128), Complex::new(0, 255)]
+/// );
+/// recording.verify_sha512()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct RawImport {
+    metadata: Metadata,
+    header_bytes: u64,
+    trailing_bytes: u64,
+    hard_link: bool,
+}
+
+impl RawImport {
+    /// An import of samples in `datatype`, taken at `sample_rate` samples per
+    /// second, from a file with no header or trailer.
+    ///
+    /// The document starts with one Captures segment at sample 0, for the
+    /// centre frequency and start time the caller almost always knows and the
+    /// raw file never records; [`captures_mut`](Self::captures_mut) reaches it.
+    pub fn new(datatype: DataFormat, sample_rate: f64) -> RawImport {
+        let mut global = GlobalMetadata::describing(datatype);
+        global.sample_rate = Some(sample_rate);
+        RawImport {
+            metadata: Metadata {
+                global,
+                captures: vec![CaptureMetadata::new(0)],
+                annotations: vec![],
+            },
+            header_bytes: 0,
+            trailing_bytes: 0,
+            hard_link: false,
+        }
+    }
+
+    /// Bytes at the start of the raw file that are not samples. They are left
+    /// behind rather than copied.
+    pub fn header_bytes(mut self, header_bytes: u64) -> Self {
+        self.header_bytes = header_bytes;
+        self
+    }
+
+    /// Bytes at the end of the raw file that are not samples. They are left
+    /// behind rather than copied.
+    pub fn trailing_bytes(mut self, trailing_bytes: u64) -> Self {
+        self.trailing_bytes = trailing_bytes;
+        self
+    }
+
+    /// Whether to hard-link the raw file to `<basename>.sigmf-data` rather
+    /// than copy it. Off by default.
+    ///
+    /// A capture of many gigabytes then costs a directory entry rather than a
+    /// second copy, but the two names are one file afterwards, and writing
+    /// through either changes both — and breaks `core:sha512`. A link is only
+    /// possible when the whole file is samples, so with a header or trailer to
+    /// leave behind, or across filesystems, the import copies instead. Where
+    /// `<basename>.sigmf-data` already exists the link fails too, and if it
+    /// is the raw file — linked by an earlier import — the import is refused
+    /// rather than copying the file onto itself.
+    pub fn hard_link(mut self, hard_link: bool) -> Self {
+        self.hard_link = hard_link;
+        self
+    }
+
+    /// The Global object of the document being written.
+    ///
+    /// `core:sha512` is computed by [`import`](Self::import), whatever was set
+    /// here, and `core:dataset`, `core:trailing_bytes` and `core:num_channels`
+    /// must stay unset: the Recording written is conforming, and one channel.
+    pub fn global_mut(&mut self) -> &mut GlobalMetadata {
+        &mut self.metadata.global
+    }
+
+    /// The Captures segments of the document being written, which start as one
+    /// segment at sample 0.
+    pub fn captures_mut(&mut self) -> &mut Vec<CaptureMetadata> {
+        &mut self.metadata.captures
+    }
+
+    /// Import `raw` as `basename.sigmf-data`, write `basename.sigmf-meta`
+    /// describing it, and return the Recording opened.
+    ///
+    /// The raw file is streamed, and never written to: asking to import a file
+    /// over itself is refused rather than left to truncate it.
+    ///
+    /// # Errors
+    ///
+    /// [`MetadataError::TrailingBytesExceedDataset`] or
+    /// [`MetadataError::CaptureOutOfBounds`] if the header and trailer claim
+    /// more than the file holds, [`MetadataError::PartialSample`] if what they
+    /// leave is not a whole number of samples,
+    /// [`MetadataError::SampleRateOutOfRange`],
+    /// [`MetadataError::MultiChannelDataset`], or [`Error::Io`]. Every error
+    /// but the last is raised before either file is created.
+    pub fn import<P: AsRef<Path>, Q: AsRef<Path>>(
+        self,
+        raw: P,
+        basename: Q,
+    ) -> Result<SigMF, Error> {
+        let (raw, basename) = (raw.as_ref(), basename.as_ref());
+        let mut metadata = self.metadata;
+        if let Some(channels) = metadata.global.num_channels {
+            if channels != 1 {
+                return Err(MetadataError::MultiChannelDataset(channels).into());
+            }
+        }
+        if let Some(rate) = metadata.global.sample_rate {
+            if !(rate > 0.0 && rate <= 1e12) {
+                return Err(MetadataError::SampleRateOutOfRange(rate).into());
+            }
+        }
+
+        // The raw file, described as the Non-Conforming Dataset it is, so that
+        // the arithmetic and its errors are the ones every other read uses.
+        let mut file = fs::File::open(raw).map_err(at(raw))?;
+        let raw_len = file.metadata().map_err(at(raw))?.len();
+        let mut described = GlobalMetadata::describing(metadata.global.datatype);
+        described.trailing_bytes = Some(self.trailing_bytes);
+        let mut capture = CaptureMetadata::new(0);
+        capture.header_bytes = Some(self.header_bytes);
+        let segments = Metadata {
+            global: described,
+            captures: vec![capture],
+            annotations: vec![],
+        }
+        .segments(raw_len)?;
+        let samples = segments[0].bytes.clone();
+
+        metadata.global.dataset = None;
+        metadata.global.trailing_bytes = None;
+        for capture in &mut metadata.captures {
+            capture.header_bytes = None;
+        }
+
+        if self.hard_link && samples == (0..raw_len) {
+            let data_path = append_extension(basename, SIGMF_DATASET_EXT);
+            // A link that fails, across filesystems or onto a file already
+            // there, falls back to the copy below — which refuses to copy the
+            // raw file onto a link to itself, as truncating it would destroy it.
+            if fs::hard_link(raw, &data_path).is_ok() {
+                let mut recording = SigMF {
+                    metadata,
+                    datafile: Some(data_path),
+                };
+                recording.metadata.global.sha512 = Some(recording.dataset_sha512()?);
+                let metadata_path = append_extension(basename, SIGMF_METADATA_EXT);
+                fs::write(&metadata_path, recording.metadata.to_json()?)
+                    .map_err(at(&metadata_path))?;
+                return Ok(recording);
+            }
+        }
+
+        let mut writer = DatasetWriter::create(basename, &[raw])?;
+        read_range(&mut file, raw, samples, |bytes| writer.write(bytes))?;
+        writer.finish(metadata)
+    }
+}
@@ -0,0 +1,380 @@
+//! NumPy `.npy` files: the array format `numpy.save` writes and `numpy.load`
+//! reads.
+//!
+//! An `.npy` file is a short header — a magic string, a version, and a Python
+//! dictionary literal giving the array's `descr`, `fortran_order` and `shape`
+//! — followed by the array's bytes. A one-dimensional array of a type SigMF
+//! has a datatype for is laid out exactly as a Dataset is, so both directions
+//! here copy bytes and decode nothing:
+//!
+//! | `descr` | `core:datatype` |
+//! |---|---|
+//! | `\|i1`, `\|u1` | `ri8`, `ru8` |
+//! | `<i2`, `<u2`, `<i4`, `<u4` | `ri16_le`, `ru16_le`, `ri32_le`, `ru32_le` |
+//! | `<f4`, `<f8` | `rf32_le`, `rf64_le` |
+//! | `<c8`, `<c16` | `cf32_le`, `cf64_le` |
+//!
+//! and likewise with `>` for `_be`. NumPy has no complex integer type, so a
+//! `ci16` Recording has no `descr` to be written as; [`SigMF::transcode`] it
+//! to `cf32` first. An array SigMF has no datatype for — `<f2`, `<i8`, a
+//! boolean, a structured or object array — is refused on import rather than
+//! reinterpreted, as is one of more than one dimension.
+//!
+//! Only the samples cross: an `.npy` file has nowhere for a sample rate, a
+//! frequency or a start time, so [`import`] is told the rate and the rest is
+//! the caller's to add to the document afterwards.
+
+use std::fs;
+use std::io::{BufWriter, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use super::dataset::{read_range, DatasetWriter};
+use super::{
+    at, finish_file, invalid, CaptureMetadata, DataFormat, DataType, Endianness, Error,
+    GlobalMetadata, Metadata, MetadataError, NumberType, SigMF,
+};
+
+/// The magic string every `.npy` file starts with.
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// The header, magic and all, is padded to a multiple of this, so that the
+/// array which follows is aligned for any type NumPy reads in place.
+const ALIGNMENT: usize = 64;
+
+impl SigMF {
+    /// Write this Recording's samples to the `.npy` file `npy`, as a
+    /// one-dimensional array whose `descr` is derived from `core:datatype`.
+    ///
+    /// Equivalent to [`export_npy_range`](Self::export_npy_range) over every
+    /// sample.
+    ///
+    /// # Errors
+    ///
+    /// As [`export_npy_range`](Self::export_npy_range), except for the range
+    /// being out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sigmf::num_complex::Complex;
+    /// use sigmf::{npy, RecordingWriter};
+    /// # let dir = tempfile::tempdir().expect("a temporary directory");
+    ///
+    /// let samples = [Complex::new(0.5f32, -0.5), Complex::new(1.0, -1.0)];
+    /// let recording = RecordingWriter::new(&samples, 32_000.0).to_file(dir.path().join("dsc"))?;
+    ///
+    /// // `numpy.load("dsc.npy")` is a `complex64` array of two.
+    /// recording.export_npy(dir.path().join("dsc.npy"))?;
+    /// let back = npy::import(dir.path().join("dsc.npy"), dir.path().join("back"), 32_000.0)?;
+    /// assert_eq!(back.samples::<Complex<f32>>()?, samples);
+    /// # Ok::<(), sigmf::Error>(())
+    /// ```
+    pub fn export_npy<P: AsRef<Path>>(&self, npy: P) -> Result<(), Error> {
+        let sample_count = self.sample_count()?;
+        self.export_npy_range(0..sample_count, npy)
+    }
+
+    /// Write the samples at Dataset indices `samples` to the `.npy` file `npy`.
+    ///
+    /// One Captures segment's samples run from its `core:sample_start` to the
+    /// next segment's, or to [`sample_count`](Self::sample_count). The array
+    /// keeps the Recording's byte order — `>i2` for `ri16_be` — since NumPy
+    /// reads either, and the bytes are streamed from the Dataset unchanged.
+    ///
+    /// # Errors
+    ///
+    /// [`MetadataError::NoEquivalentFormat`] for a complex integer datatype,
+    /// [`MetadataError::SampleRangeOutOfBounds`] if `samples` is not within the
+    /// Dataset, [`MetadataError::MultiChannelDataset`],
+    /// [`MetadataError::NoDataset`], [`MetadataError::PartialSample`], or
+    /// [`Error::Io`]. Every error but the last is raised before `npy` is
+    /// created.
+    pub fn export_npy_range<P: AsRef<Path>>(
+        &self,
+        samples: Range<u64>,
+        npy: P,
+    ) -> Result<(), Error> {
+        let path = npy.as_ref();
+        let global = &self.metadata.global;
+        if let Some(channels) = global.num_channels {
+            if channels != 1 {
+                return Err(MetadataError::MultiChannelDataset(channels).into());
+            }
+        }
+        let descr = descr(global.datatype).ok_or(MetadataError::NoEquivalentFormat {
+            datatype: global.datatype,
+            format: "a NumPy array",
+        })?;
+
+        let (mut dataset, segments) = self.open_dataset()?;
+        let sample_count = segments.last().map_or(0, |s| s.samples.end);
+        if samples.start > samples.end || samples.end > sample_count {
+            return Err(MetadataError::SampleRangeOutOfBounds {
+                start: samples.start,
+                end: samples.end,
+                sample_count,
+            }
+            .into());
+        }
+        let source = self.dataset_file()?;
+        let sample_size = global.datatype.size();
+
+        let mut out = BufWriter::new(fs::File::create(path).map_err(at(path))?);
+        out.write_all(&header(&descr, samples.end - samples.start))
+            .map_err(at(path))?;
+        for segment in &segments {
+            if segment.samples.end <= samples.start || samples.end <= segment.samples.start {
+                continue;
+            }
+            let kept =
+                segment.samples.start.max(samples.start)..segment.samples.end.min(samples.end);
+            read_range(
+                &mut dataset,
+                source,
+                segment.bytes_of(&kept, sample_size),
+                |bytes| out.write_all(bytes).map_err(at(path)),
+            )?;
+        }
+        finish_file(out, path)
+    }
+}
+
+/// Write the one-dimensional array in the `.npy` file `npy` as a new
+/// Recording, `basename.sigmf-data` and `basename.sigmf-meta`, of samples
+/// taken at `sample_rate` samples per second, and return it opened.
+///
+/// The datatype is the one the array's `descr` names, in the
+/// [module](self)'s table. A `=` byte order, which means the writing
+/// machine's, is read as this machine's. `fortran_order` is ignored: for one
+/// dimension both orders are the same bytes.
+///
+/// # Errors
+///
+/// [`MetadataError::SampleRateOutOfRange`]; [`Error::Io`] naming `npy` if it
+/// cannot be read, is not an `.npy` file, holds an array of another shape or
+/// of a type SigMF has no datatype for, or is shorter than its shape says; or
+/// [`Error::Io`] for a file that cannot be written.
+pub fn import<P: AsRef<Path>, Q: AsRef<Path>>(
+    npy: P,
+    basename: Q,
+    sample_rate: f64,
+) -> Result<SigMF, Error> {
+    let path = npy.as_ref();
+    if !(sample_rate > 0.0 && sample_rate <= 1e12) {
+        return Err(MetadataError::SampleRateOutOfRange(sample_rate).into());
+    }
+    let mut file = fs::File::open(path).map_err(at(path))?;
+
+    let mut preamble = [0u8; 10];
+    file.read_exact(&mut preamble)
+        .map_err(|_| invalid(path, "too short to be an .npy file"))?;
+    if &preamble[..6] != MAGIC {
+        return Err(invalid(path, "not an .npy file"));
+    }
+    let (header_len, data_start) = match preamble[6] {
+        1 => {
+            let len = u16::from_le_bytes([preamble[8], preamble[9]]);
+            (usize::from(len), 10 + u64::from(len))
+        }
+        2 | 3 => {
+            let mut rest = [0u8; 2];
+            file.read_exact(&mut rest)
+                .map_err(|_| invalid(path, "too short to be an .npy file"))?;
+            let len = u32::from_le_bytes([preamble[8], preamble[9], rest[0], rest[1]]);
+            (len as usize, 12 + u64::from(len))
+        }
+        major => {
+            return Err(invalid(
+                path,
+                &format!("an .npy file of version {major}, where only 1 to 3 are known"),
+            ))
+        }
+    };
+    let mut header = vec![0u8; header_len];
+    file.read_exact(&mut header)
+        .map_err(|_| invalid(path, "an .npy file cut short in its header"))?;
+    let header = String::from_utf8_lossy(&header);
+
+    let fields = parse_header(&header).ok_or_else(|| {
+        invalid(
+            path,
+            "an .npy header that is not a dictionary of `descr`, `fortran_order` and `shape`",
+        )
+    })?;
+    let datatype = fields
+        .descr
+        .as_deref()
+        .and_then(data_format)
+        .ok_or_else(|| {
+            invalid(
+                path,
+                &format!(
+                    "an array of `{}`, which has no SigMF datatype",
+                    fields.descr.as_deref().unwrap_or("a structured type")
+                ),
+            )
+        })?;
+    let length = match fields.shape.as_slice() {
+        [length] => *length,
+        shape => {
+            return Err(invalid(
+                path,
+                &format!(
+                    "an array of {} dimensions, where a Dataset is one",
+                    shape.len()
+                ),
+            ))
+        }
+    };
+    let data_len = length
+        .checked_mul(datatype.size())
+        .ok_or_else(|| invalid(path, "an array too long to address"))?;
+    let file_len = file.metadata().map_err(at(path))?.len();
+    let data_end = data_start
+        .checked_add(data_len)
+        .filter(|&end| end <= file_len)
+        .ok_or_else(|| invalid(path, "an .npy file shorter than its shape says"))?;
+
+    let mut global = GlobalMetadata::describing(datatype);
+    global.sample_rate = Some(sample_rate);
+    let mut writer = DatasetWriter::create(basename.as_ref(), &[path])?;
+    read_range(&mut file, path, data_start..data_end, |bytes| {
+        writer.write(bytes)
+    })?;
+    writer.finish(Metadata {
+        global,
+        captures: vec![CaptureMetadata::new(0)],
+        annotations: vec![],
+    })
+}
+
+/// The `descr` of `datatype`, for the datatypes NumPy has one for.
+fn descr(datatype: DataFormat) -> Option<String> {
+    let order = |endianness: Endianness| match endianness {
+        Endianness::LittleEndian => '<',
+        Endianness::BigEndian => '>',
+    };
+    let (order, kind, width) = match (datatype.number_type, datatype.data_type) {
+        (NumberType::Real, DataType::I8) => ('|', 'i', 1),
+        (NumberType::Real, DataType::U8) => ('|', 'u', 1),
+        (NumberType::Real, DataType::I16(e)) => (order(e), 'i', 2),
+        (NumberType::Real, DataType::U16(e)) => (order(e), 'u', 2),
+        (NumberType::Real, DataType::I32(e)) => (order(e), 'i', 4),
+        (NumberType::Real, DataType::U32(e)) => (order(e), 'u', 4),
+        (NumberType::Real, DataType::F32(e)) => (order(e), 'f', 4),
+        (NumberType::Real, DataType::F64(e)) => (order(e), 'f', 8),
+        (NumberType::Complex, DataType::F32(e)) => (order(e), 'c', 8),
+        (NumberType::Complex, DataType::F64(e)) => (order(e), 'c', 16),
+        (NumberType::Complex, _) => return None,
+    };
+    Some(format!("{order}{kind}{width}"))
+}
+
+/// The inverse of [`descr`], for the `descr`s that have one.
+fn data_format(descr: &str) -> Option<DataFormat> {
+    let mut chars = descr.chars();
+    let endianness = match chars.next()? {
+        '<' => Endianness::LittleEndian,
+        '>' => Endianness::BigEndian,
+        '|' | '=' if cfg!(target_endian = "big") => Endianness::BigEndian,
+        '|' | '=' => Endianness::LittleEndian,
+        _ => return None,
+    };
+    let (number_type, data_type) = match chars.as_str() {
+        "i1" | "b" => (NumberType::Real, DataType::I8),
+        "u1" | "B" => (NumberType::Real, DataType::U8),
+        "i2" => (NumberType::Real, DataType::I16(endianness)),
+        "u2" => (NumberType::Real, DataType::U16(endianness)),
+        "i4" => (NumberType::Real, DataType::I32(endianness)),
+        "u4" => (NumberType::Real, DataType::U32(endianness)),
+        "f4" => (NumberType::Real, DataType::F32(endianness)),
+        "f8" => (NumberType::Real, DataType::F64(endianness)),
+        "c8" => (NumberType::Complex, DataType::F32(endianness)),
+        "c16" => (NumberType::Complex, DataType::F64(endianness)),
+        _ => return None,
+    };
+    Some(DataFormat {
+        number_type,
+        data_type,
+    })
+}
+
+/// A version 1.0 header for `length` elements of `descr`, padded with spaces
+/// so that the array starts on an [`ALIGNMENT`] boundary.
+fn header(descr: &str, length: u64) -> Vec<u8> {
+    let dictionary =
+        format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': ({length},), }}");
+    let unpadded = MAGIC.len() + 4 + dictionary.len() + 1;
+    let padded = unpadded.next_multiple_of(ALIGNMENT);
+
+    let mut header = Vec::with_capacity(padded);
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&[1, 0]);
+    header.extend_from_slice(&((padded - MAGIC.len() - 4) as u16).to_le_bytes());
+    header.extend_from_slice(dictionary.as_bytes());
+    header.resize(padded - 1, b' ');
+    header.push(b'\n');
+    header
+}
+
+/// The fields of an `.npy` header this reads.
+struct Fields {
+    /// `None` for a `descr` that is not a string: a structured array's list.
+    descr: Option<String>,
+    shape: Vec<u64>,
+}
+
+/// Parse the Python dictionary literal of an `.npy` header: string keys, and
+/// values that are strings, `True` or `False`, tuples of integers — or, for a
+/// structured `descr`, a list, which is skipped.
+fn parse_header(header: &str) -> Option<Fields> {
+    let mut rest = header.trim().strip_prefix('{')?.trim_start();
+    let (mut descr, mut shape, mut seen_descr) = (None, None, false);
+    while !rest.starts_with('}') {
+        let (key, after) = string(rest)?;
+        rest = after.trim_start().strip_prefix(':')?.trim_start();
+        match key.as_str() {
+            "descr" => {
+                seen_descr = true;
+                if rest.starts_with('[') {
+                    let end = rest.rfind(']')?;
+                    rest = &rest[end + 1..];
+                } else {
+                    let (value, after) = string(rest)?;
+                    descr = Some(value);
+                    rest = after;
+                }
+            }
+            "shape" => {
+                let end = rest.find(')')?;
+                let dimensions = rest.strip_prefix('(')?[..end - 1]
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|d| !d.is_empty())
+                    .map(|d| d.trim_end_matches('L').parse().ok())
+                    .collect::<Option<Vec<u64>>>()?;
+                shape = Some(dimensions);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                let end = rest.find([',', '}'])?;
+                rest = &rest[end..];
+            }
+        }
+        rest = rest.trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+    seen_descr.then_some(())?;
+    Some(Fields {
+        descr,
+        shape: shape?,
+    })
+}
+
+/// A quoted Python string at the start of `text`, and what follows it.
+fn string(text: &str) -> Option<(String, &str)> {
+    let quote = text.chars().next().filter(|&c| c == '\'' || c == '"')?;
+    let end = text[1..].find(quote)? + 1;
+    Some((text[1..end].to_string(), &text[end + 1..]))
+}
@@ -0,0 +1,107 @@
+//! Turning whole Datasets into samples and back, across every core when the
+//! `rayon` feature is on.
+//!
+//! Decoding and encoding are the same small function applied to each sample
+//! independently, which is the easiest kind of work to spread over threads:
+//! the Dataset is cut into pieces, each piece converted on its own, and the
+//! pieces laid out where the serial loop would have put them. Nothing a
+//! conversion produces depends on which thread produced it or in what order
+//! the pieces finished, so the output is identical byte for byte with the
+//! feature on or off — the feature changes how long it takes, never what comes
+//! back.
+//!
+//! Hashing is the exception. SHA-512 is a chain in which each block's state
+//! depends on the block before, so no split of a Dataset hashes faster than
+//! one core can; what threads can do is keep that core busy, by reading the
+//! next piece of a file while the current one is hashed.
+
+use std::io::Read;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use sha2::{Digest, Sha512};
+
+use super::dataset::CHUNK_BYTES;
+use super::{Endianness, Sample};
+
+/// Append the samples in `bytes` — a whole number of them, `sample_size`
+/// bytes each — to `out`, in order.
+pub(super) fn decode_into<S: Sample>(
+    bytes: &[u8],
+    sample_size: usize,
+    endianness: Endianness,
+    out: &mut Vec<S>,
+) {
+    #[cfg(feature = "rayon")]
+    {
+        // `CHUNK_BYTES` is a whole number of samples of every datatype, so
+        // every piece but the last is whole, and the last is what remains of
+        // a whole number.
+        let pieces: Vec<Vec<S>> = bytes
+            .par_chunks(CHUNK_BYTES)
+            .map(|piece| {
+                let mut samples = Vec::with_capacity(piece.len() / sample_size);
+                S::decode_slice(piece, endianness, &mut samples);
+                samples
+            })
+            .collect();
+        out.reserve(bytes.len() / sample_size);
+        for piece in pieces {
+            out.extend_from_slice(&piece);
+        }
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        out.reserve(bytes.len() / sample_size);
+        S::decode_slice(bytes, endianness, out);
+    }
+}
+
+/// The bytes of `samples` in `endianness`, `sample_size` bytes each.
+pub(super) fn encode<S: Sample>(
+    samples: &[S],
+    sample_size: usize,
+    endianness: Endianness,
+) -> Vec<u8> {
+    let mut data = vec![0u8; samples.len() * sample_size];
+    #[cfg(feature = "rayon")]
+    {
+        let per_piece = CHUNK_BYTES / sample_size;
+        data.par_chunks_mut(per_piece * sample_size)
+            .zip(samples.par_chunks(per_piece))
+            .for_each(|(out, piece)| S::encode_slice(piece, endianness, out));
+    }
+    #[cfg(not(feature = "rayon"))]
+    S::encode_slice(samples, endianness, &mut data);
+    data
+}
+
+/// The SHA-512 of everything `reader` yields, read [`CHUNK_BYTES`] at a time.
+///
+/// With `rayon`, each read is made on another thread while the previous
+/// chunk is hashed.
+pub(super) fn sha512_of(mut reader: impl Read + Send) -> std::io::Result<Sha512> {
+    let mut hasher = Sha512::new();
+    let mut current = vec![0u8; CHUNK_BYTES];
+    let mut filled = reader.read(&mut current)?;
+
+    #[cfg(feature = "rayon")]
+    {
+        let mut next = vec![0u8; CHUNK_BYTES];
+        while filled > 0 {
+            let ((), read) = rayon::join(
+                || hasher.update(&current[..filled]),
+                || reader.read(&mut next),
+            );
+            filled = read?;
+            std::mem::swap(&mut current, &mut next);
+        }
+    }
+    #[cfg(not(feature = "rayon"))]
+    while filled > 0 {
+        hasher.update(&current[..filled]);
+        filled = reader.read(&mut current)?;
+    }
+
+    Ok(hasher)
+}
@@ -0,0 +1,570 @@
+//! What is in a Recording, at a glance: a power spectrum of each Captures
+//! segment, and a spectrogram of the whole, small enough for a thumbnail.
+//!
+//! [`Preview::compute`] streams the Dataset once, through a Hann-windowed FFT
+//! of non-overlapping frames, so the cost of a preview is one read of the
+//! Dataset and the memory it takes is the size of the result. Each frame's
+//! power is averaged into its segment's [`Psd`] and into one row of the
+//! [`Spectrogram`], whose rows each average a run of consecutive frames — as
+//! many runs as the preview has rows, however long the Recording.
+//!
+//! Power is in dB relative to full scale, for the reading a thumbnail needs:
+//! integer samples are scaled as [`Scaling::FullScale`](super::Scaling::FullScale)
+//! scales them, and the window's gain is divided out, so a complex tone of
+//! amplitude 1.0 centred in a bin reads 0 dB. An RTL-SDR's `cu8` is not
+//! recentred on zero first, and shows its offset as a tone at DC.
+//!
+//! The FFT is radix-2, so the frame length is a power of two.
+
+use std::f64::consts::PI;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use num_complex::Complex;
+
+use super::dataset::read_range;
+use super::transcode::{decode, full_scale};
+use super::{at, invalid, Error, MetadataError, NumberType, SigMF};
+
+/// How a Recording is to be previewed: how long a frame is, and how many rows
+/// the spectrogram has at most.
+///
+/// # Examples
+///
+/// ```
+/// use sigmf::num_complex::Complex;
+/// use sigmf::preview::Preview;
+/// use sigmf::RecordingWriter;
+/// # let dir = tempfile::tempdir().expect("a temporary directory");
+///
+/// // A tone a quarter of the way up the band, at 1 kSa/s.
+/// let tone: Vec<Complex<f32>> = (0..4096)
+///     .map(|n| Complex::from_polar(1.0, std::f32::consts::FRAC_PI_2 * n as f32))
+///     .collect();
+/// let recording = RecordingWriter::new(&tone, 1_000.0).to_file(dir.path().join("tone"))?;
+///
+/// let spectra = Preview::new(64).rows(16).compute(&recording)?;
+/// let psd = &spectra.psds[0];
+/// let peak = (0..64).max_by(|&a, &b| psd.power_db[a].total_cmp(&psd.power_db[b])).unwrap();
+/// assert_eq!(psd.frequencies[peak], 250.0);
+/// assert_eq!(spectra.spectrogram.power_db.len(), 16);
+///
+/// spectra.spectrogram.to_png(dir.path().join("tone.png"))?;
+/// # Ok::<(), sigmf::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Preview {
+    fft_size: usize,
+    rows: usize,
+}
+
+/// What [`Preview::compute`] found.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Spectra {
+    /// One power spectrum per Captures segment, in order.
+    pub psds: Vec<Psd>,
+    /// The spectrogram of the whole Recording.
+    pub spectrogram: Spectrogram,
+}
+
+/// The average power spectrum of one Captures segment.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Psd {
+    /// The segment's index in `captures`.
+    pub capture: usize,
+    /// The centre of each bin, in Hz, lowest first: on the air where the
+    /// segment has a `core:frequency`, and at baseband where it does not.
+    pub frequencies: Vec<f64>,
+    /// The power in each bin, in dB relative to full scale.
+    pub power_db: Vec<f64>,
+    /// How many frames were averaged. A segment shorter than one frame has
+    /// none, and an empty spectrum.
+    pub frames: u64,
+}
+
+/// A downsampled spectrogram: power by time and frequency, one row per run of
+/// frames, earliest first.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Spectrogram {
+    /// Row-major: `power_db[row][column]`, in dB relative to full scale.
+    pub power_db: Vec<Vec<f64>>,
+    /// The Dataset index of the first sample each row covers.
+    pub row_starts: Vec<u64>,
+    /// Each column's distance from the centre frequency of the segment it was
+    /// recorded in, in Hz — from −½ to +½ the sample rate for a complex
+    /// Recording, and from 0 to +½ for a real one.
+    pub offsets: Vec<f64>,
+    /// Where each annotation falls, in rows and columns.
+    pub boxes: Vec<AnnotationBox>,
+}
+
+/// An annotation's extent on a [`Spectrogram`].
+///
+/// Its rows are those holding any of its samples. Its columns are those
+/// between its frequency edges, taken relative to the `core:frequency` of the
+/// segment it starts in, or every column for an annotation without edges.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AnnotationBox {
+    /// The annotation's index in `annotations`.
+    pub annotation: usize,
+    /// The spectrogram rows it covers.
+    pub rows: Range<usize>,
+    /// The spectrogram columns it covers.
+    pub columns: Range<usize>,
+}
+
+impl Preview {
+    /// A preview with frames of `fft_size` samples and a spectrogram of at
+    /// most 256 rows.
+    ///
+    /// # Panics
+    ///
+    /// If `fft_size` is not a power of two of at least 2.
+    pub fn new(fft_size: usize) -> Preview {
+        assert!(
+            fft_size >= 2 && fft_size.is_power_of_two(),
+            "the FFT is radix-2, so its size must be a power of two of at least 2, not {fft_size}"
+        );
+        Preview {
+            fft_size,
+            rows: 256,
+        }
+    }
+
+    /// The most rows the spectrogram may have. A Recording of fewer frames
+    /// than this has a row per frame.
+    pub fn rows(mut self, rows: usize) -> Self {
+        self.rows = rows.max(1);
+        self
+    }
+
+    /// Stream `recording`'s Dataset through the FFT.
+    ///
+    /// Each segment is cut into whole frames, and the samples left over at its
+    /// end — fewer than a frame — are not in the preview: a frame never spans
+    /// a retune.
+    ///
+    /// # Errors
+    ///
+    /// [`MetadataError::NoSampleRate`], [`MetadataError::MultiChannelDataset`],
+    /// [`MetadataError::NoDataset`], [`MetadataError::PartialSample`], or
+    /// [`Error::Io`].
+    pub fn compute(&self, recording: &SigMF) -> Result<Spectra, Error> {
+        let metadata = &recording.metadata;
+        let global = &metadata.global;
+        if let Some(channels) = global.num_channels {
+            if channels != 1 {
+                return Err(MetadataError::MultiChannelDataset(channels).into());
+            }
+        }
+        let rate = global.sample_rate.ok_or(MetadataError::NoSampleRate)?;
+        let (mut file, segments) = recording.open_dataset()?;
+        let source = recording.dataset_file()?;
+
+        let n = self.fft_size;
+        let complex = global.datatype.number_type == NumberType::Complex;
+        let columns = if complex { n } else { n / 2 };
+        let offsets: Vec<f64> = (0..columns)
+            .map(|column| {
+                let bin = if complex {
+                    column as f64 - (n / 2) as f64
+                } else {
+                    column as f64
+                };
+                bin * rate / n as f64
+            })
+            .collect();
+
+        let total_frames: u64 = segments
+            .iter()
+            .map(|s| (s.samples.end - s.samples.start) / n as u64)
+            .sum();
+        let row_count = (self.rows as u64).min(total_frames) as usize;
+        // Row `r` averages frames `first_frame(r)..first_frame(r + 1)`.
+        let first_frame = |row: usize| -> u64 {
+            (u128::from(total_frames) * row as u128 / row_count as u128) as u64
+        };
+
+        let window: Vec<f64> = (0..n)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f64 / n as f64).cos())
+            .collect();
+        let gain = window.iter().sum::<f64>().powi(2);
+        let fft = Fft::new(n);
+
+        let data_type = global.datatype.data_type;
+        let component = data_type.size() as usize;
+        let scale = full_scale(data_type);
+        let sample_size = global.datatype.size() as usize;
+
+        let mut psds = Vec::with_capacity(segments.len());
+        let mut rows = vec![vec![0.0; columns]; row_count];
+        let mut row_frames = vec![0u64; row_count];
+        let mut row_starts = vec![0u64; row_count];
+        let mut frame = 0u64;
+        let mut row = 0usize;
+        let mut buffer = Vec::with_capacity(n);
+
+        for segment in &segments {
+            let mut power = vec![0.0; columns];
+            let mut frames = 0u64;
+            let whole = (segment.samples.end - segment.samples.start) / n as u64 * n as u64;
+            let kept = segment.samples.start..segment.samples.start + whole;
+            let mut next_sample = segment.samples.start;
+            buffer.clear();
+            read_range(
+                &mut file,
+                source,
+                segment.bytes_of(&kept, sample_size as u64),
+                |bytes| {
+                    for sample in bytes.chunks_exact(sample_size) {
+                        let re = decode(data_type, &sample[..component]) / scale;
+                        let im = if complex {
+                            decode(data_type, &sample[component..]) / scale
+                        } else {
+                            0.0
+                        };
+                        buffer.push(Complex::new(
+                            re * window[buffer.len()],
+                            im * window[buffer.len()],
+                        ));
+                        if buffer.len() < n {
+                            continue;
+                        }
+
+                        fft.transform(&mut buffer);
+                        while row + 1 < row_count && frame >= first_frame(row + 1) {
+                            row += 1;
+                        }
+                        if row_frames[row] == 0 {
+                            row_starts[row] = next_sample;
+                        }
+                        for (column, bin) in power.iter_mut().enumerate() {
+                            let index = if complex {
+                                (column + n / 2) % n
+                            } else {
+                                column
+                            };
+                            let value = buffer[index].norm_sqr() / gain;
+                            *bin += value;
+                            rows[row][column] += value;
+                        }
+                        row_frames[row] += 1;
+                        frames += 1;
+                        frame += 1;
+                        next_sample += n as u64;
+                        buffer.clear();
+                    }
+                    Ok(())
+                },
+            )?;
+
+            let centre = metadata
+                .captures
+                .get(segment.index)
+                .and_then(|capture| capture.frequency)
+                .unwrap_or(0.0);
+            let (frequencies, power_db) = if frames == 0 {
+                (vec![], vec![])
+            } else {
+                (
+                    offsets.iter().map(|offset| centre + offset).collect(),
+                    power.iter().map(|p| decibels(p / frames as f64)).collect(),
+                )
+            };
+            psds.push(Psd {
+                capture: segment.index,
+                frequencies,
+                power_db,
+                frames,
+            });
+        }
+
+        for (row, frames) in rows.iter_mut().zip(&row_frames) {
+            for bin in row.iter_mut() {
+                *bin = decibels(*bin / (*frames).max(1) as f64);
+            }
+        }
+
+        let sample_count = segments.last().map_or(0, |s| s.samples.end);
+        let boxes = if row_count == 0 {
+            vec![]
+        } else {
+            annotation_boxes(
+                recording,
+                &row_starts,
+                sample_count,
+                rate,
+                complex,
+                n,
+                columns,
+            )
+        };
+
+        Ok(Spectra {
+            psds,
+            spectrogram: Spectrogram {
+                power_db: rows,
+                row_starts,
+                offsets,
+                boxes,
+            },
+        })
+    }
+}
+
+impl Spectrogram {
+    /// Render the spectrogram to the PNG file `png`, a pixel per row and
+    /// column: time running down, frequency rising to the right, and each
+    /// annotation outlined in white.
+    ///
+    /// The colour scale runs from the spectrogram's strongest bin down 80 dB,
+    /// or to its weakest if that is nearer; anything below is the scale's
+    /// darkest colour.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Io`] if the spectrogram has no rows, or `png` cannot be
+    /// written.
+    pub fn to_png<P: AsRef<Path>>(&self, png: P) -> Result<(), Error> {
+        let path = png.as_ref();
+        let (height, width) = (self.power_db.len(), self.offsets.len());
+        if height == 0 || width == 0 {
+            return Err(invalid(path, "a spectrogram of no frames has no picture"));
+        }
+
+        let finite = || {
+            self.power_db
+                .iter()
+                .flatten()
+                .copied()
+                .filter(|p| p.is_finite())
+        };
+        let top = finite().fold(f64::NEG_INFINITY, f64::max);
+        let bottom = finite().fold(f64::INFINITY, f64::min).max(top - 80.0);
+        let span = (top - bottom).max(f64::EPSILON);
+
+        let mut pixels = vec![[0u8; 3]; width * height];
+        for (row, values) in self.power_db.iter().enumerate() {
+            for (column, value) in values.iter().enumerate() {
+                pixels[row * width + column] = colour(((value - bottom) / span).clamp(0.0, 1.0));
+            }
+        }
+        for annotation in &self.boxes {
+            let (rows, columns) = (&annotation.rows, &annotation.columns);
+            let (last_row, last_column) = (rows.end - 1, columns.end - 1);
+            for column in columns.clone() {
+                pixels[rows.start * width + column] = [255; 3];
+                pixels[last_row * width + column] = [255; 3];
+            }
+            for row in rows.clone() {
+                pixels[row * width + columns.start] = [255; 3];
+                pixels[row * width + last_column] = [255; 3];
+            }
+        }
+
+        fs::write(path, encode_png(width, height, &pixels)).map_err(at(path))
+    }
+}
+
+/// Where each of `recording`'s annotations falls on a spectrogram whose rows
+/// start at `row_starts`.
+fn annotation_boxes(
+    recording: &SigMF,
+    row_starts: &[u64],
+    sample_count: u64,
+    rate: f64,
+    complex: bool,
+    n: usize,
+    columns: usize,
+) -> Vec<AnnotationBox> {
+    let row_of = |sample: u64| row_starts.partition_point(|&start| start <= sample).max(1) - 1;
+    let column_of = |offset: f64| -> f64 {
+        let bins = offset / rate * n as f64;
+        let column = if complex { bins + (n / 2) as f64 } else { bins };
+        column.clamp(0.0, columns as f64)
+    };
+
+    let metadata = &recording.metadata;
+    let offset = metadata.global.offset.unwrap_or(0);
+    metadata
+        .annotations
+        .iter()
+        .enumerate()
+        .filter_map(|(index, annotation)| {
+            // Rows are laid out by Dataset index; annotations count from
+            // `core:offset`.
+            let start = annotation.sample_start.saturating_sub(offset);
+            let end = match annotation.sample_count {
+                Some(count) => start.saturating_add(count),
+                None => sample_count,
+            }
+            .min(sample_count);
+            if start >= end {
+                return None;
+            }
+            let rows = row_of(start)..row_of(end - 1) + 1;
+
+            let columns = match (annotation.freq_lower_edge, annotation.freq_upper_edge) {
+                (Some(lower), Some(upper)) => {
+                    let centre = metadata
+                        .captures
+                        .iter()
+                        .rev()
+                        .find(|capture| capture.sample_start <= annotation.sample_start)
+                        .and_then(|capture| capture.frequency)
+                        .unwrap_or(0.0);
+                    let first = column_of(lower - centre).floor() as usize;
+                    let last = column_of(upper - centre).ceil() as usize;
+                    first..last.min(columns)
+                }
+                _ => 0..columns,
+            };
+            (!columns.is_empty()).then_some(AnnotationBox {
+                annotation: index,
+                rows,
+                columns,
+            })
+        })
+        .collect()
+}
+
+fn decibels(power: f64) -> f64 {
+    10.0 * power.max(1e-30).log10()
+}
+
+/// An iterative radix-2 FFT of one size, its twiddle factors computed once.
+struct Fft {
+    twiddles: Vec<Complex<f64>>,
+}
+
+impl Fft {
+    fn new(n: usize) -> Fft {
+        let twiddles = (0..n / 2)
+            .map(|k| {
+                let angle = -2.0 * PI * k as f64 / n as f64;
+                Complex::new(angle.cos(), angle.sin())
+            })
+            .collect();
+        Fft { twiddles }
+    }
+
+    /// Transform `data`, whose length is twice the twiddles', in place.
+    fn transform(&self, data: &mut [Complex<f64>]) {
+        let n = data.len();
+        let bits = n.trailing_zeros();
+        for i in 0..n {
+            let j = i.reverse_bits() >> (usize::BITS - bits);
+            if i < j {
+                data.swap(i, j);
+            }
+        }
+        let mut len = 2;
+        while len <= n {
+            let stride = n / len;
+            for chunk in data.chunks_exact_mut(len) {
+                let (low, high) = chunk.split_at_mut(len / 2);
+                for (k, (a, b)) in low.iter_mut().zip(high.iter_mut()).enumerate() {
+                    let t = *b * self.twiddles[k * stride];
+                    *b = *a - t;
+                    *a += t;
+                }
+            }
+            len *= 2;
+        }
+    }
+}
+
+/// A colour for `level`, from 0.0 to 1.0: black through purple and orange to
+/// pale yellow, in the manner of matplotlib's `inferno`.
+fn colour(level: f64) -> [u8; 3] {
+    const STOPS: [[f64; 3]; 5] = [
+        [0.0, 0.0, 4.0],
+        [87.0, 16.0, 110.0],
+        [188.0, 55.0, 84.0],
+        [249.0, 142.0, 9.0],
+        [252.0, 255.0, 164.0],
+    ];
+    let position = level * (STOPS.len() - 1) as f64;
+    let index = (position.floor() as usize).min(STOPS.len() - 2);
+    let fraction = position - index as f64;
+    let (from, to) = (STOPS[index], STOPS[index + 1]);
+    [0, 1, 2]
+        .map(|channel| (from[channel] + (to[channel] - from[channel]) * fraction).round() as u8)
+}
+
+/// An 8-bit RGB PNG of `pixels`, row by row.
+///
+/// The image data is stored rather than compressed — a zlib stream of
+/// uncompressed deflate blocks — which every decoder reads, and which costs a
+/// thumbnail's worth of bytes rather than a compression library.
+fn encode_png(width: usize, height: usize, pixels: &[[u8; 3]]) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(height * (1 + 3 * width));
+    for row in pixels.chunks_exact(width) {
+        // Filter type 0: the scanline as it is.
+        raw.push(0);
+        raw.extend(row.iter().flatten());
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let blocks = raw.chunks(u16::MAX as usize);
+    let count = blocks.len();
+    for (index, block) in blocks.enumerate() {
+        zlib.push(u8::from(index + 1 == count));
+        let len = block.len() as u16;
+        zlib.extend(len.to_le_bytes());
+        zlib.extend((!len).to_le_bytes());
+        zlib.extend(block);
+    }
+    zlib.extend(adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // 8 bits per channel, colour type 2 (RGB), deflate, no filtering
+    // beyond per-row, no interlace.
+    header.extend([8, 2, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    for (kind, data) in [(b"IHDR", &header), (b"IDAT", &zlib), (b"IEND", &Vec::new())] {
+        png.extend((data.len() as u32).to_be_bytes());
+        let start = png.len();
+        png.extend(kind);
+        png.extend(data);
+        let crc = crc32(&png[start..]);
+        png.extend(crc.to_be_bytes());
+    }
+    png
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) {
+        for &byte in chunk {
+            a += u32::from(byte);
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    b << 16 | a
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                crc >> 1 ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
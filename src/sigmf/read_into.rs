@@ -0,0 +1,101 @@
+//! Reading samples into a buffer the caller owns, for pipelines that reuse
+//! their buffers from one read to the next.
+
+use std::io::{Read, Seek, SeekFrom};
+
+use super::{at, Error, MetadataError, Sample, SigMF};
+
+/// How many bytes of Dataset are read at a time, on the stack.
+///
+/// A whole number of samples of every datatype, like
+/// [`CHUNK_BYTES`](super::dataset::CHUNK_BYTES), and small enough to sit in
+/// a frame of a thread with a modest stack.
+const STACK_BYTES: usize = 1 << 14;
+
+impl SigMF {
+    /// Fill `out` with the samples from Dataset index `start` onwards, and
+    /// return how many it holds now — all of `out`, unless the Dataset ends
+    /// first.
+    ///
+    /// The samples are those [`samples`](Self::samples) returns from index
+    /// `start`, read across Captures segments and past a Non-Conforming
+    /// Dataset's `core:header_bytes` as it reads them, and `S` is checked
+    /// against `core:datatype` as it checks it. What differs is where they go:
+    /// nothing is allocated in proportion to the samples, the bytes passing
+    /// through a fixed buffer on the stack, so a loop that reads a block at a
+    /// time into the same slice costs a file open and a seek per call and
+    /// nothing that grows with the block.
+    ///
+    /// Reading from the end of the Dataset returns 0, as [`Read::read`] does at
+    /// the end of a file; the samples past the count returned are left as they
+    /// were.
+    ///
+    /// # Errors
+    ///
+    /// [`MetadataError::SampleRangeOutOfBounds`] if `start` is past the end of
+    /// the Dataset, and otherwise the errors of [`samples`](Self::samples).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sigmf::num_complex::Complex;
+    /// use sigmf::RecordingWriter;
+    /// # let dir = tempfile::tempdir().expect("a temporary directory");
+    ///
+    /// let samples: Vec<Complex<i16>> = (0..10).map(|i| Complex::new(i, -i)).collect();
+    /// let recording = RecordingWriter::new(&samples, 1e3).to_file(dir.path().join("block"))?;
+    ///
+    /// let mut block = [Complex::new(0, 0); 4];
+    /// let mut start = 0;
+    /// loop {
+    ///     let read = recording.read_samples_into(start, &mut block)?;
+    ///     if read == 0 {
+    ///         break;
+    ///     }
+    ///     assert_eq!(block[..read], samples[start as usize..][..read]);
+    ///     start += read as u64;
+    /// }
+    /// assert_eq!(start, 10);
+    /// # Ok::<(), sigmf::Error>(())
+    /// ```
+    pub fn read_samples_into<S: Sample>(&self, start: u64, out: &mut [S]) -> Result<usize, Error> {
+        let endianness = self.check_sample_type::<S>()?;
+        let (mut file, segments) = self.open_dataset()?;
+        let path = self.dataset_file()?;
+        let sample_size = self.metadata.global.datatype.size();
+
+        let sample_count = segments.last().map_or(0, |s| s.samples.end);
+        if start > sample_count {
+            return Err(MetadataError::SampleRangeOutOfBounds {
+                start,
+                end: start.saturating_add(out.len() as u64),
+                sample_count,
+            }
+            .into());
+        }
+        let end = sample_count.min(start.saturating_add(out.len() as u64));
+
+        let mut buffer = [0u8; STACK_BYTES];
+        let per_read = STACK_BYTES / sample_size as usize;
+        let mut filled = 0;
+        for segment in &segments {
+            let wanted = segment.samples.start.max(start)..segment.samples.end.min(end);
+            if wanted.is_empty() {
+                continue;
+            }
+            let bytes = segment.bytes_of(&wanted, sample_size);
+            file.seek(SeekFrom::Start(bytes.start)).map_err(at(path))?;
+
+            let mut remaining = &mut out[filled..filled + (wanted.end - wanted.start) as usize];
+            filled += remaining.len();
+            while !remaining.is_empty() {
+                let count = remaining.len().min(per_read);
+                let chunk = &mut buffer[..count * sample_size as usize];
+                file.read_exact(chunk).map_err(at(path))?;
+                S::decode_to_slice(chunk, endianness, &mut remaining[..count]);
+                remaining = &mut remaining[count..];
+            }
+        }
+        Ok(filled)
+    }
+}
@@ -0,0 +1,221 @@
+//! Cutting a span of samples out of a Recording into a Recording of its own.
+
+use std::ops::Range;
+use std::path::Path;
+
+use super::dataset::{read_range, stream_index, DatasetWriter, Segment};
+use super::datetime::Timestamp;
+use super::{AnnotationMetadata, CaptureMetadata, Error, Metadata, MetadataError, SigMF};
+
+impl SigMF {
+    /// Write the samples at Dataset indices `samples` as a new Recording,
+    /// `basename.sigmf-data` and `basename.sigmf-meta`, and return it opened.
+    ///
+    /// The bytes are copied, not decoded — a slice has the datatype of its
+    /// source, and no sample passes through a float on the way — and they are
+    /// streamed, so cutting two seconds out of an hour costs two seconds.
+    ///
+    /// # What the document says afterwards
+    ///
+    /// Sample indices are absolute, so every sample keeps its index in the
+    /// slice, and what changes is where the Dataset begins:
+    ///
+    /// - **Where the samples came from.** `core:offset` is set to the index of
+    ///   the first sample kept — the source's `core:offset` plus
+    ///   `samples.start`.
+    /// - **Captures.** Each segment holding any of `samples` survives. The first
+    ///   may start partway through its segment: its `core:sample_start` moves
+    ///   up to the new `core:offset`, and its `core:datetime` forward by the
+    ///   samples skipped, at `core:sample_rate`. A segment carries
+    ///   `core:global_index` wherever its index in the original stream is not
+    ///   its `core:sample_start`, so a gap the source recorded is still visible
+    ///   in the slice.
+    /// - **Annotations.** Those overlapping `samples` survive, clipped to it.
+    ///   One without `core:sample_count` keeps running to the end of the
+    ///   Recording only if the slice does too.
+    /// - **Hash.** `core:sha512` is computed over the new Dataset.
+    ///
+    /// The new Dataset is conforming whatever the source was, so
+    /// `core:dataset`, `core:header_bytes` and `core:trailing_bytes` do not
+    /// survive. Everything else — extensions included — is carried as it was.
+    ///
+    /// # Errors
+    ///
+    /// [`MetadataError::SampleRangeOutOfBounds`] if `samples` is not within the
+    /// Dataset, [`MetadataError::NoSampleRate`] or
+    /// [`MetadataError::InvalidDatetime`] if a `core:datetime` must be moved and
+    /// cannot be, [`MetadataError::MultiChannelDataset`],
+    /// [`MetadataError::NoDataset`], [`MetadataError::PartialSample`], or
+    /// [`Error::Io`]. Every error but the last is raised before either file is
+    /// created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sigmf::num_complex::Complex;
+    /// use sigmf::{CaptureMetadata, RecordingWriter};
+    /// # let dir = tempfile::tempdir().expect("a temporary directory");
+    ///
+    /// let samples: Vec<Complex<i16>> = (0..8).map(|i| Complex::new(i, -i)).collect();
+    /// let mut writer = RecordingWriter::new(&samples, 4.0);
+    /// let mut capture = CaptureMetadata::new(0);
+    /// capture.datetime = Some("2026-07-16T09:14:22.000Z".to_string());
+    /// writer.captures_mut().push(capture);
+    /// let hour = writer.to_file(dir.path().join("hour"))?;
+    ///
+    /// // Samples 6 and 7: a second and a half after the first, at 4 Sa/s.
+    /// let burst = hour.slice(6..8, dir.path().join("burst"))?;
+    /// assert_eq!(burst.samples::<Complex<i16>>()?, &samples[6..]);
+    /// assert_eq!(burst.metadata.global.offset, Some(6));
+    /// assert_eq!(
+    ///     burst.metadata.captures[0].datetime.as_deref(),
+    ///     Some("2026-07-16T09:14:23.500Z")
+    /// );
+    /// # Ok::<(), sigmf::Error>(())
+    /// ```
+    pub fn slice<P: AsRef<Path>>(&self, samples: Range<u64>, basename: P) -> Result<SigMF, Error> {
+        if let Some(channels) = self.metadata.global.num_channels {
+            if channels != 1 {
+                return Err(MetadataError::MultiChannelDataset(channels).into());
+            }
+        }
+
+        let (mut file, segments) = self.open_dataset()?;
+        let sample_count = segments.last().map_or(0, |s| s.samples.end);
+        if samples.start > samples.end || samples.end > sample_count {
+            return Err(MetadataError::SampleRangeOutOfBounds {
+                start: samples.start,
+                end: samples.end,
+                sample_count,
+            }
+            .into());
+        }
+
+        let surviving: Vec<&Segment> = segments
+            .iter()
+            .filter(|s| s.samples.start < samples.end && samples.start < s.samples.end)
+            .collect();
+        let metadata = self.sliced_metadata(&surviving, &samples, sample_count)?;
+
+        let source = self.dataset_file()?;
+        let mut writer = DatasetWriter::create(basename.as_ref(), &[source])?;
+        let sample_size = self.metadata.global.datatype.size();
+        for segment in surviving {
+            let kept =
+                segment.samples.start.max(samples.start)..segment.samples.end.min(samples.end);
+            read_range(
+                &mut file,
+                source,
+                segment.bytes_of(&kept, sample_size),
+                |bytes| writer.write(bytes),
+            )?;
+        }
+        writer.finish(metadata)
+    }
+
+    /// The document for a slice of `samples`, given the segments it keeps.
+    fn sliced_metadata(
+        &self,
+        surviving: &[&Segment],
+        samples: &Range<u64>,
+        sample_count: u64,
+    ) -> Result<Metadata, MetadataError> {
+        let source = &self.metadata;
+        let source_offset = source.global.offset.unwrap_or(0);
+        let absolute = |sample: u64| source_offset.saturating_add(sample);
+
+        let mut global = source.global.clone();
+        global.offset = Some(absolute(samples.start))
+            .filter(|&offset| offset != 0 || source.global.offset.is_some());
+        global.dataset = None;
+        global.trailing_bytes = None;
+        global.metadata_only = None;
+
+        let mut captures = Vec::with_capacity(surviving.len());
+        for segment in surviving {
+            let start = segment.samples.start.max(samples.start);
+            let mut capture = source
+                .captures
+                .get(segment.index)
+                .cloned()
+                .unwrap_or_else(|| CaptureMetadata::new(source_offset));
+            let skipped = absolute(start).saturating_sub(capture.sample_start);
+            capture.sample_start = absolute(start);
+            capture.header_bytes = None;
+
+            let index = stream_index(source, segment, start);
+            capture.global_index = (index != capture.sample_start).then_some(index);
+
+            if skipped > 0 {
+                if let Some(datetime) = &capture.datetime {
+                    let moved = Timestamp::parse(datetime).ok_or_else(|| {
+                        MetadataError::InvalidDatetime {
+                            index: segment.index,
+                            datetime: datetime.clone(),
+                        }
+                    })?;
+                    let rate = source
+                        .global
+                        .sample_rate
+                        .ok_or(MetadataError::NoSampleRate)?;
+                    capture.datetime = Some(moved.add_seconds(skipped as f64 / rate).to_string());
+                }
+            }
+            captures.push(capture);
+        }
+
+        let annotations = source
+            .annotations
+            .iter()
+            .filter_map(|annotation| {
+                clip(
+                    annotation,
+                    &(absolute(samples.start)..absolute(samples.end)),
+                    absolute(sample_count),
+                )
+            })
+            .collect();
+
+        Ok(Metadata {
+            global,
+            captures,
+            annotations,
+        })
+    }
+}
+
+/// `annotation` clipped to `samples`, or `None` if it covers none of them.
+///
+/// Both are in the document's absolute indices, as is `recording_end`, one
+/// past the source's last sample.
+fn clip(
+    annotation: &AnnotationMetadata,
+    samples: &Range<u64>,
+    recording_end: u64,
+) -> Option<AnnotationMetadata> {
+    let start = annotation.sample_start;
+    let end = match annotation.sample_count {
+        Some(count) => start.saturating_add(count),
+        None => recording_end.max(start),
+    };
+
+    // An annotation of no samples marks an instant, and survives if the instant
+    // does.
+    let survives = if start == end {
+        samples.contains(&start)
+    } else {
+        start.max(samples.start) < end.min(samples.end)
+    };
+    if !survives {
+        return None;
+    }
+
+    let mut clipped = annotation.clone();
+    let kept = start.max(samples.start)..end.min(samples.end);
+    clipped.sample_start = kept.start;
+    clipped.sample_count = match annotation.sample_count {
+        None if samples.end == recording_end => None,
+        _ => Some(kept.end - kept.start),
+    };
+    Some(clipped)
+}
@@ -0,0 +1,242 @@
+//! One pass over a Recording's samples for the numbers an ingest check wants:
+//! how strong, how centred, how balanced, and how damaged.
+
+use num_complex::Complex;
+
+use super::dataset::read_range;
+use super::transcode::{decode, full_scale};
+use super::{DataType, Error, MetadataError, NumberType, SigMF};
+
+/// What [`SigMF::statistics`] found, segment by segment and over the whole
+/// Dataset.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Statistics {
+    /// One entry per Captures segment, in order — or one for the implicit
+    /// segment of a document whose `captures` is empty.
+    pub captures: Vec<SignalStatistics>,
+    /// The whole Dataset, as if it were one segment.
+    pub overall: SignalStatistics,
+}
+
+/// The statistics of a run of samples.
+///
+/// Values are in full-scale units, as [`Scaling::FullScale`](super::Scaling::FullScale)
+/// reads them: a float as it is, a signed integer divided by 2<sup>bits−1</sup>,
+/// an unsigned one by 2<sup>bits</sup>. A real Recording is read as complex
+/// samples whose imaginary part is zero.
+///
+/// The averages are over the samples whose every component is finite, so a
+/// NaN in a float Dataset is counted in [`nan`](Self::nan) rather than making
+/// every other number NaN too. Where there are no such samples, the averages
+/// and the peak are zero.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SignalStatistics {
+    /// How many samples there are, finite or not.
+    pub samples: u64,
+    /// The mean of |x|², DC included.
+    pub mean_power: f64,
+    /// The square root of [`mean_power`](Self::mean_power).
+    pub rms: f64,
+    /// The mean of the I and Q components — an offset-binary `cu8` Dataset
+    /// shows about +0.5 in each.
+    pub dc_offset: Complex<f64>,
+    /// How far I and Q differ in gain and depart from quadrature, for a
+    /// complex Recording of at least two finite samples with some variation in
+    /// both components; `None` otherwise.
+    pub iq_imbalance: Option<IqImbalance>,
+    /// The largest |x|.
+    pub peak_magnitude: f64,
+    /// How many components sit at the minimum or maximum of an integer
+    /// datatype, where an ADC driven past its range pins them. Always zero for
+    /// a float datatype.
+    pub clipped: u64,
+    /// How many components are NaN. Always zero for an integer datatype.
+    pub nan: u64,
+    /// How many components are infinite. Always zero for an integer datatype.
+    pub infinite: u64,
+}
+
+/// An estimate of IQ imbalance, from the second moments of I and Q about
+/// their means.
+///
+/// For a signal that is circular in the complex plane, as most received
+/// noise and modulation is, both are zero; a tone or a real signal mixed to
+/// complex is not circular, and reads as imbalanced whatever the receiver.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct IqImbalance {
+    /// The amplitude of I relative to Q, in dB: 10·log₁₀(σ²<sub>I</sub>/σ²<sub>Q</sub>).
+    pub gain_db: f64,
+    /// How far the I and Q axes are from perpendicular, in degrees:
+    /// asin(cov(I, Q) / σ<sub>I</sub>σ<sub>Q</sub>).
+    pub phase_degrees: f64,
+}
+
+impl SigMF {
+    /// Stream the Dataset once, and return the statistics of each Captures
+    /// segment and of the whole.
+    ///
+    /// Every datatype is read, integer or float, real or complex, in either
+    /// byte order.
+    ///
+    /// # Errors
+    ///
+    /// [`MetadataError::MultiChannelDataset`], [`MetadataError::NoDataset`],
+    /// [`MetadataError::PartialSample`], or [`Error::Io`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sigmf::num_complex::Complex;
+    /// use sigmf::RecordingWriter;
+    /// # let dir = tempfile::tempdir().expect("a temporary directory");
+    ///
+    /// let samples = [Complex::new(32_767i16, 0), Complex::new(0, -16_384)];
+    /// let recording = RecordingWriter::new(&samples, 1e6).to_file(dir.path().join("adc"))?;
+    ///
+    /// let statistics = recording.statistics()?;
+    /// assert_eq!(statistics.overall.clipped, 1);
+    /// assert_eq!(statistics.overall.dc_offset.im, -0.25);
+    /// # Ok::<(), sigmf::Error>(())
+    /// ```
+    pub fn statistics(&self) -> Result<Statistics, Error> {
+        let global = &self.metadata.global;
+        if let Some(channels) = global.num_channels {
+            if channels != 1 {
+                return Err(MetadataError::MultiChannelDataset(channels).into());
+            }
+        }
+        let (mut file, segments) = self.open_dataset()?;
+        let source = self.dataset_file()?;
+
+        let data_type = global.datatype.data_type;
+        let complex = global.datatype.number_type == NumberType::Complex;
+        let width = data_type.size() as usize;
+        let sample_size = global.datatype.size() as usize;
+        let scale = full_scale(data_type);
+        let limits = limits(data_type);
+
+        let mut overall = Accumulator::default();
+        let mut captures = Vec::with_capacity(segments.len());
+        for segment in &segments {
+            let mut accumulator = Accumulator::default();
+            read_range(&mut file, source, segment.bytes.clone(), |bytes| {
+                for sample in bytes.chunks_exact(sample_size) {
+                    let mut parts = [0.0; 2];
+                    for (part, component) in parts.iter_mut().zip(sample.chunks_exact(width)) {
+                        let value = decode(data_type, component);
+                        match limits {
+                            Some((low, high)) if value == low || value == high => {
+                                accumulator.clipped += 1;
+                            }
+                            _ if value.is_nan() => accumulator.nan += 1,
+                            _ if value.is_infinite() => accumulator.infinite += 1,
+                            _ => {}
+                        }
+                        *part = value / scale;
+                    }
+                    accumulator.add(parts[0], parts[1]);
+                }
+                Ok(())
+            })?;
+            overall.merge(&accumulator);
+            captures.push(accumulator.finish(complex));
+        }
+
+        Ok(Statistics {
+            captures,
+            overall: overall.finish(complex),
+        })
+    }
+}
+
+/// The smallest and largest values of an integer datatype; `None` for a float.
+fn limits(data_type: DataType) -> Option<(f64, f64)> {
+    match data_type {
+        DataType::F32(_) | DataType::F64(_) => None,
+        DataType::I32(_) => Some((i32::MIN.into(), i32::MAX.into())),
+        DataType::I16(_) => Some((i16::MIN.into(), i16::MAX.into())),
+        DataType::I8 => Some((i8::MIN.into(), i8::MAX.into())),
+        DataType::U32(_) => Some((u32::MIN.into(), u32::MAX.into())),
+        DataType::U16(_) => Some((u16::MIN.into(), u16::MAX.into())),
+        DataType::U8 => Some((u8::MIN.into(), u8::MAX.into())),
+    }
+}
+
+/// Running sums, which add across segments.
+#[derive(Debug, Default)]
+struct Accumulator {
+    samples: u64,
+    finite: u64,
+    sum_i: f64,
+    sum_q: f64,
+    sum_ii: f64,
+    sum_qq: f64,
+    sum_iq: f64,
+    peak_power: f64,
+    clipped: u64,
+    nan: u64,
+    infinite: u64,
+}
+
+impl Accumulator {
+    fn add(&mut self, i: f64, q: f64) {
+        self.samples += 1;
+        if !(i.is_finite() && q.is_finite()) {
+            return;
+        }
+        self.finite += 1;
+        self.sum_i += i;
+        self.sum_q += q;
+        self.sum_ii += i * i;
+        self.sum_qq += q * q;
+        self.sum_iq += i * q;
+        self.peak_power = self.peak_power.max(i * i + q * q);
+    }
+
+    fn merge(&mut self, other: &Accumulator) {
+        self.samples += other.samples;
+        self.finite += other.finite;
+        self.sum_i += other.sum_i;
+        self.sum_q += other.sum_q;
+        self.sum_ii += other.sum_ii;
+        self.sum_qq += other.sum_qq;
+        self.sum_iq += other.sum_iq;
+        self.peak_power = self.peak_power.max(other.peak_power);
+        self.clipped += other.clipped;
+        self.nan += other.nan;
+        self.infinite += other.infinite;
+    }
+
+    fn finish(&self, complex: bool) -> SignalStatistics {
+        let n = self.finite.max(1) as f64;
+        let (mean_i, mean_q) = (self.sum_i / n, self.sum_q / n);
+        let mean_power = (self.sum_ii + self.sum_qq) / n;
+
+        let var_i = self.sum_ii / n - mean_i * mean_i;
+        let var_q = self.sum_qq / n - mean_q * mean_q;
+        let covariance = self.sum_iq / n - mean_i * mean_q;
+        let iq_imbalance =
+            (complex && self.finite >= 2 && var_i > 0.0 && var_q > 0.0).then(|| IqImbalance {
+                gain_db: 10.0 * (var_i / var_q).log10(),
+                phase_degrees: (covariance / (var_i * var_q).sqrt())
+                    .clamp(-1.0, 1.0)
+                    .asin()
+                    .to_degrees(),
+            });
+
+        SignalStatistics {
+            samples: self.samples,
+            mean_power,
+            rms: mean_power.sqrt(),
+            dc_offset: Complex::new(mean_i, mean_q),
+            iq_imbalance,
+            peak_magnitude: self.peak_power.sqrt(),
+            clipped: self.clipped,
+            nan: self.nan,
+            infinite: self.infinite,
+        }
+    }
+}
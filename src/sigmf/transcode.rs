@@ -0,0 +1,202 @@
+//! Rewriting a Recording's samples in another datatype or byte order.
+
+use std::path::Path;
+
+use super::dataset::{read_range, DatasetWriter, CHUNK_BYTES};
+use super::private::Sealed;
+use super::{DataType, Endianness, Error, MetadataError, Sample, SigMF};
+
+/// How a component's value is carried from one datatype to another.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scaling {
+    /// The same number on both sides: `1000i16` becomes `1000.0f32`, and
+    /// `0.25f32` becomes `0i16`.
+    ///
+    /// Right for moving between two integer types, or two float types, of the
+    /// same meaning — and almost never right for moving between an integer and
+    /// a float, where it leaves a float Dataset scaled in ADC counts.
+    Preserve,
+    /// Full scale to full scale: each component is divided by the full scale of
+    /// the source type and multiplied by that of the target.
+    ///
+    /// A float's full scale is 1.0; a signed integer's is 2<sup>bits−1</sup>, so
+    /// that `-32768i16` becomes `-1.0f32`; an unsigned integer's is
+    /// 2<sup>bits</sup>. That last is the honest reading of an unsigned
+    /// component as a magnitude, and it does *not* recentre an offset-binary
+    /// Dataset such as an RTL-SDR's `cu8` on zero — do that with
+    /// [`Factor`](Self::Factor) on a signed copy, or downstream.
+    FullScale,
+    /// Every component multiplied by this, before rounding and clipping.
+    Factor(f64),
+}
+
+/// What [`SigMF::transcode`] wrote, and what it could not represent.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Transcoded {
+    /// The new Recording, opened.
+    pub recording: SigMF,
+    /// How many components fell outside the range of an integer target type,
+    /// or were NaN, and so were clipped to its nearest value — or to 0, for NaN.
+    ///
+    /// Counted per component, so one complex sample can contribute two. Zero
+    /// for a float target, where an out-of-range value is still a value.
+    pub clipped: u64,
+}
+
+impl SigMF {
+    /// Write this Recording's samples as `To` in `endianness`, to
+    /// `basename.sigmf-data` and `basename.sigmf-meta`.
+    ///
+    /// Each component is decoded as the datatype it is stored as, scaled by
+    /// `scaling`, and — for an integer target — rounded to the nearest value
+    /// and clipped to the type's range. The Dataset is streamed, never loaded,
+    /// and `core:datatype` is derived from `To` exactly as
+    /// [`RecordingWriter::to_file`](super::RecordingWriter::to_file) derives it.
+    ///
+    /// Captures and Annotations are carried unchanged: they count samples, and
+    /// a sample is still a sample. `core:sha512` is computed over the new
+    /// Dataset; `core:dataset`, `core:trailing_bytes` and every
+    /// `core:header_bytes` are dropped, because the new Dataset is conforming
+    /// whatever the source was.
+    ///
+    /// # Errors
+    ///
+    /// [`MetadataError::NumberTypeMismatch`] if `To` is real and the Recording
+    /// complex or the other way about, [`MetadataError::NoDataset`],
+    /// [`MetadataError::PartialSample`], or [`Error::Io`] — including when
+    /// `basename` is this Recording's own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sigmf::num_complex::Complex;
+    /// use sigmf::{Endianness, RecordingWriter, Scaling};
+    /// # let dir = tempfile::tempdir().expect("a temporary directory");
+    ///
+    /// let vendor = RecordingWriter::new(&[Complex::new(16_384i16, -32_768)], 2.0e6)
+    ///     .endianness(Endianness::BigEndian)
+    ///     .to_file(dir.path().join("vendor"))?;
+    ///
+    /// let ours = vendor.transcode::<Complex<f32>, _>(
+    ///     dir.path().join("ours"),
+    ///     Scaling::FullScale,
+    ///     Endianness::LittleEndian,
+    /// )?;
+    /// assert_eq!(ours.recording.metadata.global.datatype.to_string(), "cf32_le");
+    /// assert_eq!(ours.recording.samples::<Complex<f32>>()?, [Complex::new(0.5, -1.0)]);
+    /// assert_eq!(ours.clipped, 0);
+    /// # Ok::<(), sigmf::Error>(())
+    /// ```
+    pub fn transcode<To: Sample, P: AsRef<Path>>(
+        &self,
+        basename: P,
+        scaling: Scaling,
+        endianness: Endianness,
+    ) -> Result<Transcoded, Error> {
+        let from = self.metadata.global.datatype;
+        let to = To::data_format(endianness);
+        if from.number_type != to.number_type {
+            return Err(MetadataError::NumberTypeMismatch { from, to }.into());
+        }
+        let factor = match scaling {
+            Scaling::Preserve => 1.0,
+            Scaling::FullScale => full_scale(to.data_type) / full_scale(from.data_type),
+            Scaling::Factor(factor) => factor,
+        };
+
+        let (mut file, segments) = self.open_dataset()?;
+        let source = self.dataset_file()?;
+
+        let mut metadata = self.metadata.clone();
+        metadata.global.datatype = to;
+        metadata.global.dataset = None;
+        metadata.global.trailing_bytes = None;
+        metadata.global.metadata_only = None;
+        for capture in &mut metadata.captures {
+            capture.header_bytes = None;
+        }
+
+        let mut writer = DatasetWriter::create(basename.as_ref(), &[source])?;
+        let (from_width, to_width) = (from.data_type.size(), to.data_type.size());
+        let mut out = Vec::with_capacity(CHUNK_BYTES / from_width as usize * to_width as usize);
+        let mut clipped = 0;
+        for segment in &segments {
+            read_range(&mut file, source, segment.bytes.clone(), |bytes| {
+                out.clear();
+                for component in bytes.chunks_exact(from_width as usize) {
+                    let value = decode(from.data_type, component) * factor;
+                    clipped += encode(to.data_type, value, &mut out);
+                }
+                writer.write(&out)
+            })?;
+        }
+
+        Ok(Transcoded {
+            recording: writer.finish(metadata)?,
+            clipped,
+        })
+    }
+}
+
+/// The magnitude [`Scaling::FullScale`] maps to 1.0.
+pub(super) fn full_scale(data_type: DataType) -> f64 {
+    match data_type {
+        DataType::F32(_) | DataType::F64(_) => 1.0,
+        DataType::I32(_) => 2f64.powi(31),
+        DataType::I16(_) => 2f64.powi(15),
+        DataType::I8 => 2f64.powi(7),
+        DataType::U32(_) => 2f64.powi(32),
+        DataType::U16(_) => 2f64.powi(16),
+        DataType::U8 => 2f64.powi(8),
+    }
+}
+
+/// One component, stored as `data_type`, as the number it holds.
+///
+/// Every component type converts to `f64` exactly, so nothing is lost before
+/// the scaling is applied.
+pub(super) fn decode(data_type: DataType, bytes: &[u8]) -> f64 {
+    match data_type {
+        DataType::F32(e) => f64::from(f32::decode(e, bytes)),
+        DataType::F64(e) => f64::decode(e, bytes),
+        DataType::I32(e) => f64::from(i32::decode(e, bytes)),
+        DataType::I16(e) => f64::from(i16::decode(e, bytes)),
+        DataType::U32(e) => f64::from(u32::decode(e, bytes)),
+        DataType::U16(e) => f64::from(u16::decode(e, bytes)),
+        DataType::I8 => f64::from(i8::decode(Endianness::LittleEndian, bytes)),
+        DataType::U8 => f64::from(u8::decode(Endianness::LittleEndian, bytes)),
+    }
+}
+
+/// Append `value` to `out` as one component of `data_type`, returning 1 if it
+/// had to be clipped to fit and 0 if not.
+fn encode(data_type: DataType, value: f64, out: &mut Vec<u8>) -> u64 {
+    /// Round to the nearest integer of `$t` — which `as` does, saturating, and
+    /// taking NaN to 0 — and report whether that changed the rounded value.
+    macro_rules! integer {
+        ($t:ty, $endianness:expr) => {{
+            let rounded = value.round();
+            let narrowed = rounded as $t;
+            narrowed.encode($endianness, out);
+            u64::from(f64::from(narrowed) != rounded)
+        }};
+    }
+
+    match data_type {
+        DataType::F32(e) => {
+            (value as f32).encode(e, out);
+            0
+        }
+        DataType::F64(e) => {
+            value.encode(e, out);
+            0
+        }
+        DataType::I32(e) => integer!(i32, e),
+        DataType::I16(e) => integer!(i16, e),
+        DataType::U32(e) => integer!(u32, e),
+        DataType::U16(e) => integer!(u16, e),
+        DataType::I8 => integer!(i8, Endianness::LittleEndian),
+        DataType::U8 => integer!(u8, Endianness::LittleEndian),
+    }
+}
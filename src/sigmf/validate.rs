@@ -0,0 +1,474 @@
+//! Checking a Recording against the rules of the specification that its types
+//! cannot enforce on their own.
+//!
+//! Parsing already refuses most of what the schema forbids: a `core:datatype`
+//! that is not one, a negative `core:sample_start`, an Annotation missing its
+//! start. What survives parsing is the rules that relate one value to another
+//! — ordering, pairs of fields, a Dataset that must match its description —
+//! and the values whose type is wider than the schema's range. Those are what
+//! this module reports, as [`Finding`]s rather than errors, because a document
+//! that breaks one of them is still a document, and a caller deciding what to
+//! do with it wants every problem at once rather than the first.
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde_json::Value;
+
+use super::datetime::Timestamp;
+use super::{Map, Metadata, MetadataError, SigMF, SIGMF_VERSION};
+
+/// The largest value the schema permits for any sample index or byte count.
+const MAX_INDEX: u64 = i64::MAX as u64;
+
+/// The bound the schema puts on every frequency, in either direction.
+const MAX_FREQUENCY: f64 = 1e12;
+
+/// How much a [`Finding`] matters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// A rule the specification states with MUST is broken: a conforming
+    /// reader may refuse this Recording, or read it wrongly.
+    Error,
+    /// A SHOULD is broken, or something is legal and very likely a mistake.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        })
+    }
+}
+
+/// One problem [`Metadata::validate`] or [`SigMF::validate`] found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// Whether a reader may refuse the Recording over it.
+    pub severity: Severity,
+    /// Where in the document the problem is, as a path of keys and array
+    /// indices such as `captures/2/core:datetime` — or `dataset` for a problem
+    /// with the Dataset file itself.
+    pub location: String,
+    /// What is wrong, in a sentence.
+    pub message: String,
+}
+
+impl Finding {
+    fn error(location: impl Into<String>, message: impl Into<String>) -> Finding {
+        Finding {
+            severity: Severity::Error,
+            location: location.into(),
+            message: message.into(),
+        }
+    }
+
+    fn warning(location: impl Into<String>, message: impl Into<String>) -> Finding {
+        Finding {
+            severity: Severity::Warning,
+            location: location.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// `error: captures/2/core:datetime: ...`, one line, for a terminal.
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.location, self.message)
+    }
+}
+
+impl Metadata {
+    /// Every rule of the specification this document breaks, that can be
+    /// judged from the document alone.
+    ///
+    /// Empty for a conforming document. Errors come before warnings, and
+    /// within each, findings are in document order. See [`SigMF::validate`]
+    /// for the checks that need the Dataset too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use sigmf::{Metadata, Severity};
+    ///
+    /// let metadata = Metadata::from_json(
+    ///     r#"{
+    ///         "global": {"core:datatype": "cf32_le", "core:version": "1.2.6"},
+    ///         "captures": [],
+    ///         "annotations": [
+    ///             {"core:sample_start": 0, "core:freq_lower_edge": 16804000}
+    ///         ]
+    ///     }"#,
+    /// )?;
+    /// let findings = metadata.validate();
+    /// assert_eq!(findings[0].severity, Severity::Error);
+    /// assert_eq!(findings[0].location, "annotations/0");
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn validate(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        self.validate_global(&mut findings);
+        self.validate_captures(&mut findings);
+        self.validate_annotations(&mut findings);
+        self.validate_extensions(&mut findings);
+        findings.sort_by_key(|finding| finding.severity);
+        findings
+    }
+
+    fn validate_global(&self, findings: &mut Vec<Finding>) {
+        let global = &self.global;
+
+        if !is_version(&global.version) {
+            findings.push(Finding::error(
+                "global/core:version",
+                format!("{:?} is not a version of the form X.Y.Z", global.version),
+            ));
+        } else if newer_than_supported(&global.version) {
+            findings.push(Finding::warning(
+                "global/core:version",
+                format!(
+                    "written to SigMF {}, newer than the {SIGMF_VERSION} this was checked against",
+                    global.version
+                ),
+            ));
+        }
+
+        match global.sample_rate {
+            None => findings.push(Finding::warning(
+                "global/core:sample_rate",
+                "absent, so no sample index can be converted to a time",
+            )),
+            Some(rate) if !(rate > 0.0 && rate <= 1e12) => findings.push(Finding::error(
+                "global/core:sample_rate",
+                format!("{rate} is outside the permitted range, above 0 and at most 10^12"),
+            )),
+            Some(_) => {}
+        }
+
+        if global.num_channels == Some(0) {
+            findings.push(Finding::error(
+                "global/core:num_channels",
+                "0, where a Dataset has at least one channel",
+            ));
+        }
+        if let Some(sha512) = &global.sha512 {
+            if sha512.len() != 128 || !sha512.bytes().all(|b| b.is_ascii_hexdigit()) {
+                findings.push(Finding::error(
+                    "global/core:sha512",
+                    "not a SHA-512, which is 128 hexadecimal digits",
+                ));
+            }
+        }
+        check_index(findings, "global/core:offset", global.offset);
+        check_index(
+            findings,
+            "global/core:trailing_bytes",
+            global.trailing_bytes,
+        );
+
+        if let Some(dataset) = &global.dataset {
+            if dataset.is_empty()
+                || dataset.contains(['/', '\\', ':', '*', '?', '"', '<', '>', '|'])
+            {
+                findings.push(Finding::error(
+                    "global/core:dataset",
+                    format!("{dataset:?} is not a bare filename"),
+                ));
+            }
+            if global.metadata_only == Some(true) {
+                findings.push(Finding::warning(
+                    "global/core:metadata_only",
+                    "set alongside `core:dataset`, which it should not be; readers ignore it \
+                     if the Dataset exists",
+                ));
+            }
+        }
+    }
+
+    fn validate_captures(&self, findings: &mut Vec<Finding>) {
+        let offset = self.global.offset.unwrap_or(0);
+        let mut previous = None;
+        for (index, capture) in self.captures.iter().enumerate() {
+            let location = |key: &str| format!("captures/{index}/{key}");
+
+            check_index(
+                findings,
+                location("core:sample_start"),
+                Some(capture.sample_start),
+            );
+            check_index(
+                findings,
+                location("core:global_index"),
+                capture.global_index,
+            );
+            check_index(
+                findings,
+                location("core:header_bytes"),
+                capture.header_bytes,
+            );
+            check_frequency(findings, location("core:frequency"), capture.frequency);
+            check_after_offset(
+                findings,
+                location("core:sample_start"),
+                capture.sample_start,
+                offset,
+            );
+
+            if let Some(previous) = previous {
+                if capture.sample_start < previous {
+                    findings.push(Finding::error(
+                        location("core:sample_start"),
+                        format!(
+                            "{} follows {previous}, but captures must be sorted by \
+                             `core:sample_start`",
+                            capture.sample_start
+                        ),
+                    ));
+                } else if capture.sample_start == previous {
+                    findings.push(Finding::warning(
+                        location("core:sample_start"),
+                        "shared with the previous segment, which therefore holds no samples",
+                    ));
+                }
+            }
+            previous = Some(capture.sample_start);
+
+            if let Some(datetime) = &capture.datetime {
+                if Timestamp::parse(datetime).is_none() {
+                    findings.push(Finding::error(
+                        location("core:datetime"),
+                        format!("{datetime:?} is not an RFC 3339 timestamp with the offset `Z`"),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn validate_annotations(&self, findings: &mut Vec<Finding>) {
+        let offset = self.global.offset.unwrap_or(0);
+        let mut previous = None;
+        for (index, annotation) in self.annotations.iter().enumerate() {
+            let location = |key: &str| format!("annotations/{index}/{key}");
+
+            check_index(
+                findings,
+                location("core:sample_start"),
+                Some(annotation.sample_start),
+            );
+            check_index(
+                findings,
+                location("core:sample_count"),
+                annotation.sample_count,
+            );
+            check_frequency(
+                findings,
+                location("core:freq_lower_edge"),
+                annotation.freq_lower_edge,
+            );
+            check_frequency(
+                findings,
+                location("core:freq_upper_edge"),
+                annotation.freq_upper_edge,
+            );
+            check_after_offset(
+                findings,
+                location("core:sample_start"),
+                annotation.sample_start,
+                offset,
+            );
+
+            if previous.is_some_and(|previous| annotation.sample_start < previous) {
+                findings.push(Finding::error(
+                    location("core:sample_start"),
+                    "out of order, but annotations must be sorted by `core:sample_start`",
+                ));
+            }
+            previous = Some(annotation.sample_start);
+
+            match (annotation.freq_lower_edge, annotation.freq_upper_edge) {
+                (Some(lower), Some(upper)) if lower > upper => findings.push(Finding::error(
+                    format!("annotations/{index}"),
+                    format!("the band's lower edge {lower} Hz is above its upper edge {upper} Hz"),
+                )),
+                (Some(_), None) | (None, Some(_)) => findings.push(Finding::error(
+                    format!("annotations/{index}"),
+                    "only one of `core:freq_lower_edge` and `core:freq_upper_edge` is given, \
+                     and the specification requires both or neither",
+                )),
+                _ => {}
+            }
+        }
+    }
+
+    fn validate_extensions(&self, findings: &mut Vec<Finding>) {
+        let declared = self.global.extensions.as_deref().unwrap_or_default();
+
+        let mut seen = BTreeSet::new();
+        for (index, extension) in declared.iter().enumerate() {
+            if !seen.insert(extension.name.as_str()) {
+                findings.push(Finding::warning(
+                    format!("global/core:extensions/{index}"),
+                    format!("`{}` is declared more than once", extension.name),
+                ));
+            }
+        }
+
+        let mut undeclared = BTreeSet::new();
+        let mut scan = |location: String, other: &Map<String, Value>| {
+            for key in other.keys() {
+                if let Some((namespace, _)) = key.split_once(':') {
+                    if namespace != "core"
+                        && !seen.contains(namespace)
+                        && undeclared.insert(namespace.to_string())
+                    {
+                        findings.push(Finding::warning(
+                            format!("{location}/{key}"),
+                            format!(
+                                "the `{namespace}` namespace is used but not declared in \
+                                 `core:extensions`"
+                            ),
+                        ));
+                    }
+                }
+            }
+        };
+        scan("global".to_string(), &self.global.other);
+        for (index, capture) in self.captures.iter().enumerate() {
+            scan(format!("captures/{index}"), &capture.other);
+        }
+        for (index, annotation) in self.annotations.iter().enumerate() {
+            scan(format!("annotations/{index}"), &annotation.other);
+        }
+    }
+}
+
+impl SigMF {
+    /// Every rule of the specification this Recording breaks: those of
+    /// [`Metadata::validate`], and those that relate the document to its
+    /// Dataset.
+    ///
+    /// The Dataset is measured, not read — its length is what the Captures
+    /// array is checked against — so this is cheap whatever its size. Checking
+    /// its contents against `core:sha512` is a different question, and
+    /// [`verify_sha512`](Self::verify_sha512)'s to answer.
+    ///
+    /// A Dataset that is missing or cannot be opened is a finding rather than
+    /// an error, located at `dataset`: it is exactly what a caller validating a
+    /// Recording wants to hear about, alongside everything else.
+    pub fn validate(&self) -> Vec<Finding> {
+        let mut findings = self.metadata.validate();
+        if self.metadata.global.metadata_only == Some(true)
+            && self.metadata.global.dataset.is_none()
+        {
+            return findings;
+        }
+
+        let dataset_len = match self.datafile.as_ref().map(std::fs::metadata) {
+            None => {
+                findings.push(Finding::error(
+                    "dataset",
+                    MetadataError::NoDataset.to_string(),
+                ));
+                None
+            }
+            Some(Err(e)) => {
+                findings.push(Finding::error("dataset", e.to_string()));
+                None
+            }
+            Some(Ok(file)) => Some(file.len()),
+        };
+
+        if let Some(dataset_len) = dataset_len {
+            match self.metadata.sample_count(dataset_len) {
+                Err(e) => findings.push(Finding::error("dataset", e.to_string())),
+                Ok(sample_count) => {
+                    // Annotations count from `core:offset`, the Dataset from 0.
+                    let end = self
+                        .metadata
+                        .global
+                        .offset
+                        .unwrap_or(0)
+                        .saturating_add(sample_count);
+                    for (index, annotation) in self.metadata.annotations.iter().enumerate() {
+                        if annotation.sample_start > end {
+                            findings.push(Finding::warning(
+                                format!("annotations/{index}/core:sample_start"),
+                                format!(
+                                    "{} is past the end of the Dataset, which holds \
+                                     {sample_count} samples",
+                                    annotation.sample_start
+                                ),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        findings.sort_by_key(|finding| finding.severity);
+        findings
+    }
+}
+
+/// Whether `version` is `X.Y.Z`, every part decimal digits.
+fn is_version(version: &str) -> bool {
+    let parts: Vec<&str> = version.split('.').collect();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Whether `version` is a later major or minor than [`SIGMF_VERSION`].
+///
+/// A later patch changes no rule this crate checks.
+fn newer_than_supported(version: &str) -> bool {
+    let major_minor = |v: &str| -> Option<(u64, u64)> {
+        let mut parts = v.split('.').map(str::parse::<u64>);
+        Some((parts.next()?.ok()?, parts.next()?.ok()?))
+    };
+    match (major_minor(version), major_minor(SIGMF_VERSION)) {
+        (Some(theirs), Some(ours)) => theirs > ours,
+        _ => false,
+    }
+}
+
+fn check_index(findings: &mut Vec<Finding>, location: impl Into<String>, value: Option<u64>) {
+    if let Some(value) = value.filter(|&value| value > MAX_INDEX) {
+        findings.push(Finding::error(
+            location,
+            format!("{value} is past the largest index the schema permits, 2^63 - 1"),
+        ));
+    }
+}
+
+/// Sample indices are absolute, and the specification says every one in a
+/// document should be at or above `core:offset`, the Dataset's first.
+fn check_after_offset(
+    findings: &mut Vec<Finding>,
+    location: impl Into<String>,
+    sample_start: u64,
+    offset: u64,
+) {
+    if sample_start < offset {
+        findings.push(Finding::warning(
+            location,
+            format!(
+                "{sample_start} is before `core:offset` {offset}, the index of the Dataset's \
+                 first sample"
+            ),
+        ));
+    }
+}
+
+fn check_frequency(findings: &mut Vec<Finding>, location: impl Into<String>, value: Option<f64>) {
+    if let Some(value) = value.filter(|value| value.is_nan() || value.abs() > MAX_FREQUENCY) {
+        findings.push(Finding::error(
+            location,
+            format!("{value} Hz is outside the permitted range of ±10^12 Hz"),
+        ));
+    }
+}
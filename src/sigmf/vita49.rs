@@ -0,0 +1,380 @@
+//! VITA 49 (VRT) packet streams: the IF Data and Context packets a digitizer
+//! emits, recorded to a file.
+//!
+//! A VRT stream describes itself as it goes. IF Data packets carry the
+//! samples, each with a header, optional stream identifier, class identifier
+//! and timestamp in front, and an optional trailer behind; Context packets
+//! carry what the samples mean — the RF frequency they were tuned to, the rate
+//! they were taken at — and are sent again whenever that changes. [`import`]
+//! keeps the samples, drops everything around them, and turns every change
+//! the Context packets announce into a Captures segment of its own.
+//!
+//! Only bare packets are read, one after another, as a digitizer writes them
+//! to disk. VITA 49.1 link-layer framing is not, and neither are Extension
+//! packets, whose payloads are not samples.
+
+use std::fs;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use super::dataset::DatasetWriter;
+use super::datetime::Timestamp;
+use super::{
+    at, invalid, CaptureMetadata, DataFormat, Error, GlobalMetadata, Metadata, MetadataError, SigMF,
+};
+
+/// Packet types, from the top four bits of the header.
+const IF_DATA: u32 = 0b0000;
+const IF_DATA_WITH_STREAM_ID: u32 = 0b0001;
+const IF_CONTEXT: u32 = 0b0100;
+
+/// Integer timestamp type 1: seconds since the Unix epoch.
+const TSI_UTC: u32 = 1;
+/// Fractional timestamp type 1: samples since the integer second.
+const TSF_SAMPLE_COUNT: u32 = 1;
+/// Fractional timestamp type 2: picoseconds since the integer second.
+const TSF_REAL_TIME: u32 = 2;
+
+/// Context Indicator Field bits this reads, and the length in words of every
+/// field before the last of them, which must be stepped over to reach it.
+const CIF_RF_FREQUENCY: u32 = 27;
+const CIF_SAMPLE_RATE: u32 = 21;
+const CIF_FIELD_WORDS: [(u32, usize); 10] = [
+    (30, 1), // reference point identifier
+    (29, 2), // bandwidth
+    (28, 2), // IF reference frequency
+    (27, 2), // RF reference frequency
+    (26, 2), // RF reference frequency offset
+    (25, 2), // IF band offset
+    (24, 1), // reference level
+    (23, 1), // gain
+    (22, 1), // over-range count
+    (21, 2), // sample rate
+];
+
+/// Bits 7 to 1 of a VITA 49.2 CIF0, each announcing a further CIF word that
+/// precedes the fields.
+const CIF_EXTENSIONS: [u32; 4] = [7, 3, 2, 1];
+
+/// Write the samples of the IF Data packets in `packets` as a new Recording,
+/// `basename.sigmf-data` and `basename.sigmf-meta`, and return it opened.
+///
+/// The payloads are stated to be `datatype`, because a data packet does not
+/// say what its payload is: the Data Packet Payload Format field that could is
+/// optional, and seldom sent. VRT is big-endian throughout, so a digitizer's
+/// 16-bit I/Q is `ci16_be`.
+///
+/// A file may interleave several streams. The one imported is `stream_id`,
+/// or, if that is `None`, the first data packet's; data packets of any other
+/// stream are skipped, as are Context packets whose stream identifier is not
+/// the imported stream's. Context packets that come before the first data
+/// packet are held until it says which stream that is. If the data packets
+/// carry no stream identifier, every Context packet applies to them.
+///
+/// # What the document says
+///
+/// - **`core:sample_rate`**, from the first Context packet that states one.
+/// - **A Captures segment** at the first sample, and at the first sample of
+///   every data packet after a Context packet changes the RF reference
+///   frequency, or after a data packet's timestamp is not where the samples
+///   before it put it — which is how a dropped packet shows. Each has
+///   `core:frequency` from the RF reference frequency, and `core:datetime`
+///   from its first packet's timestamp, if that is UTC. A Context packet
+///   applies from the next data packet on, whatever its own timestamp says.
+/// - **`core:sha512`**, computed over the Dataset written.
+///
+/// # Errors
+///
+/// [`Error::Io`] naming `packets` if it cannot be read or is not a sequence
+/// of VRT packets, or if a Context packet changes the sample rate once samples
+/// have been taken at another — which one Recording cannot express;
+/// [`MetadataError::PartialSample`] if a payload is not a whole number of
+/// samples of `datatype`; or [`Error::Io`] for a file that cannot be written.
+///
+/// # Examples
+///
+/// ```no_run
+/// use sigmf::vita49;
+///
+/// let recording = vita49::import("digitizer.vrt", "watch", "ci16_be".parse()?, None)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn import<P: AsRef<Path>, Q: AsRef<Path>>(
+    packets: P,
+    basename: Q,
+    datatype: DataFormat,
+    stream_id: Option<u32>,
+) -> Result<SigMF, Error> {
+    let path = packets.as_ref();
+    let mut input = BufReader::new(fs::File::open(path).map_err(at(path))?);
+    let mut writer = DatasetWriter::create(basename.as_ref(), &[path])?;
+
+    // The stream imported: `Some(None)` once data packets turn out to carry
+    // no stream identifier, which leaves nothing to match Context packets
+    // against, so all of them apply.
+    let mut stream = stream_id.map(Some);
+    // Context packets that came before the first data packet said which
+    // stream is imported, in order.
+    let mut held: Vec<Packet> = Vec::new();
+    let mut tuning = Tuning {
+        sample_rate: None,
+        frequency: None,
+        changed: true,
+    };
+    // Where the next data packet's timestamp should be, if it has one.
+    let mut expected: Option<Timestamp> = None;
+    let mut samples = 0u64;
+    let mut captures: Vec<CaptureMetadata> = Vec::new();
+
+    while let Some(packet) = read_packet(&mut input, path)? {
+        match packet.packet_type {
+            IF_DATA | IF_DATA_WITH_STREAM_ID => {
+                let chosen = *stream.get_or_insert(packet.stream_id);
+                for context in held.drain(..) {
+                    if chosen.is_none_or(|chosen| context.stream_id == Some(chosen)) {
+                        tuning.apply(context.context(path)?, samples, path)?;
+                    }
+                }
+                if packet.stream_id.unwrap_or(0) != chosen.unwrap_or(0) {
+                    continue;
+                }
+                let payload = packet.payload();
+                if !(payload.len() as u64).is_multiple_of(datatype.size()) {
+                    return Err(MetadataError::PartialSample {
+                        bytes: payload.len() as u64,
+                        datatype,
+                    }
+                    .into());
+                }
+
+                let sample_rate = tuning.sample_rate;
+                let time = packet.time(sample_rate);
+                // Off by more than half a sample, so that rounding in either
+                // timestamp is not mistaken for a lost packet.
+                let gap = match (time, expected, sample_rate) {
+                    (Some(time), Some(expected), Some(rate)) => {
+                        time.seconds_since(expected).abs() > 0.5 / rate
+                    }
+                    _ => false,
+                };
+                if tuning.changed || gap {
+                    let mut capture = CaptureMetadata::new(samples);
+                    capture.frequency = tuning.frequency;
+                    capture.datetime = time.map(|time| time.to_string());
+                    // Two changes before any samples describe one segment.
+                    if captures.last().is_some_and(|c| c.sample_start == samples) {
+                        captures.pop();
+                    }
+                    captures.push(capture);
+                    tuning.changed = false;
+                }
+
+                let count = payload.len() as u64 / datatype.size();
+                writer.write(payload)?;
+                samples += count;
+                expected = time
+                    .zip(sample_rate)
+                    .map(|(time, rate)| time.add_seconds(count as f64 / rate));
+            }
+            IF_CONTEXT => match stream {
+                None => held.push(packet),
+                Some(Some(chosen)) if packet.stream_id != Some(chosen) => {}
+                Some(_) => tuning.apply(packet.context(path)?, samples, path)?,
+            },
+            _ => {}
+        }
+    }
+
+    let mut global = GlobalMetadata::describing(datatype);
+    global.sample_rate = tuning.sample_rate;
+    writer.finish(Metadata {
+        global,
+        captures,
+        annotations: vec![],
+    })
+}
+
+/// What the Context packets applied so far say about the samples.
+struct Tuning {
+    sample_rate: Option<f64>,
+    frequency: Option<f64>,
+    /// Whether a Context packet has changed something since the last segment
+    /// began.
+    changed: bool,
+}
+
+impl Tuning {
+    /// Take in `context`, with `samples` samples already written.
+    fn apply(&mut self, context: Context, samples: u64, path: &Path) -> Result<(), Error> {
+        if let Some(rate) = context.sample_rate {
+            match self.sample_rate {
+                Some(current) if current != rate && samples > 0 => {
+                    return Err(invalid(
+                        path,
+                        "a Context packet changes the sample rate partway through the \
+                         stream, which one Recording cannot have",
+                    ))
+                }
+                _ => self.sample_rate = Some(rate),
+            }
+        }
+        if let Some(new) = context.frequency {
+            if self.frequency != Some(new) {
+                self.frequency = Some(new);
+                self.changed = true;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One VRT packet, its header decoded and the rest held as words.
+struct Packet {
+    packet_type: u32,
+    stream_id: Option<u32>,
+    tsi: u32,
+    tsf: u32,
+    integer_seconds: Option<u32>,
+    fractional: Option<u64>,
+    /// Every byte after the prologue.
+    body: Vec<u8>,
+    /// Whether the last word of `body` is a trailer.
+    trailer: bool,
+}
+
+/// Read the packet at the reader's position, or `None` at the end of the
+/// file.
+fn read_packet(input: &mut impl Read, path: &Path) -> Result<Option<Packet>, Error> {
+    let mut word = [0u8; 4];
+    match input.read(&mut word[..1]).map_err(at(path))? {
+        0 => return Ok(None),
+        _ => input
+            .read_exact(&mut word[1..])
+            .map_err(|_| invalid(path, "a VRT packet cut short"))?,
+    }
+    let header = u32::from_be_bytes(word);
+    let size = (header & 0xFFFF) as usize;
+    if size == 0 {
+        return Err(invalid(path, "a VRT packet of no words"));
+    }
+    let mut rest = vec![0u8; (size - 1) * 4];
+    input
+        .read_exact(&mut rest)
+        .map_err(|_| invalid(path, "a VRT packet cut short"))?;
+
+    let packet_type = header >> 28;
+    let has_stream_id = packet_type & 1 == 1 || packet_type == IF_CONTEXT;
+    let class_id = header >> 27 & 1 == 1;
+    let tsi = header >> 22 & 0b11;
+    let tsf = header >> 20 & 0b11;
+    let trailer = packet_type <= 0b0011 && header >> 26 & 1 == 1;
+
+    let mut words = rest
+        .chunks_exact(4)
+        .map(|w| u32::from_be_bytes([w[0], w[1], w[2], w[3]]));
+    let mut prologue = 0;
+    let mut next = || {
+        prologue += 1;
+        words
+            .next()
+            .ok_or_else(|| invalid(path, "a VRT packet shorter than its own header"))
+    };
+    let stream_id = if has_stream_id { Some(next()?) } else { None };
+    if class_id {
+        next()?;
+        next()?;
+    }
+    let integer_seconds = if tsi != 0 { Some(next()?) } else { None };
+    let fractional = if tsf != 0 {
+        Some(u64::from(next()?) << 32 | u64::from(next()?))
+    } else {
+        None
+    };
+    if trailer && prologue == size - 1 {
+        return Err(invalid(path, "a VRT packet shorter than its own header"));
+    }
+
+    rest.drain(..prologue * 4);
+    Ok(Some(Packet {
+        packet_type,
+        stream_id,
+        tsi,
+        tsf,
+        integer_seconds,
+        fractional,
+        body: rest,
+        trailer,
+    }))
+}
+
+/// What a Context packet says that a Recording has a field for.
+struct Context {
+    frequency: Option<f64>,
+    sample_rate: Option<f64>,
+}
+
+impl Packet {
+    /// The samples of a data packet.
+    fn payload(&self) -> &[u8] {
+        let end = self.body.len() - if self.trailer { 4 } else { 0 };
+        &self.body[..end]
+    }
+
+    /// The instant of the packet's first sample, for a UTC timestamp whose
+    /// fraction is in picoseconds, or in samples at a known rate.
+    fn time(&self, sample_rate: Option<f64>) -> Option<Timestamp> {
+        if self.tsi != TSI_UTC {
+            return None;
+        }
+        let seconds = Timestamp::from_unix(i64::from(self.integer_seconds?));
+        match (self.tsf, self.fractional) {
+            (0, _) => Some(seconds),
+            (TSF_REAL_TIME, Some(picoseconds)) => {
+                Some(seconds.add_seconds(picoseconds as f64 / 1e12))
+            }
+            (TSF_SAMPLE_COUNT, Some(count)) => {
+                Some(seconds.add_seconds(count as f64 / sample_rate?))
+            }
+            _ => None,
+        }
+    }
+
+    /// The RF reference frequency and sample rate fields of a Context packet,
+    /// where its CIF0 says they are present.
+    fn context(&self, path: &Path) -> Result<Context, Error> {
+        let malformed = || invalid(path, "a Context packet shorter than its fields");
+        let words: Vec<u32> = self
+            .body
+            .chunks_exact(4)
+            .map(|w| u32::from_be_bytes([w[0], w[1], w[2], w[3]]))
+            .collect();
+        let cif0 = *words.first().ok_or_else(malformed)?;
+        let mut at = 1 + CIF_EXTENSIONS
+            .iter()
+            .filter(|&&bit| cif0 >> bit & 1 == 1)
+            .count();
+
+        let mut context = Context {
+            frequency: None,
+            sample_rate: None,
+        };
+        for (bit, len) in CIF_FIELD_WORDS {
+            if cif0 >> bit & 1 == 0 {
+                continue;
+            }
+            let field = words.get(at..at + len).ok_or_else(malformed)?;
+            if bit == CIF_RF_FREQUENCY || bit == CIF_SAMPLE_RATE {
+                // 64-bit two's complement, with the radix point 20 bits up.
+                let raw = (u64::from(field[0]) << 32 | u64::from(field[1])) as i64;
+                let hertz = raw as f64 / f64::from(1 << 20);
+                if bit == CIF_RF_FREQUENCY {
+                    context.frequency = Some(hertz);
+                } else {
+                    context.sample_rate = Some(hertz);
+                }
+            }
+            at += len;
+        }
+        Ok(context)
+    }
+}
@@ -0,0 +1,481 @@
+//! WAV files: the container SDR# and HDSDR record baseband into, and the one
+//! audio tools read.
+//!
+//! A two-channel WAV file recorded by an SDR is I on the left and Q on the
+//! right, interleaved frame by frame — which is exactly the layout of a `c`
+//! datatype, so a WAV file's sample data is a Dataset already, once the chunks
+//! around it are set aside. [`import`] and [`export`] move between the two, and
+//! a one-channel file is a real Recording.
+//!
+//! Both directions stream, and neither decodes a sample to a number: every
+//! conversion here is a rearrangement of bytes, so nothing is rounded.
+//!
+//! # What survives
+//!
+//! The sample rate, from the `fmt ` chunk. The `auxi` chunk SDR# and HDSDR
+//! write carries the centre frequency and the time recording started, and
+//! those become the first Captures segment's `core:frequency` and
+//! `core:datetime` on import, and are written back on export. The chunk
+//! records no time zone; its start time is read and written as UTC, and a
+//! file recorded on local time needs its `core:datetime` corrected by hand.
+//! Everything else a WAV file carries is left behind, as is everything but the
+//! samples, the rate and those two fields of a Recording.
+//!
+//! Only RIFF WAV is read and written, not RF64, which caps a file at 4 GiB.
+
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use super::dataset::{read_range, DatasetWriter, CHUNK_BYTES};
+use super::datetime::{Civil, Timestamp};
+use super::{
+    at, finish_file, invalid, CaptureMetadata, DataFormat, DataType, Endianness, Error,
+    GlobalMetadata, Metadata, MetadataError, NumberType, SigMF,
+};
+
+/// `WAVE_FORMAT_PCM`: integer samples.
+const PCM: u16 = 1;
+/// `WAVE_FORMAT_IEEE_FLOAT`: floating-point samples.
+const IEEE_FLOAT: u16 = 3;
+/// `WAVE_FORMAT_EXTENSIBLE`, whose real format tag is the first two bytes of
+/// its sub-format GUID.
+const EXTENSIBLE: u16 = 0xFFFE;
+
+/// The length of the `auxi` chunk this module writes: two `SYSTEMTIME`s, then
+/// nine 32-bit fields of which the first two — centre frequency and sample
+/// rate — are the ones readers use.
+const AUXI_LEN: usize = 2 * 16 + 9 * 4;
+
+/// Write the samples of the WAV file `wav` as a new Recording,
+/// `basename.sigmf-data` and `basename.sigmf-meta`, and return it opened.
+///
+/// | WAV samples | `core:datatype` |
+/// |---|---|
+/// | 8-bit integer | `ru8` / `cu8` |
+/// | 16-bit integer | `ri16_le` / `ci16_le` |
+/// | 24-bit integer | `ri32_le` / `ci32_le`, each widened to the top of 32 bits |
+/// | 32-bit integer | `ri32_le` / `ci32_le` |
+/// | 32-bit float | `rf32_le` / `cf32_le` |
+/// | 64-bit float | `rf64_le` / `cf64_le` |
+///
+/// SigMF has no 24-bit type, so those samples are shifted into the high bytes
+/// of an `i32`, which keeps full scale at full scale. Every other format's
+/// bytes are copied as they are.
+///
+/// # Errors
+///
+/// [`Error::Io`] naming `wav` if it is not a WAV file this can read — neither
+/// one nor two channels, or a format in none of the rows above — or cannot be
+/// read at all, [`MetadataError::PartialSample`] if its sample data ends
+/// partway through a frame, [`MetadataError::SampleRateOutOfRange`], or
+/// [`Error::Io`] for a file that cannot be written.
+///
+/// # Examples
+///
+/// ```
+/// use sigmf::num_complex::Complex;
+/// use sigmf::{wav, RecordingWriter};
+/// # let dir = tempfile::tempdir().expect("a temporary directory");
+///
+/// let samples = [Complex::new(1i16, -1), Complex::new(2, -2)];
+/// let mut writer = RecordingWriter::new(&samples, 48_000.0);
+/// let mut capture = sigmf::CaptureMetadata::new(0);
+/// capture.frequency = Some(7_074_000.0);
+/// writer.captures_mut().push(capture);
+/// let recording = writer.to_file(dir.path().join("ft8"))?;
+///
+/// wav::export(&recording, dir.path().join("ft8.wav"))?;
+/// let back = wav::import(dir.path().join("ft8.wav"), dir.path().join("again"))?;
+/// assert_eq!(back.samples::<Complex<i16>>()?, samples);
+/// assert_eq!(back.metadata.captures[0].frequency, Some(7_074_000.0));
+/// # Ok::<(), sigmf::Error>(())
+/// ```
+pub fn import<P: AsRef<Path>, Q: AsRef<Path>>(wav: P, basename: Q) -> Result<SigMF, Error> {
+    let wav = wav.as_ref();
+    let mut file = fs::File::open(wav).map_err(at(wav))?;
+    let layout = read_layout(&mut file, wav)?;
+
+    let (data_type, widen) = match (layout.format, layout.bits) {
+        (PCM, 8) => (DataType::U8, false),
+        (PCM, 16) => (DataType::I16(Endianness::LittleEndian), false),
+        (PCM, 24) => (DataType::I32(Endianness::LittleEndian), true),
+        (PCM, 32) => (DataType::I32(Endianness::LittleEndian), false),
+        (IEEE_FLOAT, 32) => (DataType::F32(Endianness::LittleEndian), false),
+        (IEEE_FLOAT, 64) => (DataType::F64(Endianness::LittleEndian), false),
+        (format, bits) => {
+            return Err(invalid(
+                wav,
+                &format!("WAV format {format:#06x} with {bits}-bit samples has no SigMF datatype"),
+            ))
+        }
+    };
+    let number_type = match layout.channels {
+        1 => NumberType::Real,
+        2 => NumberType::Complex,
+        channels => {
+            return Err(invalid(
+                wav,
+                &format!("a WAV file of {channels} channels is neither real nor I and Q"),
+            ))
+        }
+    };
+    let frame = u64::from(layout.channels) * u64::from(layout.bits / 8);
+    let data_len = layout.data.end - layout.data.start;
+    if data_len % frame != 0 {
+        return Err(MetadataError::PartialSample {
+            bytes: data_len,
+            datatype: DataFormat {
+                number_type,
+                data_type,
+            },
+        }
+        .into());
+    }
+
+    let rate = f64::from(layout.sample_rate);
+    if !(rate > 0.0 && rate <= 1e12) {
+        return Err(MetadataError::SampleRateOutOfRange(rate).into());
+    }
+    let mut global = GlobalMetadata::describing(DataFormat {
+        number_type,
+        data_type,
+    });
+    global.sample_rate = Some(rate);
+    let mut capture = CaptureMetadata::new(0);
+    if let Some(auxi) = &layout.auxi {
+        capture.frequency = auxi.frequency;
+        capture.datetime = auxi.start.map(|start| start.to_string());
+    }
+    let metadata = Metadata {
+        global,
+        captures: vec![capture],
+        annotations: vec![],
+    };
+
+    let mut writer = DatasetWriter::create(basename.as_ref(), &[wav])?;
+    // Whole frames per read, which for 24-bit samples `CHUNK_BYTES` alone is
+    // not, so that no sample is split across two.
+    let step = CHUNK_BYTES as u64 / frame * frame;
+    let mut start = layout.data.start;
+    let mut widened = Vec::new();
+    while start < layout.data.end {
+        let end = layout.data.end.min(start + step);
+        read_range(&mut file, wav, start..end, |bytes| {
+            if !widen {
+                return writer.write(bytes);
+            }
+            widened.clear();
+            for sample in bytes.chunks_exact(3) {
+                widened.extend_from_slice(&[0, sample[0], sample[1], sample[2]]);
+            }
+            writer.write(&widened)
+        })?;
+        start = end;
+    }
+    writer.finish(metadata)
+}
+
+/// Write the samples of `recording` to the WAV file `wav`, in the WAV format
+/// closest to its datatype.
+///
+/// | `core:datatype` component | WAV samples |
+/// |---|---|
+/// | `u8`, `i8` | 8-bit integer |
+/// | `i16`, `u16` | 16-bit integer |
+/// | `i32`, `u32` | 32-bit integer |
+/// | `f32` | 32-bit float |
+/// | `f64` | 64-bit float |
+///
+/// WAV's 8-bit samples are unsigned and its wider ones signed, so `i8`, `u16`
+/// and `u32` components are moved to the other representation by inverting
+/// their top bit — which is exact, and puts each signal's midpoint where a
+/// WAV reader expects silence. Big-endian components are byte-swapped. A real
+/// Recording becomes one channel and a complex one two, I on the left.
+///
+/// # Errors
+///
+/// [`MetadataError::MultiChannelDataset`], [`MetadataError::NoSampleRate`],
+/// [`MetadataError::NoDataset`], [`MetadataError::PartialSample`], or
+/// [`Error::Io`] — naming `wav` if the Dataset is too long for a WAV file, or
+/// if the sample rate or the byte rate it implies is not a whole number a WAV
+/// file's 32-bit fields can hold. A `core:frequency` the `auxi` chunk cannot
+/// hold is left out of it instead: the samples do not depend on it.
+pub fn export<P: AsRef<Path>>(recording: &SigMF, wav: P) -> Result<(), Error> {
+    let wav = wav.as_ref();
+    let global = &recording.metadata.global;
+    if let Some(channels) = global.num_channels {
+        if channels != 1 {
+            return Err(MetadataError::MultiChannelDataset(channels).into());
+        }
+    }
+    let sample_rate = global.sample_rate.ok_or(MetadataError::NoSampleRate)?;
+
+    let (mut dataset, segments) = recording.open_dataset()?;
+    let source = recording.dataset_file()?;
+    let data_len: u64 = segments.iter().map(|s| s.bytes.end - s.bytes.start).sum();
+
+    let data_type = global.datatype.data_type;
+    let (format, flip_sign) = match data_type {
+        DataType::F32(_) | DataType::F64(_) => (IEEE_FLOAT, false),
+        DataType::I8 | DataType::U16(_) | DataType::U32(_) => (PCM, true),
+        DataType::U8 | DataType::I16(_) | DataType::I32(_) => (PCM, false),
+    };
+    let swap = global.datatype.endianness() == Some(Endianness::BigEndian);
+    let component = data_type.size() as usize;
+    let channels: u16 = match global.datatype.number_type {
+        NumberType::Real => 1,
+        NumberType::Complex => 2,
+    };
+    let frame = u32::from(channels) * component as u32;
+    let rate = whole_u32(sample_rate).ok_or_else(|| {
+        invalid(
+            wav,
+            "the sample rate is not a whole number of Hz up to 2^32",
+        )
+    })?;
+    let byte_rate = rate.checked_mul(frame).ok_or_else(|| {
+        invalid(
+            wav,
+            "the sample rate is too high for a WAV file's byte rate",
+        )
+    })?;
+
+    let auxi = match recording.metadata.captures.first() {
+        Some(capture) => auxi_chunk(capture, rate, data_len / u64::from(frame)),
+        None => Vec::new(),
+    };
+    let mut header = Vec::with_capacity(80 + auxi.len());
+    let fmt_len: u32 = if format == PCM { 16 } else { 18 };
+    header.extend_from_slice(b"fmt ");
+    header.extend_from_slice(&fmt_len.to_le_bytes());
+    header.extend_from_slice(&format.to_le_bytes());
+    header.extend_from_slice(&channels.to_le_bytes());
+    header.extend_from_slice(&rate.to_le_bytes());
+    header.extend_from_slice(&byte_rate.to_le_bytes());
+    header.extend_from_slice(&(frame as u16).to_le_bytes());
+    header.extend_from_slice(&(component as u16 * 8).to_le_bytes());
+    if format != PCM {
+        // `cbSize`, and the `fact` chunk every non-PCM format must carry.
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(b"fact");
+        header.extend_from_slice(&4u32.to_le_bytes());
+        let frames = u32::try_from(data_len / u64::from(frame)).unwrap_or(u32::MAX);
+        header.extend_from_slice(&frames.to_le_bytes());
+    }
+    header.extend_from_slice(&auxi);
+
+    let riff_len =
+        u32::try_from(4 + header.len() as u64 + 8 + data_len + data_len % 2).map_err(|_| {
+            invalid(
+                wav,
+                "the Dataset is too long for a WAV file, which ends at 4 GiB",
+            )
+        })?;
+    let data_chunk_len = data_len as u32;
+
+    let mut out = BufWriter::new(fs::File::create(wav).map_err(at(wav))?);
+    let mut write = |bytes: &[u8]| out.write_all(bytes).map_err(at(wav));
+    write(b"RIFF")?;
+    write(&riff_len.to_le_bytes())?;
+    write(b"WAVE")?;
+    write(&header)?;
+    write(b"data")?;
+    write(&data_chunk_len.to_le_bytes())?;
+
+    let mut converted = Vec::new();
+    for segment in &segments {
+        read_range(&mut dataset, source, segment.bytes.clone(), |bytes| {
+            converted.clear();
+            converted.extend_from_slice(bytes);
+            for value in converted.chunks_exact_mut(component) {
+                if swap {
+                    value.reverse();
+                }
+                // Little-endian by now, so the top bit is in the last byte.
+                if flip_sign {
+                    value[component - 1] ^= 0x80;
+                }
+            }
+            write(&converted)
+        })?;
+    }
+    if data_len % 2 == 1 {
+        write(&[0])?;
+    }
+    finish_file(out, wav)
+}
+
+/// Where a WAV file's samples are, and what they are.
+struct Layout {
+    format: u16,
+    channels: u16,
+    sample_rate: u32,
+    bits: u16,
+    /// The bytes of the `data` chunk within the file.
+    data: std::ops::Range<u64>,
+    auxi: Option<Auxi>,
+}
+
+/// What an `auxi` chunk says that a Recording has a field for.
+struct Auxi {
+    frequency: Option<f64>,
+    start: Option<Timestamp>,
+}
+
+/// Walk the chunks of the WAV file `file`, reading `fmt ` and `auxi` and
+/// locating `data`.
+fn read_layout(file: &mut fs::File, path: &Path) -> Result<Layout, Error> {
+    let file_len = file.metadata().map_err(at(path))?.len();
+    let mut input = BufReader::new(file);
+    let mut riff = [0u8; 12];
+    input.read_exact(&mut riff).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => invalid(path, "too short to be a WAV file"),
+        _ => Error::Io {
+            path: path.to_path_buf(),
+            source: e,
+        },
+    })?;
+    if &riff[..4] != b"RIFF" || &riff[8..] != b"WAVE" {
+        return Err(invalid(path, "not a RIFF WAVE file"));
+    }
+
+    let mut fmt = None;
+    let mut auxi = None;
+    let mut data = None;
+    let mut position = 12u64;
+    while position + 8 <= file_len {
+        let mut chunk = [0u8; 8];
+        input.read_exact(&mut chunk).map_err(at(path))?;
+        let len = u64::from(u32::from_le_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]));
+        let body = position + 8;
+        let end = match &chunk[..4] {
+            b"fmt " | b"auxi" => {
+                if len > 1 << 16 {
+                    return Err(invalid(path, "a `fmt ` or `auxi` chunk longer than 64 KiB"));
+                }
+                let mut bytes = vec![0u8; len as usize];
+                input.read_exact(&mut bytes).map_err(at(path))?;
+                if &chunk[..4] == b"fmt " {
+                    fmt = Some(bytes);
+                } else {
+                    auxi = Some(bytes);
+                }
+                body + len
+            }
+            // A recorder stopped abruptly may never fill in the length, leaving
+            // a placeholder longer than the file: the samples then run to its
+            // end.
+            b"data" => {
+                let end = (body + len).min(file_len);
+                data = Some(body..end);
+                end
+            }
+            _ => body + len,
+        };
+        // Every chunk is padded to an even length.
+        position = end + end % 2;
+        input.seek(SeekFrom::Start(position)).map_err(at(path))?;
+    }
+
+    let fmt = fmt.ok_or_else(|| invalid(path, "a WAV file without a `fmt ` chunk"))?;
+    let data = data.ok_or_else(|| invalid(path, "a WAV file without a `data` chunk"))?;
+    if fmt.len() < 16 {
+        return Err(invalid(path, "a `fmt ` chunk shorter than 16 bytes"));
+    }
+    let u16_at = |at: usize| u16::from_le_bytes([fmt[at], fmt[at + 1]]);
+    let mut format = u16_at(0);
+    if format == EXTENSIBLE {
+        if fmt.len() < 40 {
+            return Err(invalid(
+                path,
+                "a `WAVE_FORMAT_EXTENSIBLE` chunk shorter than 40 bytes",
+            ));
+        }
+        format = u16_at(24);
+    }
+    Ok(Layout {
+        format,
+        channels: u16_at(2),
+        sample_rate: u32::from_le_bytes([fmt[4], fmt[5], fmt[6], fmt[7]]),
+        bits: u16_at(14),
+        data,
+        auxi: auxi.map(|auxi| read_auxi(&auxi)),
+    })
+}
+
+/// The fields of an `auxi` chunk a Recording can carry: 0 and an all-zero
+/// time are what writers leave in a field they do not know.
+fn read_auxi(bytes: &[u8]) -> Auxi {
+    let u16_at = |at: usize| {
+        bytes
+            .get(at..at + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+    };
+    let frequency = bytes
+        .get(32..36)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .filter(|&frequency| frequency != 0)
+        .map(f64::from);
+    let field = |index: usize| u16_at(2 * index).map(i64::from);
+    let start = (|| {
+        Timestamp::from_civil(Civil {
+            year: field(0)?,
+            month: field(1)?,
+            weekday: field(2)?,
+            day: field(3)?,
+            hour: field(4)?,
+            minute: field(5)?,
+            second: field(6)?,
+            nanos: u32::try_from(field(7)? * 1_000_000).ok()?,
+        })
+    })();
+    Auxi { frequency, start }
+}
+
+/// An `auxi` chunk for a Recording of `frames` samples whose first Captures
+/// segment is `capture`, or nothing if the chunk would hold nothing.
+///
+/// A `core:datetime` this crate cannot parse is left out, and so is a centre
+/// frequency the chunk's 32 bits cannot hold exactly — a fraction of a hertz,
+/// or above 4.29 GHz. Its field is written as 0, which readers take as
+/// absent, rather than as a different frequency.
+fn auxi_chunk(capture: &CaptureMetadata, sample_rate: u32, frames: u64) -> Vec<u8> {
+    let frequency = capture.frequency.and_then(whole_u32).unwrap_or(0);
+    let start = capture.datetime.as_deref().and_then(Timestamp::parse);
+    if frequency == 0 && start.is_none() {
+        return Vec::new();
+    }
+
+    let mut chunk = Vec::with_capacity(8 + AUXI_LEN);
+    chunk.extend_from_slice(b"auxi");
+    chunk.extend_from_slice(&(AUXI_LEN as u32).to_le_bytes());
+    let stop = start.map(|start| start.add_seconds(frames as f64 / f64::from(sample_rate)));
+    for time in [start, stop] {
+        let fields = time.map_or([0; 8], |time| {
+            let civil = time.civil();
+            [
+                civil.year,
+                civil.month,
+                civil.weekday,
+                civil.day,
+                civil.hour,
+                civil.minute,
+                civil.second,
+                i64::from(civil.nanos / 1_000_000),
+            ]
+        });
+        for field in fields {
+            chunk.extend_from_slice(&(field as u16).to_le_bytes());
+        }
+    }
+    chunk.extend_from_slice(&frequency.to_le_bytes());
+    chunk.extend_from_slice(&sample_rate.to_le_bytes());
+    chunk.resize(8 + AUXI_LEN, 0);
+    chunk
+}
+
+/// `value` as a `u32`, if it is a whole number in range.
+fn whole_u32(value: f64) -> Option<u32> {
+    (value.fract() == 0.0 && (0.0..=f64::from(u32::MAX)).contains(&value)).then_some(value as u32)
+}
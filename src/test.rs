@@ -474,6 +474,53 @@ mod data_format {
     }
 }
 
+mod sample_slices {
+    //! The whole-buffer conversions take the length of the buffer they are
+    //! given on trust only as far as their documentation says.
+
+    use crate::num_complex::Complex;
+    use crate::sigmf::{Endianness, Sample};
+
+    fn encode<S: Sample>(samples: &[S], out: &mut [u8]) {
+        S::encode_slice(samples, Endianness::BigEndian, out);
+    }
+
+    fn decode<S: Sample>(bytes: &[u8], out: &mut [S]) {
+        S::decode_to_slice(bytes, Endianness::BigEndian, out);
+    }
+
+    /// A longer buffer is written or read at its start, and the rest left
+    /// alone.
+    #[test]
+    fn a_longer_buffer_is_used_from_its_start() {
+        let mut out = [0xEE; 7];
+        encode(&[Complex::new(1i16, -1)], &mut out);
+        assert_eq!(out, [0x00, 0x01, 0xFF, 0xFF, 0xEE, 0xEE, 0xEE]);
+
+        let mut samples = [0u16; 2];
+        decode(&[0x01, 0x02, 0x03, 0x04, 0x05], &mut samples);
+        assert_eq!(samples, [0x0102, 0x0304]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`out` is too short")]
+    fn encoding_into_too_short_a_buffer_panics() {
+        encode(&[1u32, 2], &mut [0; 7]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`out` is too short")]
+    fn encoding_complex_samples_into_too_short_a_buffer_panics() {
+        encode(&[Complex::new(1.0f32, 2.0)], &mut [0; 7]);
+    }
+
+    #[test]
+    #[should_panic(expected = "fewer samples than `out`")]
+    fn decoding_from_too_few_bytes_panics() {
+        decode(&[0; 3], &mut [0i16; 2]);
+    }
+}
+
 /// Where each Captures segment's samples sit in the Dataset.
 ///
 /// These tests once fed `Metadata::from_str` an 8000-byte buffer of zeroes and read
@@ -560,6 +607,37 @@ mod capture_tests {
         Ok(())
     }
 
+    /// `core:sample_start` is absolute: with `core:offset` 1000, the segment at
+    /// 1500 begins 500 samples into the Dataset.
+    #[test]
+    fn test_boundary_with_offset() -> Result<(), Box<dyn Error>> {
+        let json_data = r#"{
+            "global": {
+                "core:datatype": "cf32_le",
+                "core:version": "1.0.0",
+                "core:num_channels": 1,
+                "core:offset": 1000
+            },
+            "captures": [
+                {
+                    "core:sample_start": 1000
+                },
+                {
+                    "core:sample_start": 1500
+                }
+            ],
+            "annotations": []
+        }"#;
+        let metadata = Metadata::from_json(json_data)?;
+
+        assert_eq!(
+            metadata.capture_boundaries(8000)?,
+            vec![0..4000, 4000..8000]
+        );
+        assert_eq!(metadata.sample_count(8000)?, 1000);
+        Ok(())
+    }
+
     #[test]
     fn test_boundary_with_trailing_first_chunk() -> Result<(), Box<dyn Error>> {
         let json_data = r#"{
@@ -745,3 +823,40 @@ mod capture_tests {
         Ok(())
     }
 }
+
+/// `core:extensions` is the reader's cue, not the data.
+///
+/// Undeclared extension data is not a requirement — nothing told the reader it
+/// must understand it — and a declared-optional extension never is either. Only
+/// `optional: false` can refuse a document.
+#[test]
+fn only_declared_required_extensions_are_checked() -> Result<(), Box<dyn Error>> {
+    let metadata = Metadata::from_json(
+        r#"{
+        "global": {
+            "core:datatype": "rf32_le",
+            "core:version": "1.2.6",
+            "core:extensions": [
+                { "name": "antenna", "version": "1.0.0", "optional": true }
+            ],
+            "antenna:model": "ARA CSB-16",
+            "undeclared:key": "present but never declared"
+        },
+        "captures": [],
+        "annotations": []
+    }"#,
+    )?;
+    metadata.check_extensions(&ExtensionRegistry::new())?;
+
+    let mut required = metadata;
+    required.global.extensions = Some(vec![Extension {
+        name: "antenna".to_string(),
+        version: "1.0.0".to_string(),
+        optional: false,
+    }]);
+    assert!(required
+        .check_extensions(&ExtensionRegistry::new())
+        .is_err());
+    required.check_extensions(&ExtensionRegistry::new().with::<AntennaGlobal>())?;
+    Ok(())
+}
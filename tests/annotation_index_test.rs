@@ -0,0 +1,218 @@
+//! Queries over a Recording's annotations.
+//!
+//! The index answers the same questions a scan of `Metadata::annotations` would,
+//! so every test here states its expectation as the scan would find it. The cases
+//! that need care are the ones where the specification fills in something the
+//! document leaves out: an annotation with no `core:sample_count` runs to the end
+//! of the Dataset, and one with no band is never excluded by a band.
+
+mod common;
+
+use common::annotation;
+use serde_json::json;
+use sigmf::num_complex::Complex;
+use sigmf::{AnnotationIndex, AnnotationMetadata, Metadata, RecordingWriter};
+use tempfile::TempDir;
+
+/// A one-minute survey at 1 kSa/s, with an FM carrier, an ISM burst that runs to
+/// the end of the Recording, and an unbanded operator note.
+fn survey() -> Metadata {
+    serde_json::from_value(json!({
+        "global": { "core:datatype": "cf32_le", "core:version": "1.2.6" },
+        "captures": [],
+        "annotations": [
+            { "core:sample_start": 0, "core:sample_count": 100,
+              "core:freq_lower_edge": 96.9e6, "core:freq_upper_edge": 97.1e6,
+              "core:label": "fm" },
+            { "core:sample_start": 50_000,
+              "core:freq_lower_edge": 433.82e6, "core:freq_upper_edge": 434.02e6,
+              "core:label": "ism" },
+            { "core:sample_start": 20_000, "core:sample_count": 10,
+              "core:label": "note" },
+        ]
+    }))
+    .expect("the survey document must deserialize")
+}
+
+fn labels(found: &[&AnnotationMetadata]) -> Vec<String> {
+    found
+        .iter()
+        .map(|a| a.label.clone().unwrap_or_default())
+        .collect()
+}
+
+/// An annotation without `core:sample_count` applies to the end of the Recording,
+/// so its extent depends on the Dataset and not on the document.
+#[test]
+fn an_open_ended_annotation_runs_to_the_end_of_the_dataset() {
+    let metadata = survey();
+
+    let index = AnnotationIndex::new(&metadata, 60_000);
+    assert_eq!(labels(&index.overlapping_samples(59_999..60_000)), ["ism"]);
+    assert!(
+        index.overlapping_samples(60_000..70_000).is_empty(),
+        "and not past it"
+    );
+
+    let shorter = AnnotationIndex::new(&metadata, 50_000);
+    assert!(
+        shorter
+            .overlapping_samples(0..u64::MAX)
+            .iter()
+            .all(|a| a.label.as_deref() != Some("ism")),
+        "an open-ended annotation starting at the end of the Dataset covers nothing"
+    );
+}
+
+/// A band narrows the answer, except for annotations that state no band: the
+/// index cannot know they are outside it.
+#[test]
+fn a_band_excludes_only_annotations_stating_a_different_band() {
+    let metadata = survey();
+    let index = AnnotationIndex::new(&metadata, 60_000);
+
+    assert_eq!(
+        labels(&index.overlapping(0..60_000, 88e6..=108e6)),
+        ["fm", "note"]
+    );
+    assert_eq!(
+        labels(&index.overlapping_band(434e6..=434e6)),
+        ["ism", "note"],
+        "band edges are inclusive, and a single frequency is a band"
+    );
+}
+
+/// Containment is stricter than overlap at both ends.
+#[test]
+fn within_requires_the_whole_annotation_inside_the_query() {
+    let metadata = survey();
+    let index = AnnotationIndex::new(&metadata, 60_000);
+
+    assert_eq!(labels(&index.within(0..100, 96e6..=98e6)), ["fm"]);
+    assert!(
+        index.within(0..99, 96e6..=98e6).is_empty(),
+        "one sample short of the annotation's end"
+    );
+    assert!(
+        index.within(0..100, 97e6..=98e6).is_empty(),
+        "the band clips the annotation's lower edge"
+    );
+}
+
+/// `nearest` prefers an annotation covering the sample, and otherwise measures to
+/// whichever edge is closer.
+#[test]
+fn nearest_measures_to_the_closest_edge() {
+    let metadata = survey();
+    let index = AnnotationIndex::new(&metadata, 60_000);
+
+    let nearest = |sample| index.nearest(sample).and_then(|a| a.label.clone());
+    assert_eq!(nearest(20_005).as_deref(), Some("note"), "covered");
+    assert_eq!(
+        nearest(10_000).as_deref(),
+        Some("fm"),
+        "9 901 after fm's last sample"
+    );
+    assert_eq!(
+        nearest(40_000).as_deref(),
+        Some("ism"),
+        "10 000 before ism begins"
+    );
+}
+
+/// The index agrees with a plain scan on a document large enough to exercise the
+/// tree rather than a handful of leaves.
+#[test]
+fn the_index_agrees_with_a_scan() {
+    let mut metadata = survey();
+    metadata.annotations = (0..500u64)
+        .map(|i| {
+            annotation(json!({
+                "core:sample_start": (i * 7919) % 10_000,
+                "core:sample_count": (i * 104_729) % 300,
+                "core:label": i.to_string(),
+            }))
+        })
+        .collect();
+    let index = AnnotationIndex::new(&metadata, 10_000);
+
+    for query in [0..1, 100..250, 4_000..4_001, 9_990..20_000, 0..10_000] {
+        let scanned: Vec<&AnnotationMetadata> = metadata
+            .annotations
+            .iter()
+            .filter(|a| {
+                let end = a.sample_start + a.sample_count.unwrap_or(0);
+                a.sample_start.max(query.start) < end.min(query.end)
+            })
+            .collect();
+        assert_eq!(
+            index.overlapping_samples(query.clone()),
+            scanned,
+            "query {query:?}"
+        );
+    }
+}
+
+/// A band edge of NaN, which no JSON document holds but a caller can write,
+/// does not disorder the tree for the annotations around it.
+#[test]
+fn a_nan_band_edge_leaves_the_other_answers_alone() {
+    let mut metadata = survey();
+    metadata.annotations = (0..500u64)
+        .map(|i| {
+            let mut annotation = annotation(json!({
+                "core:sample_start": i,
+                "core:sample_count": 1,
+                "core:freq_lower_edge": ((i * 7919) % 1_000) as f64,
+                "core:freq_upper_edge": ((i * 7919) % 1_000 + 10) as f64,
+                "core:label": i.to_string(),
+            }));
+            if i % 3 == 0 {
+                annotation.freq_lower_edge = Some(f64::NAN);
+                annotation.freq_upper_edge = Some(f64::NAN);
+            }
+            annotation
+        })
+        .collect();
+    let index = AnnotationIndex::new(&metadata, 500);
+
+    for band in [0.0..=5.0, 250.0..=260.0, 995.0..=2_000.0] {
+        let found: Vec<&AnnotationMetadata> = index
+            .overlapping_band(band.clone())
+            .into_iter()
+            .filter(|a| a.freq_lower_edge.is_some_and(|f| !f.is_nan()))
+            .collect();
+        let scanned: Vec<&AnnotationMetadata> = metadata
+            .annotations
+            .iter()
+            .filter(|a| {
+                let (lower, upper) = (a.freq_lower_edge.unwrap(), a.freq_upper_edge.unwrap());
+                !lower.is_nan() && lower <= *band.end() && *band.start() <= upper
+            })
+            .collect();
+        assert_eq!(found, scanned, "band {band:?}");
+    }
+}
+
+/// An opened Recording measures its own Dataset for the index.
+#[test]
+fn an_opened_recording_indexes_against_its_own_length() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = vec![Complex::new(0.0f32, 0.0); 64];
+    let mut writer = RecordingWriter::new(&samples, 1_000.0);
+    writer.annotations_mut().push(annotation(json!({
+        "core:sample_start": 60,
+        "core:label": "tail",
+    })));
+    let recording = writer
+        .to_file(dir.path().join("tail"))
+        .expect("writing must succeed");
+
+    assert_eq!(
+        recording.sample_count().expect("the Dataset is measurable"),
+        64
+    );
+    let index = recording.annotation_index().expect("an index");
+    assert_eq!(labels(&index.overlapping_samples(63..64)), ["tail"]);
+    assert!(index.overlapping_samples(64..65).is_empty());
+}
@@ -0,0 +1,153 @@
+//! Annotations out to CSV and JSON Lines, and back into a document.
+//!
+//! The promise is a round trip: whatever an annotation carries — extension
+//! keys included, of any JSON type — is what comes back, and the derived time
+//! and frequency fields added on the way out do not.
+
+mod common;
+
+use std::fs;
+
+use common::{annotation, retuned_captures};
+use serde_json::json;
+use sigmf::{annotations, Error, GlobalMetadata, Metadata};
+use tempfile::TempDir;
+
+/// A `cf32` watch at 10 Sa/s in two segments, the second retuned, with
+/// annotations whose extension keys are each a different JSON type — and a
+/// string that looks like a number, which must stay a string.
+fn a_watch() -> Metadata {
+    let mut global = GlobalMetadata::describing("cf32_le".parse().expect("a datatype"));
+    global.sample_rate = Some(10.0);
+
+    Metadata {
+        global,
+        captures: retuned_captures(100).to_vec(),
+        annotations: vec![
+            annotation(json!({
+                "core:sample_start": 5,
+                "core:sample_count": 20,
+                "core:freq_lower_edge": 2_187_200.0,
+                "core:freq_upper_edge": 2_187_800.0,
+                "core:label": "DSC distress",
+                "core:comment": "MMSI 211000000, \"undesignated\",\nfire",
+                "dsc:mmsi": "211000000",
+                "dsc:priority": 1,
+                "dsc:position": {"lat": 54.1, "lon": 7.9},
+            })),
+            annotation(json!({
+                "core:sample_start": 110,
+                "core:sample_count": 5,
+                "core:generator": "dsc-decoder",
+                "dsc:acknowledged": true,
+                "dsc:channels": [70, 16],
+            })),
+        ],
+    }
+}
+
+#[test]
+fn csv_round_trips_every_field() {
+    let dir = TempDir::new().expect("a temp dir");
+    let watch = a_watch();
+
+    annotations::export_csv(&watch, dir.path().join("watch.csv")).expect("exporting");
+    let back = annotations::import_csv(dir.path().join("watch.csv")).expect("importing");
+
+    assert_eq!(back, watch.annotations);
+}
+
+#[test]
+fn jsonl_round_trips_every_field() {
+    let dir = TempDir::new().expect("a temp dir");
+    let watch = a_watch();
+
+    annotations::export_jsonl(&watch, dir.path().join("watch.jsonl")).expect("exporting");
+    let back = annotations::import_jsonl(dir.path().join("watch.jsonl")).expect("importing");
+
+    assert_eq!(back, watch.annotations);
+}
+
+/// Times come from the segment holding the first sample; a band from the
+/// edges, or, for an annotation without them, from that segment's frequency
+/// and the sample rate.
+#[test]
+fn derived_fields_place_each_annotation_in_time_and_frequency() {
+    let dir = TempDir::new().expect("a temp dir");
+    let watch = a_watch();
+
+    annotations::export_jsonl(&watch, dir.path().join("watch.jsonl")).expect("exporting");
+    let text = fs::read_to_string(dir.path().join("watch.jsonl")).expect("the export");
+    let lines: Vec<serde_json::Value> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("a JSON object"))
+        .collect();
+
+    assert_eq!(lines[0]["start_time"], "2026-07-16T09:14:22.500Z");
+    assert_eq!(lines[0]["end_time"], "2026-07-16T09:14:24.500Z");
+    assert_eq!(lines[0]["lower_frequency"], 2_187_200.0);
+    assert_eq!(lines[1]["start_time"], "2026-07-16T09:15:01.000Z");
+    assert_eq!(lines[1]["lower_frequency"], 8_414_495.0);
+    assert_eq!(lines[1]["upper_frequency"], 8_414_505.0);
+}
+
+#[test]
+fn csv_has_a_column_per_field_and_extension_key() {
+    let dir = TempDir::new().expect("a temp dir");
+
+    annotations::export_csv(&a_watch(), dir.path().join("watch.csv")).expect("exporting");
+    let text = fs::read_to_string(dir.path().join("watch.csv")).expect("the export");
+
+    assert_eq!(
+        text.lines().next(),
+        Some(
+            "core:sample_start,core:sample_count,core:freq_lower_edge,core:freq_upper_edge,\
+             core:label,core:generator,core:comment,core:uuid,\
+             start_time,end_time,lower_frequency,upper_frequency,\
+             dsc:acknowledged,dsc:channels,dsc:mmsi,dsc:position,dsc:priority"
+        )
+    );
+}
+
+/// A labeller's spreadsheet: columns reordered, derived ones deleted, an
+/// extension column added and typed into by hand, and LF line endings.
+#[test]
+fn a_hand_edited_csv_imports() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::write(
+        dir.path().join("labels.csv"),
+        "core:label,core:sample_start,review:verdict,review:score\n\
+         carrier,0,confirmed,0.9\n\
+         ,40,,\n",
+    )
+    .expect("a CSV file");
+
+    let back = annotations::import_csv(dir.path().join("labels.csv")).expect("importing");
+
+    assert_eq!(
+        back,
+        [
+            annotation(json!({
+                "core:sample_start": 0,
+                "core:label": "carrier",
+                "review:verdict": "confirmed",
+                "review:score": 0.9,
+            })),
+            annotation(json!({"core:sample_start": 40})),
+        ]
+    );
+}
+
+#[test]
+fn a_cell_of_the_wrong_type_is_refused_with_its_row() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::write(
+        dir.path().join("bad.csv"),
+        "core:sample_start,core:sample_count\n0,1\n5,five\n",
+    )
+    .expect("a CSV file");
+
+    let err = annotations::import_csv(dir.path().join("bad.csv")).expect_err("five is a word");
+    assert!(matches!(err, Error::Io { .. }));
+    assert!(err.to_string().contains("row 3"), "{err}");
+}
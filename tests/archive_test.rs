@@ -0,0 +1,157 @@
+//! Archives: Recordings into one `.sigmf` tar file and back out.
+//!
+//! An Archive is an interchange format, so the tests that matter most are the
+//! ones about files this crate did not write, and about Archives that must
+//! not be trusted.
+
+mod common;
+
+use std::fs;
+
+use common::watch;
+use sigmf::{archive, Error};
+use tempfile::TempDir;
+
+/// One 512-byte ustar header for a regular file `name` of `size` bytes, built
+/// by hand so that the reader is judged against the format rather than against
+/// the writer.
+fn ustar_header(name: &str, size: usize) -> Vec<u8> {
+    let mut block = vec![0u8; 512];
+    block[..name.len()].copy_from_slice(name.as_bytes());
+    block[100..107].copy_from_slice(b"0000644");
+    block[124..135].copy_from_slice(format!("{size:011o}").as_bytes());
+    block[156] = b'0';
+    block[257..263].copy_from_slice(b"ustar\0");
+    block[263..265].copy_from_slice(b"00");
+    block[148..156].fill(b' ');
+    let sum: u32 = block.iter().map(|&b| u32::from(b)).sum();
+    block[148..155].copy_from_slice(format!("{sum:06o}\0").as_bytes());
+    block
+}
+
+#[test]
+fn an_archive_holds_one_directory_named_for_it() {
+    let dir = TempDir::new().expect("a temp dir");
+    watch(dir.path(), "hf8");
+    watch(dir.path(), "hf16");
+
+    archive::pack(
+        dir.path().join("walvisbay.sigmf"),
+        &[
+            dir.path().join("hf8.sigmf-meta"),
+            dir.path().join("hf16.sigmf-meta"),
+        ],
+    )
+    .expect("packing must succeed");
+    let recordings = archive::unpack(dir.path().join("walvisbay.sigmf"), dir.path().join("out"))
+        .expect("unpacking must succeed");
+
+    let mut entries: Vec<String> = fs::read_dir(dir.path().join("out/walvisbay"))
+        .expect("the Archive's directory")
+        .map(|e| {
+            e.expect("an entry")
+                .file_name()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    entries.sort();
+    assert_eq!(
+        entries,
+        [
+            "hf16.sigmf-data",
+            "hf16.sigmf-meta",
+            "hf8.sigmf-data",
+            "hf8.sigmf-meta"
+        ]
+    );
+    assert_eq!(recordings.len(), 2);
+    for recording in &recordings {
+        recording
+            .verify_sha512()
+            .expect("every Dataset arrives intact");
+    }
+}
+
+/// Two Recordings cannot both be `watch` in one directory.
+#[test]
+fn recordings_sharing_a_name_are_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::create_dir(dir.path().join("a")).expect("a directory");
+    fs::create_dir(dir.path().join("b")).expect("a directory");
+    watch(&dir.path().join("a"), "watch");
+    watch(&dir.path().join("b"), "watch");
+
+    let err = archive::pack(
+        dir.path().join("both.sigmf"),
+        &[
+            dir.path().join("a/watch.sigmf-meta"),
+            dir.path().join("b/watch.sigmf-meta"),
+        ],
+    )
+    .expect_err("the second watch would overwrite the first");
+    assert!(matches!(err, Error::Io { .. }));
+}
+
+/// An Archive written by another tool, with nothing but plain ustar in it.
+#[test]
+fn a_hand_built_ustar_archive_unpacks() {
+    let dir = TempDir::new().expect("a temp dir");
+    let meta = br#"{"global": {"core:datatype": "ru8", "core:version": "1.2.6"}, "captures": [], "annotations": []}"#;
+    let data = [1u8, 2, 3];
+
+    let mut tar = Vec::new();
+    for (name, bytes) in [
+        ("elsewhere/x.sigmf-meta", &meta[..]),
+        ("elsewhere/x.sigmf-data", &data[..]),
+    ] {
+        tar.extend(ustar_header(name, bytes.len()));
+        tar.extend(bytes);
+        tar.resize(tar.len().next_multiple_of(512), 0);
+    }
+    tar.extend([0u8; 1024]);
+    fs::write(dir.path().join("elsewhere.sigmf"), tar).expect("writing the Archive");
+
+    let recordings = archive::unpack(dir.path().join("elsewhere.sigmf"), dir.path())
+        .expect("unpacking must succeed");
+    assert_eq!(
+        recordings[0].samples::<u8>().expect("reads back"),
+        [1, 2, 3]
+    );
+}
+
+/// A member named `../escape` would be written outside the directory it was
+/// asked to be unpacked into.
+#[test]
+fn a_member_climbing_out_of_the_target_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    let mut tar = ustar_header("../escape.sigmf-meta", 2);
+    tar.extend(b"{}");
+    tar.resize(1024, 0);
+    tar.extend([0u8; 1024]);
+    fs::write(dir.path().join("hostile.sigmf"), tar).expect("writing the Archive");
+
+    let target = dir.path().join("target");
+    archive::unpack(dir.path().join("hostile.sigmf"), &target)
+        .expect_err("the member escapes the target directory");
+    assert!(!dir.path().join("escape.sigmf-meta").exists());
+}
+
+/// A corrupted header is caught by its checksum, not extracted as garbage.
+#[test]
+fn a_corrupted_header_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    watch(dir.path(), "watch");
+    archive::pack(
+        dir.path().join("watch.sigmf"),
+        &[dir.path().join("watch.sigmf-meta")],
+    )
+    .expect("packing must succeed");
+
+    let mut bytes = fs::read(dir.path().join("watch.sigmf")).expect("the Archive");
+    bytes[0] ^= 0x20;
+    fs::write(dir.path().join("watch.sigmf"), bytes).expect("corrupting it");
+
+    archive::unpack(dir.path().join("watch.sigmf"), dir.path().join("out"))
+        .expect_err("the checksum no longer matches");
+}
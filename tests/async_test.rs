@@ -0,0 +1,151 @@
+//! The async API against the blocking one it mirrors: the same files written,
+//! the same samples read, and the same Recordings refused for the same
+//! reasons.
+
+use std::fs;
+
+use serde_json::json;
+use sigmf::num_complex::Complex;
+use sigmf::{CaptureMetadata, Error, MetadataError, RecordingWriter, SampleStream, SigMF};
+use tempfile::TempDir;
+
+/// Every chunk a stream has left, end to end.
+async fn drain<S: sigmf::Sample>(mut stream: SampleStream<S>) -> (Vec<S>, usize) {
+    let mut samples = Vec::new();
+    let mut chunks = 0;
+    while let Some(chunk) = stream.next_chunk().await.expect("the Dataset reads") {
+        assert!(!chunk.is_empty(), "a chunk is never empty");
+        samples.extend(chunk);
+        chunks += 1;
+    }
+    (samples, chunks)
+}
+
+/// What `to_file_async` writes is byte for byte what `to_file` writes, and a
+/// Dataset of several mebibytes comes back whole, over several chunks.
+#[tokio::test]
+async fn writes_what_to_file_writes_and_streams_it_back() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples: Vec<Complex<f32>> = (0..300_000)
+        .map(|n| Complex::new(n as f32, -(n as f32)))
+        .collect();
+
+    RecordingWriter::new(&samples, 1e6)
+        .to_file(dir.path().join("blocking"))
+        .expect("writing must succeed");
+    RecordingWriter::new(&samples, 1e6)
+        .to_file_async(dir.path().join("async"))
+        .await
+        .expect("writing must succeed");
+    for ext in ["sigmf-data", "sigmf-meta"] {
+        assert_eq!(
+            fs::read(dir.path().join(format!("async.{ext}"))).expect("written"),
+            fs::read(dir.path().join(format!("blocking.{ext}"))).expect("written"),
+            "the .{ext} files differ"
+        );
+    }
+
+    let recording = SigMF::open_async(dir.path().join("async.sigmf-meta"))
+        .await
+        .expect("opens");
+    let stream = recording
+        .sample_stream::<Complex<f32>>()
+        .await
+        .expect("the datatype matches");
+    let (read, chunks) = drain(stream).await;
+    assert_eq!(read, samples);
+    assert_eq!(chunks, 3, "2.4 MB in chunks of at most a mebibyte");
+}
+
+/// A Non-Conforming Dataset's header and trailing bytes are skipped at every
+/// segment, as `samples` skips them.
+#[tokio::test]
+async fn non_conforming_datasets_stream_only_their_samples() {
+    let dir = TempDir::new().expect("a temp dir");
+    let mut data = vec![0xEE; 3];
+    data.extend([1i16, -1, 2, -2].iter().flat_map(|s| s.to_le_bytes()));
+    data.extend([0xEE; 5]);
+    data.extend([3i16, -3].iter().flat_map(|s| s.to_le_bytes()));
+    data.extend([0xEE; 2]);
+    fs::write(dir.path().join("capture.raw"), data).expect("the data file");
+    let metadata = json!({
+        "global": {
+            "core:datatype": "ci16_le",
+            "core:version": "1.2.0",
+            "core:dataset": "capture.raw",
+            "core:trailing_bytes": 2
+        },
+        "captures": [
+            { "core:sample_start": 0, "core:header_bytes": 3 },
+            { "core:sample_start": 2, "core:header_bytes": 5 }
+        ],
+        "annotations": []
+    });
+    fs::write(
+        dir.path().join("capture.sigmf-meta"),
+        serde_json::to_vec(&metadata).expect("serializes"),
+    )
+    .expect("the metadata file");
+
+    let recording = SigMF::open_async(dir.path().join("capture.sigmf-meta"))
+        .await
+        .expect("opens");
+    let stream = recording
+        .sample_stream::<Complex<i16>>()
+        .await
+        .expect("the datatype matches");
+    let (read, chunks) = drain(stream).await;
+
+    assert_eq!(read, recording.samples::<Complex<i16>>().expect("reads"));
+    assert_eq!(
+        read,
+        [
+            Complex::new(1, -1),
+            Complex::new(2, -2),
+            Complex::new(3, -3)
+        ]
+    );
+    assert_eq!(chunks, 2, "one per segment");
+}
+
+/// The checks are the blocking API's own: the wrong sample type, and a
+/// `core:dataset` that climbs out of its directory, fail with the same errors.
+#[tokio::test]
+async fn refuses_what_the_blocking_api_refuses() {
+    let dir = TempDir::new().expect("a temp dir");
+    let mut writer = RecordingWriter::new(&[0.5f32, -0.5], 8_000.0);
+    writer.captures_mut().push(CaptureMetadata::new(0));
+    let recording = writer
+        .to_file_async(dir.path().join("real"))
+        .await
+        .expect("writing must succeed");
+    assert!(matches!(
+        recording.sample_stream::<Complex<f32>>().await,
+        Err(Error::Metadata(MetadataError::DatatypeMismatch { .. }))
+    ));
+
+    let escaping = json!({
+        "global": {
+            "core:datatype": "rf32_le",
+            "core:version": "1.2.0",
+            "core:dataset": "../elsewhere.raw"
+        },
+        "captures": [],
+        "annotations": []
+    });
+    let path = dir.path().join("escaping.sigmf-meta");
+    fs::write(&path, serde_json::to_vec(&escaping).expect("serializes"))
+        .expect("the metadata file");
+    assert!(matches!(
+        SigMF::from_file(&path),
+        Err(Error::Metadata(MetadataError::DatasetPathEscapesDirectory(
+            _
+        )))
+    ));
+    assert!(matches!(
+        SigMF::open_async(&path).await,
+        Err(Error::Metadata(MetadataError::DatasetPathEscapesDirectory(
+            _
+        )))
+    ));
+}
@@ -0,0 +1,247 @@
+//! MIDAS Bluefiles, built field by field as X-Midas lays them out.
+
+use std::fs;
+
+use serde_json::json;
+use sigmf::bluefile::{self, BluefileGlobal};
+use sigmf::num_complex::Complex;
+use sigmf::{Error, MetadataError, RecordingWriter};
+use tempfile::TempDir;
+
+/// 2026-07-16T09:14:22Z, in seconds since 1950-01-01.
+const TIMECODE: f64 = 2_415_345_262.0;
+
+/// A little-endian type 1000 Bluefile of `format`, with `main` as the main
+/// header's keywords, `extended` as its extended header, and `data` starting
+/// at byte 512.
+fn bluefile(format: &[u8; 2], xdelta: f64, main: &str, extended: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut hcb = [0u8; 512];
+    let mut put = |at: usize, bytes: &[u8]| hcb[at..at + bytes.len()].copy_from_slice(bytes);
+    let ext_start = (512 + data.len()).div_ceil(512);
+    put(0, b"BLUEEEEIEEEI");
+    put(24, &(ext_start as i32).to_le_bytes());
+    put(28, &(extended.len() as i32).to_le_bytes());
+    put(32, &512f64.to_le_bytes());
+    put(40, &(data.len() as f64).to_le_bytes());
+    put(48, &1000i32.to_le_bytes());
+    put(52, format);
+    put(56, &TIMECODE.to_le_bytes());
+    put(160, &(main.len() as i32).to_le_bytes());
+    put(164, main.as_bytes());
+    put(264, &xdelta.to_le_bytes());
+    put(272, &1i32.to_le_bytes());
+
+    let mut file = hcb.to_vec();
+    file.extend(data);
+    file.resize(ext_start * 512, 0);
+    file.extend(extended);
+    file
+}
+
+/// One extended header keyword record: `name` of type `code` holding `data`.
+fn keyword(name: &str, code: u8, data: &[u8]) -> Vec<u8> {
+    let lkey = (8 + data.len() + name.len()).next_multiple_of(8);
+    let mut record = (lkey as u32).to_le_bytes().to_vec();
+    record.extend(((lkey - data.len()) as u16).to_le_bytes());
+    record.extend([name.len() as u8, code]);
+    record.extend(data);
+    record.extend(name.as_bytes());
+    record.resize(lkey, 0);
+    record
+}
+
+#[test]
+fn the_header_becomes_the_metadata() {
+    let dir = TempDir::new().expect("a temp dir");
+    let data: Vec<u8> = [1i16, -1, 2, -2]
+        .iter()
+        .flat_map(|s| s.to_le_bytes())
+        .collect();
+    let extended = keyword("RF_FREQ", b'D', &2_187_500f64.to_le_bytes());
+    fs::write(
+        dir.path().join("dsc.tmp"),
+        bluefile(b"CI", 1.0 / 32_000.0, "", &extended, &data),
+    )
+    .expect("a Bluefile");
+
+    let recording = bluefile::import(dir.path().join("dsc.tmp"), dir.path().join("dsc"))
+        .expect("importing must succeed");
+
+    let global = &recording.metadata.global;
+    assert_eq!(global.datatype.to_string(), "ci16_le");
+    assert_eq!(global.sample_rate, Some(32_000.0));
+    let capture = &recording.metadata.captures[0];
+    assert_eq!(capture.frequency, Some(2_187_500.0));
+    assert_eq!(
+        capture.datetime.as_deref(),
+        Some("2026-07-16T09:14:22.000Z")
+    );
+    assert_eq!(
+        recording.samples::<Complex<i16>>().expect("reads back"),
+        [Complex::new(1, -1), Complex::new(2, -2)]
+    );
+    assert_eq!(
+        global.get_extension::<BluefileGlobal>().expect("parses"),
+        None,
+        "RF_FREQ has a field of its own, and there is nothing else to keep"
+    );
+    recording
+        .verify_sha512()
+        .expect("the hash is of the Dataset");
+}
+
+/// Keywords with no SigMF field are kept, typed as the Bluefile typed them,
+/// from both the main header and the extended one.
+#[test]
+fn other_keywords_are_kept_in_the_extension() {
+    let dir = TempDir::new().expect("a temp dir");
+    let data: Vec<u8> = [0.5f32, -0.5]
+        .iter()
+        .flat_map(|s| s.to_le_bytes())
+        .collect();
+    let mut extended = keyword("OPERATOR", b'A', b"watch officer");
+    extended.extend(keyword(
+        "GAINS",
+        b'L',
+        &[10i32.to_le_bytes(), 20i32.to_le_bytes()].concat(),
+    ));
+    extended.extend(keyword("TC_PREC", b'D', &0.25f64.to_le_bytes()));
+    fs::write(
+        dir.path().join("watch.tmp"),
+        bluefile(b"SF", 0.5, "SITE=North Head\0", &extended, &data),
+    )
+    .expect("a Bluefile");
+
+    let recording = bluefile::import(dir.path().join("watch.tmp"), dir.path().join("watch"))
+        .expect("importing must succeed");
+
+    assert_eq!(recording.metadata.global.datatype.to_string(), "rf32_le");
+    assert_eq!(
+        recording.metadata.captures[0].datetime.as_deref(),
+        Some("2026-07-16T09:14:22.250Z"),
+        "TC_PREC refines the start time"
+    );
+    let kept = recording
+        .metadata
+        .global
+        .get_extension::<BluefileGlobal>()
+        .expect("parses")
+        .expect("the keywords are kept");
+    assert_eq!(
+        serde_json::to_value(&kept.keywords).expect("serializes"),
+        json!({"GAINS": [10, 20], "OPERATOR": "watch officer", "SITE": "North Head"})
+    );
+}
+
+/// Out and back again, the samples, metadata and keywords are what they were.
+#[test]
+fn an_export_imports_back_unchanged() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [Complex::new(0.25f64, -0.25), Complex::new(1.0, -1.0)];
+    let mut writer = RecordingWriter::new(&samples, 32_000.0);
+    let mut capture = sigmf::CaptureMetadata::new(0);
+    capture.frequency = Some(8_414_500.0);
+    capture.datetime = Some("2026-07-16T09:14:22.125Z".to_string());
+    writer.captures_mut().push(capture);
+    let mut keywords = BluefileGlobal::default();
+    keywords
+        .keywords
+        .insert("SITE".to_string(), json!("North Head"));
+    keywords
+        .keywords
+        .insert("GAINS".to_string(), json!([10, 20]));
+    writer
+        .global_mut()
+        .set_extension(keywords.clone())
+        .expect("the extension serializes");
+    let recording = writer
+        .to_file(dir.path().join("watch"))
+        .expect("writing must succeed");
+
+    bluefile::export(&recording, dir.path().join("watch.tmp")).expect("exporting must succeed");
+    let back = bluefile::import(dir.path().join("watch.tmp"), dir.path().join("back"))
+        .expect("importing must succeed");
+
+    assert_eq!(back.metadata.global.datatype.to_string(), "cf64_le");
+    assert_eq!(back.metadata.global.sample_rate, Some(32_000.0));
+    assert_eq!(back.metadata.captures, recording.metadata.captures);
+    assert_eq!(back.samples::<Complex<f64>>().expect("reads back"), samples);
+    assert_eq!(
+        back.metadata
+            .global
+            .get_extension::<BluefileGlobal>()
+            .expect("parses"),
+        Some(keywords)
+    );
+}
+
+/// Bluefiles have no unsigned 16 or 32-bit format; rather than reinterpret
+/// the samples, the export says so.
+#[test]
+fn unsigned_wide_samples_are_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [1u16, 2, 3];
+    let recording = RecordingWriter::new(&samples, 32_000.0)
+        .to_file(dir.path().join("counts"))
+        .expect("writing must succeed");
+
+    let err = bluefile::export(&recording, dir.path().join("counts.tmp"))
+        .expect_err("ru16_le has no Bluefile format");
+    assert!(matches!(
+        err,
+        Error::Metadata(MetadataError::NoEquivalentFormat { .. })
+    ));
+    assert!(!dir.path().join("counts.tmp").exists());
+}
+
+#[test]
+fn a_file_that_is_not_a_bluefile_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    let mut file = bluefile(b"CI", 1.0, "", &[], &[0; 4]);
+    file[..4].copy_from_slice(b"RIFF");
+    fs::write(dir.path().join("other.tmp"), file).expect("a file");
+
+    let err = bluefile::import(dir.path().join("other.tmp"), dir.path().join("other"))
+        .expect_err("no BLUE magic");
+    assert!(matches!(err, Error::Io { .. }));
+    assert!(!dir.path().join("other.sigmf-data").exists());
+}
+
+#[test]
+fn data_of_part_of_a_sample_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::write(
+        dir.path().join("odd.tmp"),
+        bluefile(b"CF", 1.0, "", &[], &[0; 12]),
+    )
+    .expect("a Bluefile");
+
+    let err = bluefile::import(dir.path().join("odd.tmp"), dir.path().join("odd"))
+        .expect_err("twelve bytes of eight-byte samples");
+    assert!(matches!(
+        err,
+        Error::Metadata(MetadataError::PartialSample { bytes: 12, .. })
+    ));
+}
+
+/// `data_start` and `data_size` are only numbers in a header; data they place
+/// past the end of the file, or past the end of any file, is refused before
+/// anything is written.
+#[test]
+fn data_past_the_end_of_the_file_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    for (name, data_start, data_size) in [("long", 512.0, 1024.0), ("huge", 1e18, 1.8e19)] {
+        let mut file = bluefile(b"CF", 1.0, "", &[], &[0; 8]);
+        file[32..40].copy_from_slice(&f64::to_le_bytes(data_start));
+        file[40..48].copy_from_slice(&f64::to_le_bytes(data_size));
+        let blue = dir.path().join(format!("{name}.tmp"));
+        fs::write(&blue, file).expect("a Bluefile");
+
+        let err = bluefile::import(&blue, dir.path().join(name)).expect_err("no such data");
+        assert!(
+            matches!(err, Error::Io { ref path, .. } if *path == blue),
+            "{err}"
+        );
+        assert!(!dir.path().join(format!("{name}.sigmf-data")).exists());
+    }
+}
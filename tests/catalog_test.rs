@@ -0,0 +1,343 @@
+//! Scanning a store of Recordings into a catalog: the summary of each, and
+//! every broken pair reported rather than stopping the scan; then searching
+//! the catalog by band, time, place and label.
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use common::{annotation, retuned_captures};
+use serde_json::json;
+use sigmf::catalog::{self, Catalog, ProblemKind, Query};
+use sigmf::{archive, CaptureMetadata, Collection, Geolocation, RecordingWriter};
+use tempfile::TempDir;
+
+/// Ten samples at 10 Sa/s in two timed segments, retuned between them, with
+/// three annotations of which two share a label.
+fn a_watch(basename: &Path) {
+    let samples = [0.5f32; 10];
+    let mut writer = RecordingWriter::new(&samples, 10.0);
+    writer.global_mut().geolocation = Some(Geolocation::new(14.5, -22.9));
+    writer.captures_mut().extend(retuned_captures(4));
+    writer.annotations_mut().extend([
+        annotation(json!({"core:sample_start": 0, "core:label": "DSC"})),
+        annotation(json!({"core:sample_start": 5, "core:label": "DSC"})),
+        annotation(json!({"core:sample_start": 6, "core:label": "carrier"})),
+    ]);
+    writer.to_file(basename).expect("writing must succeed");
+}
+
+#[test]
+fn each_recording_is_summarized() {
+    let dir = TempDir::new().expect("a temp dir");
+    a_watch(&dir.path().join("watch"));
+
+    let catalog = catalog::scan(dir.path()).expect("scanning must succeed");
+
+    assert_eq!(catalog.problems, []);
+    let [entry] = &catalog.recordings[..] else {
+        panic!("one Recording: {:?}", catalog.recordings);
+    };
+    assert_eq!(entry.path, dir.path().join("watch.sigmf-meta"));
+    assert_eq!(entry.datatype.to_string(), "rf32_le");
+    assert_eq!(entry.sample_rate, Some(10.0));
+    assert_eq!(entry.sample_count, Some(10));
+    assert_eq!(entry.duration, Some(1.0));
+    assert_eq!(entry.lower_frequency, Some(2_187_495.0));
+    assert_eq!(entry.upper_frequency, Some(8_414_505.0));
+    assert_eq!(
+        entry.start_time.as_deref(),
+        Some("2026-07-16T09:14:22.000Z")
+    );
+    assert_eq!(
+        entry.end_time.as_deref(),
+        Some("2026-07-16T09:15:00.600Z"),
+        "the second segment's six samples, at 10 Sa/s"
+    );
+    assert_eq!(entry.geolocation, Some(Geolocation::new(14.5, -22.9)));
+    assert_eq!(entry.sha512.as_ref().map(String::len), Some(128));
+    assert_eq!(entry.labels, ["DSC", "carrier"]);
+}
+
+/// Every kind of broken pair in one tree, each reported against its file,
+/// and the one good Recording still catalogued.
+#[test]
+fn broken_pairs_are_problems_not_errors() {
+    let dir = TempDir::new().expect("a temp dir");
+    a_watch(&dir.path().join("good"));
+    a_watch(&dir.path().join("lost"));
+    fs::remove_file(dir.path().join("lost.sigmf-data")).expect("removing a Dataset");
+    fs::write(dir.path().join("stray.sigmf-data"), [0u8; 8]).expect("an orphan");
+    fs::write(
+        dir.path().join("escape.sigmf-meta"),
+        json!({
+            "global": {
+                "core:datatype": "cf32_le",
+                "core:version": "1.2.0",
+                "core:dataset": "../elsewhere.sigmf-data",
+            },
+            "captures": [],
+            "annotations": [],
+        })
+        .to_string(),
+    )
+    .expect("a Metadata file");
+    fs::write(dir.path().join("garbled.sigmf-meta"), "{").expect("a broken file");
+
+    let catalog = catalog::scan(dir.path()).expect("scanning must succeed");
+
+    let problems: Vec<(String, ProblemKind)> = catalog
+        .problems
+        .iter()
+        .map(|p| {
+            let name = p.path.file_name().expect("a file name");
+            (name.to_string_lossy().into_owned(), p.kind)
+        })
+        .collect();
+    assert_eq!(
+        problems,
+        [
+            (
+                "escape.sigmf-meta".to_string(),
+                ProblemKind::DatasetPathEscapesDirectory
+            ),
+            ("garbled.sigmf-meta".to_string(), ProblemKind::Unreadable),
+            ("lost.sigmf-meta".to_string(), ProblemKind::MissingDataset),
+            ("stray.sigmf-data".to_string(), ProblemKind::OrphanDataset),
+        ]
+    );
+
+    let catalogued: Vec<_> = catalog.recordings.iter().map(|e| &e.path).collect();
+    assert_eq!(
+        catalogued,
+        [
+            &dir.path().join("good.sigmf-meta"),
+            &dir.path().join("lost.sigmf-meta")
+        ]
+    );
+    assert_eq!(catalog.recordings[1].sample_count, None);
+    assert_eq!(
+        catalog.recordings[1].start_time.as_deref(),
+        Some("2026-07-16T09:14:22.000Z"),
+        "what the Metadata alone says is still there"
+    );
+    assert_eq!(
+        catalog.recordings[1].end_time, None,
+        "without the Dataset, the last segment's length is unknown"
+    );
+}
+
+/// An Archive's Recordings are catalogued from inside it, without being
+/// extracted.
+#[test]
+fn archives_are_read_in_place() {
+    let dir = TempDir::new().expect("a temp dir");
+    let store = dir.path().join("store");
+    fs::create_dir(&store).expect("a store");
+    a_watch(&dir.path().join("watch"));
+    archive::pack(
+        store.join("walvisbay.sigmf"),
+        &[dir.path().join("watch.sigmf-meta")],
+    )
+    .expect("packing must succeed");
+
+    let catalog = catalog::scan(&store).expect("scanning must succeed");
+
+    assert_eq!(catalog.problems, []);
+    let [entry] = &catalog.recordings[..] else {
+        panic!("one Recording: {:?}", catalog.recordings);
+    };
+    assert_eq!(entry.path, store.join("walvisbay.sigmf"));
+    assert_eq!(entry.member.as_deref(), Some("walvisbay/watch.sigmf-meta"));
+    assert_eq!(entry.sample_count, Some(10));
+    assert_eq!(entry.labels, ["DSC", "carrier"]);
+    assert_eq!(fs::read_dir(&store).expect("the store").count(), 1);
+}
+
+/// A Collection is listed with its streams, and a stream edited since the
+/// Collection hashed it is reported.
+#[test]
+fn collections_are_listed_and_checked() {
+    let dir = TempDir::new().expect("a temp dir");
+    a_watch(&dir.path().join("hf8"));
+    a_watch(&dir.path().join("hf16"));
+    let path = dir.path().join("walvisbay.sigmf-collection");
+    Collection::create(
+        &path,
+        &[
+            dir.path().join("hf8.sigmf-meta"),
+            dir.path().join("hf16.sigmf-meta"),
+        ],
+    )
+    .expect("collecting must succeed");
+    let edited = fs::read_to_string(dir.path().join("hf16.sigmf-meta"))
+        .expect("a Metadata file")
+        .replace("carrier", "tone");
+    fs::write(dir.path().join("hf16.sigmf-meta"), edited).expect("an edit");
+
+    let catalog = catalog::scan(dir.path()).expect("scanning must succeed");
+
+    assert_eq!(catalog.collections.len(), 1);
+    assert_eq!(catalog.collections[0].streams, ["hf8", "hf16"]);
+    let [problem] = &catalog.problems[..] else {
+        panic!("one problem: {:?}", catalog.problems);
+    };
+    assert_eq!(problem.kind, ProblemKind::CollectionStream);
+    assert!(problem.detail.contains("hf16"), "{}", problem.detail);
+    assert_eq!(catalog.recordings.len(), 2);
+}
+
+#[test]
+fn a_catalog_round_trips_through_json() {
+    let dir = TempDir::new().expect("a temp dir");
+    a_watch(&dir.path().join("watch"));
+    fs::write(dir.path().join("stray.sigmf-data"), [0u8; 8]).expect("an orphan");
+    let catalog = catalog::scan(dir.path()).expect("scanning must succeed");
+
+    catalog
+        .to_file(dir.path().join("catalog.json"))
+        .expect("writing must succeed");
+    let json: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(dir.path().join("catalog.json")).expect("the catalog"),
+    )
+    .expect("JSON");
+    assert_eq!(json["recordings"][0]["datatype"], "rf32_le");
+    assert_eq!(json["problems"][0]["kind"], "orphan_dataset");
+
+    let back = Catalog::from_file(dir.path().join("catalog.json")).expect("reading must succeed");
+    assert_eq!(back, catalog);
+}
+
+fn at(datetime: &str) -> SystemTime {
+    let seconds = match datetime {
+        "09:14" => 1_784_193_240,
+        "09:15" => 1_784_193_300,
+        "09:20" => 1_784_193_600,
+        "10:00" => 1_784_196_000,
+        other => panic!("no fixture for {other}"),
+    };
+    UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+/// A Recording's band is its segments' bands, not the span between them: the
+/// watch hops from 2.1875 MHz to 8.4145 MHz and never hears 5 MHz.
+#[test]
+fn a_query_matches_a_segment_band_not_the_span() {
+    let dir = TempDir::new().expect("a temp dir");
+    a_watch(&dir.path().join("watch"));
+    let catalog = catalog::scan(dir.path()).expect("scanning must succeed");
+
+    assert_eq!(catalog.query(&Query::new().covering(2_187_500.0)).len(), 1);
+    assert_eq!(catalog.query(&Query::new().covering(8_414_504.0)).len(), 1);
+    assert!(catalog.query(&Query::new().covering(5e6)).is_empty());
+    assert_eq!(
+        catalog
+            .query(&Query::new().overlapping(8_414_504.0, 9e6))
+            .len(),
+        1
+    );
+}
+
+/// Band, time and place must hold in one segment: the drive was off Walvis
+/// Bay on channel 70 and further up the coast on channel 16, and was never on
+/// channel 16 off Walvis Bay.
+#[test]
+fn a_query_holds_every_condition_in_one_segment() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [0.5f32; 20];
+    let mut writer = RecordingWriter::new(&samples, 10.0);
+    let mut walvis = CaptureMetadata::new(0);
+    walvis.frequency = Some(156_525_000.0);
+    walvis.datetime = Some("2026-07-16T09:14:00.000Z".to_string());
+    walvis.geolocation = Some(Geolocation::new(14.5053, -22.9576));
+    let mut north = CaptureMetadata::new(10);
+    north.frequency = Some(156_800_000.0);
+    north.geolocation = Some(Geolocation::new(14.52, -22.60));
+    writer.captures_mut().extend([walvis, north]);
+    writer
+        .to_file(dir.path().join("drive"))
+        .expect("writing must succeed");
+    let catalog = catalog::scan(dir.path()).expect("scanning must succeed");
+
+    let off_walvis_bay = Geolocation::new(14.55, -22.95);
+    let channel_70 = Query::new()
+        .covering(156_525_000.0)
+        .within(10_000.0, &off_walvis_bay);
+    let channel_16 = Query::new()
+        .covering(156_800_000.0)
+        .within(10_000.0, &off_walvis_bay);
+    assert_eq!(catalog.query(&channel_70).len(), 1);
+    assert!(catalog.query(&channel_16).is_empty());
+    assert_eq!(
+        catalog
+            .query(
+                &Query::new()
+                    .covering(156_800_000.0)
+                    .within(50_000.0, &off_walvis_bay)
+            )
+            .len(),
+        1,
+        "about 39 km up the coast"
+    );
+}
+
+/// A segment without a datetime of its own is timed from the one before,
+/// across the gap its `core:global_index` records.
+#[test]
+fn an_untimed_segment_is_timed_from_the_one_before() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [0.5f32; 20];
+    let mut writer = RecordingWriter::new(&samples, 10.0);
+    let mut first = CaptureMetadata::new(0);
+    first.datetime = Some("2026-07-16T09:14:00.000Z".to_string());
+    let mut resumed = CaptureMetadata::new(10);
+    // Five minutes of samples were dropped before this segment.
+    resumed.global_index = Some(10 + 10 * 300);
+    writer.captures_mut().extend([first, resumed]);
+    writer
+        .to_file(dir.path().join("gappy"))
+        .expect("writing must succeed");
+    let catalog = catalog::scan(dir.path()).expect("scanning must succeed");
+
+    let segments = &catalog.recordings[0].segments;
+    assert_eq!(
+        segments[1].start_time.as_deref(),
+        Some("2026-07-16T09:19:01.000Z")
+    );
+    assert_eq!(
+        catalog.recordings[0].end_time.as_deref(),
+        Some("2026-07-16T09:19:02.000Z")
+    );
+    assert_eq!(
+        catalog
+            .query(&Query::new().between(at("09:15"), at("09:20")))
+            .len(),
+        1
+    );
+    assert!(catalog
+        .query(&Query::new().between(at("09:20"), at("10:00")))
+        .is_empty());
+}
+
+#[test]
+fn every_label_asked_for_must_be_there() {
+    let dir = TempDir::new().expect("a temp dir");
+    a_watch(&dir.path().join("watch"));
+    let catalog = catalog::scan(dir.path()).expect("scanning must succeed");
+
+    let both = Query::new().labelled("DSC").labelled("carrier");
+    assert_eq!(catalog.query(&both).len(), 1);
+    assert!(catalog.query(&both.labelled("lora")).is_empty());
+    assert_eq!(
+        catalog
+            .query(
+                &Query::new()
+                    .labelled("DSC")
+                    .between(at("09:14"), at("09:15"))
+            )
+            .len(),
+        1
+    );
+}
@@ -0,0 +1,291 @@
+//! The `sigmf` binary, driven as an operator drives it.
+//!
+//! Each subcommand is a thin layer over the library, so these tests are about
+//! the layer: that the right Recording is opened, that the output says what an
+//! operator needs, and above all that the exit code is the one a script
+//! branches on.
+
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use sigmf::num_complex::Complex;
+use sigmf::{Collection, RecordingWriter, SigMF};
+use tempfile::TempDir;
+
+use common::{assert_valid_file, watch};
+
+fn sigmf(args: &[&str], dir: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sigmf"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .expect("the binary runs")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn info_reports_the_datatype_and_a_duration_from_the_dataset() {
+    let dir = TempDir::new().expect("a temp dir");
+    watch(dir.path(), "watch");
+
+    let output = sigmf(&["info", "watch.sigmf-meta"], dir.path());
+    assert!(output.status.success());
+    let text = stdout(&output);
+    assert!(text.contains("rf32_le"), "{text}");
+    assert!(text.contains("samples      12"), "{text}");
+    assert!(text.contains("duration     3.000000 s"), "{text}");
+    assert!(text.contains("captures     0"), "{text}");
+}
+
+/// A Dataset that ends partway through a sample is reported, not rounded down
+/// to the samples it does hold.
+#[test]
+fn info_refuses_a_dataset_ending_partway_through_a_sample() {
+    let dir = TempDir::new().expect("a temp dir");
+    watch(dir.path(), "watch");
+    let mut data = fs::read(dir.path().join("watch.sigmf-data")).expect("the Dataset");
+    data.extend([0, 0]);
+    fs::write(dir.path().join("watch.sigmf-data"), data).expect("half a sample more");
+
+    let output = sigmf(&["info", "watch"], dir.path());
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("not a whole number"), "{stderr}");
+}
+
+/// Tab completion lands on either file, and typing the basename is shorter.
+#[test]
+fn a_recording_can_be_named_by_either_file_or_its_basename() {
+    let dir = TempDir::new().expect("a temp dir");
+    watch(dir.path(), "watch");
+
+    for name in ["watch.sigmf-meta", "watch.sigmf-data", "watch"] {
+        let output = sigmf(&["info", name], dir.path());
+        assert!(output.status.success(), "naming it {name} must work");
+    }
+}
+
+#[test]
+fn validate_exits_zero_for_a_conforming_recording() {
+    let dir = TempDir::new().expect("a temp dir");
+    watch(dir.path(), "watch");
+
+    let output = sigmf(&["validate", "watch"], dir.path());
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "watch: ok\n");
+}
+
+/// A broken MUST is an exit of 1, and the finding names where it is.
+#[test]
+fn validate_exits_one_and_reports_a_broken_rule() {
+    let dir = TempDir::new().expect("a temp dir");
+    watch(dir.path(), "watch");
+    fs::write(
+        dir.path().join("watch.sigmf-meta"),
+        r#"{
+            "global": {"core:datatype": "rf32_le", "core:version": "1.2.6", "core:sample_rate": 4},
+            "captures": [{"core:sample_start": 0, "core:datetime": "yesterday"}],
+            "annotations": []
+        }"#,
+    )
+    .expect("rewriting the metadata");
+
+    let output = sigmf(&["validate", "watch"], dir.path());
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        stdout(&output).contains("watch: error: captures/0/core:datetime"),
+        "{}",
+        stdout(&output)
+    );
+}
+
+/// A warning passes unless the operator asks for it not to.
+#[test]
+fn validate_fails_on_a_warning_only_when_strict() {
+    let dir = TempDir::new().expect("a temp dir");
+    watch(dir.path(), "watch");
+    fs::write(
+        dir.path().join("watch.sigmf-meta"),
+        r#"{"global": {"core:datatype": "rf32_le", "core:version": "1.2.6"}, "captures": [], "annotations": []}"#,
+    )
+    .expect("rewriting the metadata");
+
+    assert_eq!(
+        sigmf(&["validate", "watch"], dir.path()).status.code(),
+        Some(0)
+    );
+    assert_eq!(
+        sigmf(&["validate", "--strict", "watch"], dir.path())
+            .status
+            .code(),
+        Some(1)
+    );
+}
+
+#[test]
+fn verify_tells_a_matching_dataset_from_a_corrupted_one() {
+    let dir = TempDir::new().expect("a temp dir");
+    watch(dir.path(), "watch");
+
+    let output = sigmf(&["verify", "watch"], dir.path());
+    assert_eq!(output.status.code(), Some(0));
+
+    let data = dir.path().join("watch.sigmf-data");
+    let mut bytes = fs::read(&data).expect("the Dataset");
+    bytes[0] ^= 1;
+    fs::write(&data, bytes).expect("corrupting one bit");
+
+    let output = sigmf(&["verify", "watch"], dir.path());
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).starts_with("watch: FAILED"));
+}
+
+/// No hash is not a match and not a mismatch: the question cannot be asked.
+#[test]
+fn verify_exits_two_when_there_is_no_hash_to_check() {
+    let dir = TempDir::new().expect("a temp dir");
+    RecordingWriter::new(&[0.5f32; 4], 4.0)
+        .checksum(false)
+        .to_file(dir.path().join("unhashed"))
+        .expect("writing must succeed");
+
+    let output = sigmf(&["verify", "unhashed"], dir.path());
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("core:sha512"));
+}
+
+#[test]
+fn a_missing_recording_exits_two() {
+    let dir = TempDir::new().expect("a temp dir");
+    let output = sigmf(&["validate", "nowhere"], dir.path());
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn slice_writes_the_span_asked_for() {
+    let dir = TempDir::new().expect("a temp dir");
+    RecordingWriter::new(&[0i16, 1, 2, 3, 4, 5, 6, 7], 4.0)
+        .to_file(dir.path().join("watch"))
+        .expect("writing must succeed");
+
+    let output = sigmf(
+        &[
+            "slice", "watch", "--start", "2", "--count", "3", "-o", "burst",
+        ],
+        dir.path(),
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert_valid_file(&dir.path().join("burst.sigmf-meta"));
+    let burst = SigMF::from_file(dir.path().join("burst.sigmf-meta")).expect("the slice opens");
+    assert_eq!(burst.samples::<i16>().expect("reads back"), [2, 3, 4]);
+
+    let output = sigmf(
+        &["slice", "watch", "--start", "6", "--end", "9", "-o", "past"],
+        dir.path(),
+    );
+    assert_eq!(output.status.code(), Some(2), "there are only 8 samples");
+}
+
+#[test]
+fn convert_writes_the_datatype_asked_for() {
+    let dir = TempDir::new().expect("a temp dir");
+    RecordingWriter::new(&[Complex::new(16_384i16, -32_768)], 4.0)
+        .to_file(dir.path().join("vendor"))
+        .expect("writing must succeed");
+
+    let output = sigmf(
+        &[
+            "convert",
+            "vendor",
+            "--to",
+            "cf32_be",
+            "-o",
+            "ours.sigmf-meta",
+        ],
+        dir.path(),
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert_valid_file(&dir.path().join("ours.sigmf-meta"));
+    let ours = SigMF::from_file(dir.path().join("ours.sigmf-meta")).expect("the output opens");
+    assert_eq!(ours.metadata.global.datatype.to_string(), "cf32_be");
+    assert_eq!(
+        ours.samples::<Complex<f32>>().expect("reads back"),
+        [Complex::new(0.5, -1.0)]
+    );
+}
+
+/// Clipping is not failure — the file was written — but it is worth a line.
+#[test]
+fn convert_says_how_much_it_clipped() {
+    let dir = TempDir::new().expect("a temp dir");
+    RecordingWriter::new(&[0.5f32, 2.0, -3.0], 4.0)
+        .to_file(dir.path().join("floats"))
+        .expect("writing must succeed");
+
+    let output = sigmf(
+        &["convert", "floats", "--to", "ri8", "-o", "narrow"],
+        dir.path(),
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("2 components"));
+    assert_valid_file(&dir.path().join("narrow.sigmf-meta"));
+}
+
+#[test]
+fn pack_and_unpack_round_trip_an_archive() {
+    let dir = TempDir::new().expect("a temp dir");
+    watch(dir.path(), "watch");
+    RecordingWriter::new(&[1u8, 2, 3], 4.0)
+        .to_file(dir.path().join("other"))
+        .expect("writing must succeed");
+
+    let output = sigmf(
+        &["pack", "walvisbay.sigmf", "watch", "other.sigmf-data"],
+        dir.path(),
+    );
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = sigmf(&["unpack", "walvisbay.sigmf", "-C", "out"], dir.path());
+    assert_eq!(output.status.code(), Some(0));
+    for name in ["watch", "other"] {
+        let unpacked = dir.path().join(format!("out/walvisbay/{name}.sigmf-meta"));
+        assert_valid_file(&unpacked);
+        SigMF::from_file(&unpacked)
+            .expect("an unpacked Recording opens")
+            .verify_sha512()
+            .expect("and its Dataset survived intact");
+    }
+
+    let output = sigmf(&["unpack", "walvisbay.sigmf", "-C", "out"], dir.path());
+    assert_eq!(output.status.code(), Some(2), "unpacking never overwrites");
+}
+
+#[test]
+fn collect_writes_a_collection_and_marks_its_members() {
+    let dir = TempDir::new().expect("a temp dir");
+    for band in ["hf8", "hf16"] {
+        RecordingWriter::new(&[0.5f32; 4], 4.0)
+            .to_file(dir.path().join(band))
+            .expect("writing must succeed");
+    }
+
+    let output = sigmf(&["collect", "walvisbay", "hf8", "hf16"], dir.path());
+    assert_eq!(output.status.code(), Some(0));
+
+    let path = dir.path().join("walvisbay.sigmf-collection");
+    let collection = Collection::from_file(&path).expect("the Collection reads back");
+    let names: Vec<&str> = collection.streams.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["hf8", "hf16"]);
+    for band in ["hf8", "hf16"] {
+        assert_valid_file(&dir.path().join(format!("{band}.sigmf-meta")));
+    }
+    collection
+        .open_recordings(&path)
+        .expect("every member matches its hash");
+}
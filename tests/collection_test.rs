@@ -0,0 +1,182 @@
+//! Collections: which Recordings belong together, pinned by hash.
+
+use std::fs;
+
+use sigmf::{Collection, Error, MetadataError, RecordingWriter};
+use tempfile::TempDir;
+
+/// The shape of the file, which other tools read: an outer `collection`
+/// object, and a stream per Recording naming it and hashing its Metadata.
+#[test]
+fn a_collection_file_has_the_shape_the_specification_gives_it() {
+    let dir = TempDir::new().expect("a temp dir");
+    RecordingWriter::new(&[1.0f32], 32_000.0)
+        .to_file(dir.path().join("hf8"))
+        .expect("writing must succeed");
+    let path = dir.path().join("walvisbay.sigmf-collection");
+    Collection::create(&path, &[dir.path().join("hf8.sigmf-meta")])
+        .expect("collecting must succeed");
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).expect("the file")).expect("JSON");
+    let collection = &json["collection"];
+    assert_eq!(collection["core:version"], "1.2.6");
+    assert_eq!(collection["core:streams"][0]["name"], "hf8");
+
+    let meta = fs::read(dir.path().join("hf8.sigmf-meta")).expect("the member's Metadata");
+    use sha2::Digest;
+    let hash: String = sha2::Sha512::digest(&meta)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    assert_eq!(
+        collection["core:streams"][0]["hash"], hash,
+        "the hash is of the member's Metadata file as it now stands"
+    );
+}
+
+/// Editing a member after the fact is what the hash is there to catch.
+#[test]
+fn a_member_edited_since_is_caught() {
+    let dir = TempDir::new().expect("a temp dir");
+    RecordingWriter::new(&[1.0f32], 32_000.0)
+        .to_file(dir.path().join("hf8"))
+        .expect("writing must succeed");
+    let path = dir.path().join("walvisbay.sigmf-collection");
+    let collection = Collection::create(&path, &[dir.path().join("hf8.sigmf-meta")])
+        .expect("collecting must succeed");
+
+    let meta = dir.path().join("hf8.sigmf-meta");
+    let edited = fs::read_to_string(&meta)
+        .expect("the Metadata")
+        .replace("32000", "16000");
+    fs::write(&meta, edited).expect("editing it");
+
+    let err = collection
+        .open_recordings(&path)
+        .expect_err("the member no longer matches");
+    assert!(matches!(
+        err,
+        Error::Metadata(MetadataError::StreamHashMismatch { ref name }) if name == "hf8"
+    ));
+}
+
+/// A stream is named relative to the Collection, so a Recording outside its
+/// directory has no name to be given.
+#[test]
+fn a_recording_outside_the_collections_directory_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::create_dir(dir.path().join("sub")).expect("a directory");
+    RecordingWriter::new(&[1.0f32], 32_000.0)
+        .to_file(dir.path().join("hf8"))
+        .expect("writing must succeed");
+
+    Collection::create(
+        dir.path().join("sub/walvisbay.sigmf-collection"),
+        &[dir.path().join("hf8.sigmf-meta")],
+    )
+    .expect_err("hf8 is above the Collection");
+}
+
+/// Every Recording is checked before any is rewritten, so one that cannot be
+/// read leaves the others untouched.
+#[test]
+fn a_bad_recording_leaves_the_others_unchanged() {
+    let dir = TempDir::new().expect("a temp dir");
+    RecordingWriter::new(&[1.0f32], 32_000.0)
+        .to_file(dir.path().join("hf8"))
+        .expect("writing must succeed");
+    fs::write(dir.path().join("hf16.sigmf-meta"), "{ not json").expect("the bad file");
+    let before = fs::read(dir.path().join("hf8.sigmf-meta")).expect("the Metadata");
+
+    let path = dir.path().join("walvisbay.sigmf-collection");
+    Collection::create(
+        &path,
+        &[
+            dir.path().join("hf8.sigmf-meta"),
+            dir.path().join("hf16.sigmf-meta"),
+        ],
+    )
+    .expect_err("hf16 is not a Recording");
+
+    assert_eq!(
+        fs::read(dir.path().join("hf8.sigmf-meta")).expect("the Metadata"),
+        before
+    );
+    assert!(!path.exists());
+}
+
+/// A member's Metadata file is replaced, not truncated and rewritten in
+/// place: another name for the old file still holds the old document, and no
+/// temporary file is left beside the new one.
+#[test]
+fn a_member_is_replaced_whole() {
+    let dir = TempDir::new().expect("a temp dir");
+    RecordingWriter::new(&[1.0f32], 32_000.0)
+        .to_file(dir.path().join("hf8"))
+        .expect("writing must succeed");
+    let meta = dir.path().join("hf8.sigmf-meta");
+    let before = fs::read(&meta).expect("the Metadata");
+    fs::hard_link(&meta, dir.path().join("backup")).expect("a second name for it");
+
+    Collection::create(dir.path().join("walvisbay.sigmf-collection"), &[&meta])
+        .expect("collecting must succeed");
+
+    assert_eq!(
+        fs::read(dir.path().join("backup")).expect("the link"),
+        before
+    );
+    assert_ne!(fs::read(&meta).expect("the Metadata"), before);
+    let mut names: Vec<String> = fs::read_dir(dir.path())
+        .expect("the directory")
+        .map(|entry| {
+            entry
+                .expect("an entry")
+                .file_name()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            "backup",
+            "hf8.sigmf-data",
+            "hf8.sigmf-meta",
+            "walvisbay.sigmf-collection"
+        ]
+    );
+}
+
+/// A stream name is a path below the Collection's directory; one that is
+/// absolute, or climbs out with `..`, is refused rather than followed.
+#[test]
+fn a_stream_named_outside_the_directory_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::create_dir(dir.path().join("sub")).expect("a directory");
+    RecordingWriter::new(&[1.0f32], 32_000.0)
+        .to_file(dir.path().join("hf8"))
+        .expect("writing must succeed");
+    let outside = dir.path().join("hf8").to_string_lossy().into_owned();
+
+    for name in ["../hf8", outside.as_str()] {
+        let path = dir.path().join("sub/walvisbay.sigmf-collection");
+        let json = serde_json::json!({
+            "collection": {
+                "core:version": "1.2.6",
+                "core:streams": [{ "name": name, "hash": "00" }]
+            }
+        });
+        fs::write(&path, json.to_string()).expect("the Collection");
+
+        let err = Collection::from_file(&path)
+            .expect("a Collection")
+            .open_recordings(&path)
+            .expect_err("the stream is outside the Collection's directory");
+        assert!(
+            matches!(err, Error::Io { path: ref at, .. } if *at == path),
+            "{name}: {err}"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Fixtures and assertions shared by the integration tests.
+//!
+//! Every file under `tests/` is a crate of its own and uses only some of
+//! these, so the rest are dead code to it.
+
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde_json::Value;
+use sigmf::{AnnotationMetadata, CaptureMetadata, RecordingWriter, SigMF};
+
+/// An annotation, written as the JSON a document would hold it in.
+pub fn annotation(value: Value) -> AnnotationMetadata {
+    serde_json::from_value(value).expect("the annotation literal must deserialize")
+}
+
+/// Three seconds of real samples at 4 Sa/s, and nothing else, written as
+/// `dir/name`.
+pub fn watch(dir: &Path, name: &str) -> SigMF {
+    RecordingWriter::new(&[0.5f32; 12], 4.0)
+        .to_file(dir.join(name))
+        .expect("writing must succeed")
+}
+
+/// The two timed segments of a watch that retuned at sample `retune`: 2 187.5
+/// kHz from 09:14:22, then 8 414.5 kHz from 09:15:00.
+pub fn retuned_captures(retune: u64) -> [CaptureMetadata; 2] {
+    let mut first = CaptureMetadata::new(0);
+    first.frequency = Some(2_187_500.0);
+    first.datetime = Some("2026-07-16T09:14:22.000Z".to_string());
+    let mut second = CaptureMetadata::new(retune);
+    second.frequency = Some(8_414_500.0);
+    second.datetime = Some("2026-07-16T09:15:00.000Z".to_string());
+    [first, second]
+}
+
+/// The vendored SigMF schema, `tests/spec/sigmf-schema.json`.
+pub fn schema() -> &'static Value {
+    static SCHEMA: OnceLock<Value> = OnceLock::new();
+    SCHEMA.get_or_init(|| {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/spec/sigmf-schema.json");
+        let raw = fs::read_to_string(&path).expect("the vendored schema");
+        serde_json::from_str(&raw).expect("the vendored schema is JSON")
+    })
+}
+
+fn validator() -> &'static jsonschema::Validator {
+    static VALIDATOR: OnceLock<jsonschema::Validator> = OnceLock::new();
+    VALIDATOR.get_or_init(|| {
+        jsonschema::validator_for(schema())
+            .expect("the vendored schema must itself be a valid JSON Schema")
+    })
+}
+
+/// Assert `instance` satisfies the SigMF schema, reporting *every* violation with
+/// its JSON path rather than just the first.
+pub fn assert_valid(instance: &Value, what: &str) {
+    let errors: Vec<String> = validator()
+        .iter_errors(instance)
+        .map(|e| format!("  at {}: {e}", e.instance_path()))
+        .collect();
+    assert!(
+        errors.is_empty(),
+        "{what} does not satisfy the SigMF schema:\n{}",
+        errors.join("\n")
+    );
+}
+
+/// [`assert_valid`] for the Metadata file at `path`.
+pub fn assert_valid_file(path: &Path) {
+    let instance: Value =
+        serde_json::from_str(&fs::read_to_string(path).expect("the written Metadata"))
+            .expect("the written Metadata is JSON");
+    assert_valid(&instance, &path.display().to_string());
+}
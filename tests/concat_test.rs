@@ -0,0 +1,191 @@
+//! Joining consecutive Recordings into one.
+//!
+//! Each input's indices count from its own `core:offset`; in the joined
+//! Recording they must count from the first input's, and the first Global must
+//! be able to speak for every input.
+
+mod common;
+
+use common::annotation;
+use serde_json::json;
+use sigmf::{CaptureMetadata, Error, MetadataError, RecordingWriter, SigMF};
+use std::path::Path;
+use tempfile::TempDir;
+
+/// A file of a recorder that rolls over, holding `samples` from stream index
+/// `offset` on, tuned to `frequency`, with one annotation running to its end.
+fn rollover(dir: &Path, name: &str, samples: &[i16], offset: u64, frequency: f64) -> SigMF {
+    let mut writer = RecordingWriter::new(samples, 8_000.0);
+    writer.global_mut().offset = Some(offset);
+    writer.global_mut().hw = Some("WiNRADiO G31DDC".to_string());
+    let mut capture = CaptureMetadata::new(offset);
+    capture.frequency = Some(frequency);
+    writer.captures_mut().push(capture);
+    writer.annotations_mut().push(annotation(
+        json!({"core:sample_start": offset + 1, "core:label": name}),
+    ));
+    writer
+        .to_file(dir.join(name))
+        .expect("writing an input must succeed")
+}
+
+#[test]
+fn the_joined_dataset_is_the_inputs_end_to_end() {
+    let dir = TempDir::new().expect("a temp dir");
+    let a = rollover(dir.path(), "a", &[1, 2, 3], 0, 16_804_500.0);
+    let b = rollover(dir.path(), "b", &[4, 5], 3, 16_804_500.0);
+    let c = rollover(dir.path(), "c", &[6], 5, 16_804_500.0);
+
+    let joined = a
+        .concat(&[&b, &c], dir.path().join("joined"))
+        .expect("joining must succeed");
+    assert_eq!(
+        joined.samples::<i16>().expect("the join reads back"),
+        [1, 2, 3, 4, 5, 6]
+    );
+
+    let reopened =
+        SigMF::from_file(dir.path().join("joined.sigmf-meta")).expect("the join reopens");
+    assert_eq!(
+        reopened.metadata.global.sha512,
+        joined.metadata.global.sha512
+    );
+    assert!(joined.metadata.global.sha512.is_some(), "and is hashed");
+}
+
+/// Each input keeps its own capture, moved to where its samples now start; a
+/// gap between files stays on record, and contiguous files need no mention.
+#[test]
+fn each_input_keeps_its_captures_and_its_place_in_the_stream() {
+    let dir = TempDir::new().expect("a temp dir");
+    let a = rollover(dir.path(), "a", &[1, 2, 3], 100, 16_804_500.0);
+    let b = rollover(dir.path(), "b", &[4, 5], 103, 8_414_500.0);
+    let c = rollover(dir.path(), "c", &[6], 200, 8_414_500.0);
+
+    let joined = a
+        .concat(&[&b, &c], dir.path().join("joined"))
+        .expect("joining must succeed");
+    let captures: Vec<(u64, Option<f64>, Option<u64>)> = joined
+        .metadata
+        .captures
+        .iter()
+        .map(|c| (c.sample_start, c.frequency, c.global_index))
+        .collect();
+
+    assert_eq!(joined.metadata.global.offset, Some(100));
+    assert_eq!(
+        captures,
+        [
+            (100, Some(16_804_500.0), None),
+            (103, Some(8_414_500.0), None),
+            (105, Some(8_414_500.0), Some(200)),
+        ]
+    );
+}
+
+/// Files that never said where they sat in a stream are taken to be
+/// contiguous: no `core:global_index` is made up for them, and so no
+/// discontinuity either.
+#[test]
+fn inputs_without_a_place_in_a_stream_join_contiguously() {
+    let dir = TempDir::new().expect("a temp dir");
+    let write = |name: &str, samples: &[i16]| {
+        let mut writer = RecordingWriter::new(samples, 8_000.0);
+        writer.captures_mut().push(CaptureMetadata::new(0));
+        writer
+            .to_file(dir.path().join(name))
+            .expect("writing an input must succeed")
+    };
+    let a = write("a", &[1, 2, 3]);
+    let b = write("b", &[4, 5, 6]);
+
+    let joined = a
+        .concat(&[&b], dir.path().join("joined"))
+        .expect("joining must succeed");
+    let captures: Vec<(u64, Option<u64>)> = joined
+        .metadata
+        .captures
+        .iter()
+        .map(|c| (c.sample_start, c.global_index))
+        .collect();
+    assert_eq!(joined.metadata.global.offset, None);
+    assert_eq!(captures, [(0, None), (3, None)]);
+    assert_eq!(joined.metadata.discontinuities(), []);
+}
+
+/// An annotation that ran to the end of its file ends there still, unless its
+/// file is the last.
+#[test]
+fn annotations_move_with_their_samples() {
+    let dir = TempDir::new().expect("a temp dir");
+    let a = rollover(dir.path(), "a", &[1, 2, 3], 0, 16_804_500.0);
+    let b = rollover(dir.path(), "b", &[4, 5], 3, 16_804_500.0);
+
+    let joined = a
+        .concat(&[&b], dir.path().join("joined"))
+        .expect("joining must succeed");
+    let spans: Vec<(u64, Option<u64>)> = joined
+        .metadata
+        .annotations
+        .iter()
+        .map(|a| (a.sample_start, a.sample_count))
+        .collect();
+    assert_eq!(spans, [(1, Some(2)), (4, None)]);
+}
+
+/// The error names the input, the field, and both values — enough to decide
+/// whether the difference is real or one to edit away.
+#[test]
+fn inputs_whose_globals_disagree_are_refused_and_the_field_is_named() {
+    let dir = TempDir::new().expect("a temp dir");
+    let a = rollover(dir.path(), "a", &[1, 2, 3], 0, 16_804_500.0);
+    let mut b = rollover(dir.path(), "b", &[4, 5], 3, 16_804_500.0);
+    b.metadata.global.sample_rate = Some(16_000.0);
+
+    let err = a
+        .concat(&[&b], dir.path().join("joined"))
+        .expect_err("two sample rates cannot share a Global");
+    match err {
+        Error::Metadata(MetadataError::ConflictingGlobals {
+            index,
+            key,
+            expected,
+            found,
+        }) => {
+            assert_eq!(index, 1);
+            assert_eq!(key, "core:sample_rate");
+            assert_eq!(expected, "8000.0");
+            assert_eq!(found, "16000.0");
+        }
+        other => panic!("expected ConflictingGlobals, got {other:?}"),
+    }
+    assert!(!dir.path().join("joined.sigmf-data").exists());
+
+    b.metadata.global.sample_rate = Some(8_000.0);
+    b.metadata.global.hw = None;
+    let err = a
+        .concat(&[&b], dir.path().join("joined"))
+        .expect_err("a field present in one and absent in the other disagrees too");
+    assert!(
+        err.to_string().contains("`core:hw` absent"),
+        "the message must say which side lacks it: {err}"
+    );
+}
+
+/// Joining differently typed Datasets would reinterpret one of them.
+#[test]
+fn inputs_of_different_datatypes_are_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    let a = rollover(dir.path(), "a", &[1, 2, 3], 0, 16_804_500.0);
+    let b = RecordingWriter::new(&[4.0f32, 5.0], 8_000.0)
+        .to_file(dir.path().join("b"))
+        .expect("writing an input must succeed");
+
+    let err = a
+        .concat(&[&b], dir.path().join("joined"))
+        .expect_err("ri16 and rf32 cannot share a Dataset");
+    assert!(matches!(
+        err,
+        Error::Metadata(MetadataError::ConflictingGlobals { ref key, .. }) if key == "core:datatype"
+    ));
+}
@@ -0,0 +1,151 @@
+//! Breaks in the stream between Captures segments: dropped and repeated
+//! samples, and clocks that jumped.
+
+use serde_json::json;
+use sigmf::num_complex::Complex;
+use sigmf::{CaptureMetadata, DiscontinuityKind, Metadata, RecordingWriter};
+use tempfile::TempDir;
+
+fn metadata(sample_rate: Option<f64>, captures: serde_json::Value) -> Metadata {
+    let mut document = json!({
+        "global": { "core:datatype": "ci16_le", "core:version": "1.2.6" },
+        "captures": captures,
+        "annotations": []
+    });
+    if let Some(rate) = sample_rate {
+        document["global"]["core:sample_rate"] = json!(rate);
+    }
+    serde_json::from_value(document).expect("a valid document")
+}
+
+fn kinds(metadata: &Metadata) -> Vec<(usize, DiscontinuityKind)> {
+    metadata
+        .discontinuities()
+        .into_iter()
+        .map(|d| (d.capture, d.kind))
+        .collect()
+}
+
+/// Segments that follow on, in index and in time, are not reported — nor is
+/// a time written too coarsely to agree to the sample.
+#[test]
+fn a_continuous_stream_has_none() {
+    let metadata = metadata(
+        Some(3.0),
+        json!([
+            { "core:sample_start": 0, "core:datetime": "2026-10-18T12:00:00Z" },
+            { "core:sample_start": 3, "core:datetime": "2026-10-18T12:00:01Z" },
+            { "core:sample_start": 4, "core:datetime": "2026-10-18T12:00:01.3Z" },
+            { "core:sample_start": 5 },
+            { "core:sample_start": 7, "core:datetime": "2026-10-18T12:00:02.333333Z" }
+        ]),
+    );
+    assert_eq!(kinds(&metadata), []);
+}
+
+/// A `core:global_index` behind the stream is an overlap, and the times are
+/// then held to the stream as declared.
+#[test]
+fn a_global_index_behind_the_stream_is_an_overlap() {
+    let metadata = metadata(
+        Some(10.0),
+        json!([
+            { "core:sample_start": 0, "core:datetime": "2026-10-18T12:00:00.0Z" },
+            {
+                "core:sample_start": 20,
+                "core:global_index": 15,
+                "core:datetime": "2026-10-18T12:00:01.5Z"
+            }
+        ]),
+    );
+    assert_eq!(
+        kinds(&metadata),
+        [(1, DiscontinuityKind::Overlap { samples: 5 })]
+    );
+}
+
+/// A time that jumps with no `core:global_index` to account for it is a
+/// clock step, either way; without a sample rate, times are not checked.
+#[test]
+fn a_time_jump_the_indices_do_not_explain_is_a_clock_step() {
+    let captures = json!([
+        { "core:sample_start": 0, "core:datetime": "2026-10-18T12:00:00.000Z" },
+        { "core:sample_start": 100, "core:datetime": "2026-10-18T12:00:00.350Z" },
+        { "core:sample_start": 200, "core:datetime": "2026-10-18T12:00:00.400Z" }
+    ]);
+    let found = kinds(&metadata(Some(1_000.0), captures.clone()));
+    let [(1, DiscontinuityKind::ClockStep { seconds: forward }), (2, DiscontinuityKind::ClockStep { seconds: back })] =
+        found[..]
+    else {
+        panic!("two clock steps: {found:?}");
+    };
+    assert!((forward - 0.25).abs() < 1e-9, "{forward}");
+    assert!((back + 0.05).abs() < 1e-9, "measured from the step: {back}");
+
+    assert_eq!(kinds(&metadata(None, captures)), []);
+}
+
+/// A slice of a Recording with a gap in it keeps the gap, and it is reported
+/// at the slice's own segment.
+#[test]
+fn a_slice_keeps_its_sources_gap() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples: Vec<Complex<i16>> = (0..20).map(|i| Complex::new(i, 0)).collect();
+    let mut writer = RecordingWriter::new(&samples, 1e3);
+    let mut resumed = CaptureMetadata::new(10);
+    resumed.global_index = Some(42);
+    writer
+        .captures_mut()
+        .extend([CaptureMetadata::new(0), resumed]);
+    let source = writer
+        .to_file(dir.path().join("source"))
+        .expect("writing must succeed");
+    assert_eq!(
+        kinds(&source.metadata),
+        [(1, DiscontinuityKind::Gap { samples: 32 })]
+    );
+
+    let slice = source
+        .slice(5..15, dir.path().join("slice"))
+        .expect("slicing must succeed");
+    let found = slice.metadata.discontinuities();
+    assert_eq!(found.len(), 1, "{found:?}");
+    assert_eq!(found[0].sample_start, 10, "sample indices survive a slice");
+    assert_eq!(found[0].kind, DiscontinuityKind::Gap { samples: 32 });
+}
+
+/// Stream indices at the top of the range are measured without wrapping
+/// round; a segment whose samples would run past `u64::MAX` is taken to end
+/// there, as `global_index` places them.
+#[test]
+fn indices_near_the_top_of_the_range_do_not_overflow() {
+    let top = |captures| kinds(&metadata(None, captures));
+    assert_eq!(
+        top(json!([
+            { "core:sample_start": 0, "core:global_index": u64::MAX - 10 },
+            { "core:sample_start": 5, "core:global_index": u64::MAX - 5 }
+        ])),
+        []
+    );
+    assert_eq!(
+        top(json!([
+            { "core:sample_start": 0, "core:global_index": u64::MAX - 10 },
+            { "core:sample_start": 5, "core:global_index": u64::MAX }
+        ])),
+        [(1, DiscontinuityKind::Gap { samples: 5 })]
+    );
+    assert_eq!(
+        top(json!([
+            { "core:sample_start": 0, "core:global_index": u64::MAX - 5 },
+            { "core:sample_start": 10, "core:global_index": u64::MAX }
+        ])),
+        []
+    );
+    assert_eq!(
+        top(json!([
+            { "core:sample_start": 0, "core:global_index": u64::MAX - 5 },
+            { "core:sample_start": 10, "core:global_index": 0 }
+        ])),
+        [(1, DiscontinuityKind::Overlap { samples: u64::MAX })]
+    );
+}
@@ -245,3 +245,32 @@ fn the_error_types_are_send_and_sync_and_static() {
     usable_with_anyhow::<MetadataError>();
     usable_with_anyhow::<ParseDataFormatError>();
 }
+
+/// A Recording requiring extensions the reader lacks is refused with every one of
+/// them named, and only the required ones.
+///
+/// The fixture declares `antenna` as optional and `capture_details` as required.
+/// An empty registry understands neither, and the error still names only
+/// `capture_details`: an optional extension is never a reason to refuse.
+#[test]
+fn a_required_extension_the_reader_lacks_is_named_in_the_error() {
+    let path = "tests/sigmf_test_files/extensions.sigmf-meta";
+
+    let err = SigMF::from_file_supporting(path, &ExtensionRegistry::new())
+        .expect_err("capture_details is required and nothing supports it");
+    match &err {
+        Error::Metadata(MetadataError::UnsupportedRequiredExtensions(unsupported)) => {
+            let names: Vec<&str> = unsupported.iter().map(|e| e.name.as_str()).collect();
+            assert_eq!(names, ["capture_details"]);
+        }
+        other => panic!("expected UnsupportedRequiredExtensions, got {other:?}"),
+    }
+    assert!(
+        err.to_string().contains("capture_details"),
+        "the rendered message names the namespace: {err}"
+    );
+
+    let registry = ExtensionRegistry::new().with_namespace("capture_details");
+    SigMF::from_file_supporting(path, &registry)
+        .expect("a reader supporting the required extension may open the Recording");
+}
@@ -0,0 +1,197 @@
+//! Survey Recordings out to map layers: where each segment was made, and what
+//! was heard there.
+
+use std::fs;
+use std::path::Path;
+
+use serde_json::{json, Value};
+use sigmf::{geo, CaptureMetadata, Error, Geolocation, RecordingWriter};
+use tempfile::TempDir;
+
+/// A drive past Walvis Bay: three segments, the first two placed by GNSS —
+/// one with a satellite count — and the third, after the fix was lost, not.
+fn a_drive(basename: &Path) {
+    let samples = [0.5f32, -0.5, 0.25];
+    let mut writer = RecordingWriter::new(&samples, 32_000.0);
+    writer.global_mut().recorder = Some("survey-van".to_string());
+
+    let mut first = CaptureMetadata::new(0);
+    first.frequency = Some(156_525_000.0);
+    first.datetime = Some("2026-07-16T09:14:22.000Z".to_string());
+    let mut fix = Geolocation::new(14.5053, -22.9576);
+    fix.altitude = Some(7.0);
+    fix.other.insert("satellites".to_string(), json!(9));
+    first.geolocation = Some(fix);
+    let mut second = CaptureMetadata::new(1);
+    second.frequency = Some(156_525_000.0);
+    second.geolocation = Some(Geolocation::new(14.5210, -22.9480));
+    let third = CaptureMetadata::new(2);
+    writer.captures_mut().extend([first, second, third]);
+
+    writer.to_file(basename).expect("writing must succeed");
+}
+
+/// A fixed site, positioned once in the Global object.
+fn a_fixed_site(basename: &Path) {
+    let samples = [1.0f32];
+    let mut writer = RecordingWriter::new(&samples, 32_000.0);
+    writer.global_mut().geolocation = Some(Geolocation::new(18.4241, -33.9249));
+    writer.captures_mut().push(CaptureMetadata::new(0));
+    writer.to_file(basename).expect("writing must succeed");
+}
+
+fn read_json(path: &Path) -> Value {
+    serde_json::from_str(&fs::read_to_string(path).expect("the export")).expect("JSON")
+}
+
+#[test]
+fn each_geolocated_segment_is_a_feature() {
+    let dir = TempDir::new().expect("a temp dir");
+    a_drive(&dir.path().join("drive"));
+
+    geo::export_geojson(
+        &[dir.path().join("drive.sigmf-meta")],
+        dir.path().join("drive.geojson"),
+    )
+    .expect("exporting must succeed");
+
+    let map = read_json(&dir.path().join("drive.geojson"));
+    assert_eq!(map["type"], "FeatureCollection");
+    let features = map["features"].as_array().expect("features");
+    assert_eq!(features.len(), 2, "the third segment has no position");
+    assert_eq!(
+        features[0]["geometry"],
+        json!({"type": "Point", "coordinates": [14.5053, -22.9576, 7.0], "satellites": 9}),
+        "the position is as the Recording has it, foreign members included"
+    );
+    let properties = &features[0]["properties"];
+    assert_eq!(properties["capture"], 0);
+    assert_eq!(properties["frequency"], 156_525_000.0);
+    assert_eq!(properties["datetime"], "2026-07-16T09:14:22.000Z");
+    assert_eq!(properties["recorder"], "survey-van");
+    assert_eq!(features[1]["properties"]["sample_start"], 1);
+    assert!(features[1]["properties"].get("datetime").is_none());
+}
+
+/// A directory is walked to any depth, in order of path, and a segment with
+/// no position of its own falls back to the Global one.
+#[test]
+fn a_directory_of_recordings_is_one_map() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::create_dir(dir.path().join("day2")).expect("a subdirectory");
+    a_drive(&dir.path().join("day2").join("drive"));
+    a_fixed_site(&dir.path().join("capetown"));
+
+    geo::export_geojson(&[dir.path()], dir.path().join("survey.geojson"))
+        .expect("exporting must succeed");
+
+    let map = read_json(&dir.path().join("survey.geojson"));
+    let coordinates: Vec<&Value> = map["features"]
+        .as_array()
+        .expect("features")
+        .iter()
+        .map(|feature| &feature["geometry"]["coordinates"][0])
+        .collect();
+    assert_eq!(
+        coordinates,
+        [&json!(18.4241), &json!(14.5053), &json!(14.521)]
+    );
+}
+
+#[test]
+fn kml_has_a_placemark_per_segment() {
+    let dir = TempDir::new().expect("a temp dir");
+    a_drive(&dir.path().join("drive"));
+
+    geo::export_kml(
+        &[dir.path().join("drive.sigmf-meta")],
+        dir.path().join("drive.kml"),
+    )
+    .expect("exporting must succeed");
+
+    let kml = fs::read_to_string(dir.path().join("drive.kml")).expect("the export");
+    assert!(kml.starts_with("<?xml"));
+    assert_eq!(kml.matches("<Placemark>").count(), 2);
+    assert!(kml.contains("<coordinates>14.5053,-22.9576,7</coordinates>"));
+    assert!(kml.contains("<coordinates>14.521,-22.948</coordinates>"));
+    assert!(kml.contains("<TimeStamp><when>2026-07-16T09:14:22.000Z</when></TimeStamp>"));
+    assert!(kml.contains("<Data name=\"recorder\"><value>survey-van</value></Data>"));
+}
+
+/// Text from a Recording is escaped, not spliced into the markup.
+#[test]
+fn kml_escapes_what_it_quotes() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [1.0f32];
+    let mut writer = RecordingWriter::new(&samples, 32_000.0);
+    writer.global_mut().recorder = Some("<R&D> \"van\"".to_string());
+    writer.global_mut().geolocation = Some(Geolocation::new(0.5, 0.5));
+    writer.captures_mut().push(CaptureMetadata::new(0));
+    writer
+        .to_file(dir.path().join("lab"))
+        .expect("writing must succeed");
+
+    geo::export_kml(&[dir.path()], dir.path().join("lab.kml")).expect("exporting must succeed");
+
+    let kml = fs::read_to_string(dir.path().join("lab.kml")).expect("the export");
+    assert!(kml.contains("<value>&lt;R&amp;D&gt; &quot;van&quot;</value>"));
+}
+
+/// A Recording without captures has one implicit segment from sample 0,
+/// and it is placed at the Global position.
+#[test]
+fn a_recording_without_captures_is_still_on_the_map() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [1.0f32];
+    let mut writer = RecordingWriter::new(&samples, 32_000.0);
+    writer.global_mut().geolocation = Some(Geolocation::new(18.4241, -33.9249));
+    let recording = writer
+        .to_file(dir.path().join("mast"))
+        .expect("writing must succeed");
+    assert!(recording.metadata.captures.is_empty());
+
+    geo::export_geojson(&[dir.path()], dir.path().join("mast.geojson"))
+        .expect("exporting must succeed");
+
+    let map = read_json(&dir.path().join("mast.geojson"));
+    let features = map["features"].as_array().expect("features");
+    assert_eq!(features.len(), 1);
+    assert_eq!(
+        features[0]["geometry"]["coordinates"],
+        json!([18.4241, -33.9249])
+    );
+    assert_eq!(features[0]["properties"]["capture"], 0);
+    assert_eq!(features[0]["properties"]["sample_start"], 0);
+}
+
+/// One unreadable Metadata file in a directory is reported, and the rest of
+/// the directory is still exported.
+#[test]
+fn an_unreadable_file_in_a_directory_is_reported() {
+    let dir = TempDir::new().expect("a temp dir");
+    a_fixed_site(&dir.path().join("capetown"));
+    fs::write(dir.path().join("broken.sigmf-meta"), "{ not json").expect("the bad file");
+
+    let skipped = geo::export_kml(&[dir.path()], dir.path().join("sites.kml"))
+        .expect("exporting must succeed");
+
+    assert_eq!(skipped.len(), 1);
+    let (path, err) = &skipped[0];
+    assert!(path.ends_with("broken.sigmf-meta"));
+    assert!(matches!(err, Error::Json(_)), "{err}");
+    let kml = fs::read_to_string(dir.path().join("sites.kml")).expect("the export");
+    assert_eq!(kml.matches("<Placemark>").count(), 1);
+}
+
+#[test]
+fn a_missing_source_is_named() {
+    let dir = TempDir::new().expect("a temp dir");
+
+    let err = geo::export_geojson(
+        &[dir.path().join("nowhere.sigmf-meta")],
+        dir.path().join("map.geojson"),
+    )
+    .expect_err("there is no Recording");
+    assert!(matches!(err, Error::Io { ref path, .. } if path.ends_with("nowhere.sigmf-meta")));
+    assert!(!dir.path().join("map.geojson").exists());
+}
@@ -0,0 +1,137 @@
+//! Translating between Dataset and stream sample indices, through
+//! `core:offset` and `core:global_index`.
+
+use serde_json::json;
+use sigmf::num_complex::Complex;
+use sigmf::{CaptureMetadata, Metadata, RecordingWriter};
+use tempfile::TempDir;
+
+fn metadata(global: serde_json::Value, captures: serde_json::Value) -> Metadata {
+    let mut document = json!({
+        "global": { "core:datatype": "cf32_le", "core:version": "1.2.6" },
+        "captures": captures,
+        "annotations": []
+    });
+    document["global"]
+        .as_object_mut()
+        .expect("an object")
+        .extend(global.as_object().expect("an object").clone());
+    serde_json::from_value(document).expect("a valid document")
+}
+
+/// With neither field, the two index spaces are the same one.
+#[test]
+fn without_offset_or_global_index_the_indices_agree() {
+    let metadata = metadata(json!({}), json!([]));
+    for sample in [0, 1, 1_000_000] {
+        assert_eq!(metadata.global_index(sample), sample);
+        assert_eq!(metadata.dataset_index(sample), Some(sample));
+    }
+}
+
+/// `core:offset` shifts every sample, and the stream before it is not in
+/// the Recording.
+#[test]
+fn offset_places_a_recording_that_began_mid_stream() {
+    let metadata = metadata(
+        json!({ "core:offset": 5_000 }),
+        json!([{ "core:sample_start": 5_000 }, { "core:sample_start": 5_010 }]),
+    );
+    assert_eq!(metadata.global_index(0), 5_000);
+    assert_eq!(metadata.global_index(12), 5_012);
+    assert_eq!(metadata.dataset_index(5_012), Some(12));
+    assert_eq!(metadata.dataset_index(4_999), None);
+}
+
+/// A segment's `core:global_index` opens a gap, and a stream index in the
+/// gap has no sample; the translation round-trips everywhere else.
+#[test]
+fn global_index_records_a_gap() {
+    let metadata = metadata(
+        json!({ "core:offset": 100 }),
+        json!([
+            { "core:sample_start": 100 },
+            { "core:sample_start": 104, "core:global_index": 110 },
+            { "core:sample_start": 106 }
+        ]),
+    );
+    let globals: Vec<u64> = (0..8).map(|s| metadata.global_index(s)).collect();
+    assert_eq!(globals, [100, 101, 102, 103, 110, 111, 106, 107]);
+
+    for sample in [0, 3, 4, 5] {
+        assert_eq!(
+            metadata.dataset_index(metadata.global_index(sample)),
+            Some(sample)
+        );
+    }
+    for dropped in 104..106 {
+        assert_eq!(metadata.dataset_index(dropped), None, "{dropped}");
+    }
+    assert_eq!(
+        metadata.dataset_index(106),
+        Some(6),
+        "the third segment has no `core:global_index`, so starts at its \
+         `core:sample_start`"
+    );
+}
+
+/// A slice records where it was cut from, so the same instant has the same
+/// stream index in both Recordings — the point of the translation.
+#[test]
+fn a_slice_lines_up_with_its_source() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples: Vec<Complex<f32>> = (0..20).map(|i| Complex::new(i as f32, 0.0)).collect();
+    let mut writer = RecordingWriter::new(&samples, 1e3);
+    let mut resumed = CaptureMetadata::new(1_008);
+    resumed.global_index = Some(1_050);
+    writer.global_mut().offset = Some(1_000);
+    writer
+        .captures_mut()
+        .extend([CaptureMetadata::new(1_000), resumed]);
+    let source = writer
+        .to_file(dir.path().join("source"))
+        .expect("writing must succeed");
+
+    let slice = source
+        .slice(5..15, dir.path().join("slice"))
+        .expect("slicing must succeed");
+
+    for sample in 0..10 {
+        let global = slice.metadata.global_index(sample);
+        assert_eq!(global, source.metadata.global_index(5 + sample));
+        assert_eq!(source.metadata.dataset_index(global), Some(5 + sample));
+    }
+}
+
+/// Indices at the top of the range are not wrapped round to the bottom: a
+/// stream index past `u64::MAX` is given as `u64::MAX`, and a Dataset index
+/// past it does not exist.
+#[test]
+fn indices_near_the_top_of_the_range_saturate() {
+    let near = u64::MAX - 10;
+    let high = metadata(
+        json!({ "core:offset": 10 }),
+        json!([{ "core:sample_start": 10, "core:global_index": near }]),
+    );
+    assert_eq!(high.global_index(2), u64::MAX - 8);
+    assert_eq!(
+        high.global_index(12),
+        u64::MAX,
+        "its segment starts 10 below the top"
+    );
+    assert_eq!(high.global_index(u64::MAX), u64::MAX);
+
+    assert_eq!(high.dataset_index(u64::MAX - 8), Some(2));
+    assert_eq!(high.dataset_index(u64::MAX - 4), Some(6));
+    assert_eq!(high.dataset_index(5), None);
+
+    let wide = metadata(
+        json!({}),
+        json!([
+            { "core:sample_start": 0, "core:global_index": u64::MAX - 1 },
+            { "core:sample_start": near, "core:global_index": 0 }
+        ]),
+    );
+    assert_eq!(wide.dataset_index(10), Some(u64::MAX));
+    assert_eq!(wide.dataset_index(11), None, "past the last Dataset index");
+}
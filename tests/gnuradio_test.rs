@@ -0,0 +1,239 @@
+//! GNU Radio File Meta Sink headers, detached and inline.
+//!
+//! There is no GNU Radio here to write the headers, so [`header`] serializes
+//! them as `pmt_serialize.cc` does, and checks its own output against the one
+//! length GNU Radio fixes: 149 bytes for the first dictionary.
+
+use std::fs;
+
+use sigmf::num_complex::Complex;
+use sigmf::{gnuradio, SigMF};
+use tempfile::TempDir;
+
+fn symbol(out: &mut Vec<u8>, name: &str) {
+    out.push(0x02);
+    out.extend((name.len() as u16).to_be_bytes());
+    out.extend(name.as_bytes());
+}
+
+fn double(value: f64) -> Vec<u8> {
+    let mut out = vec![0x04];
+    out.extend(value.to_be_bytes());
+    out
+}
+
+fn long(value: i32) -> Vec<u8> {
+    let mut out = vec![0x03];
+    out.extend(value.to_be_bytes());
+    out
+}
+
+fn uint64(value: u64) -> Vec<u8> {
+    let mut out = vec![0x0b];
+    out.extend(value.to_be_bytes());
+    out
+}
+
+/// A dictionary as GNU Radio serializes one: `dict_add` conses each entry on
+/// to the front, so the last added comes first.
+fn dictionary(entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (key, value) in entries.iter().rev() {
+        out.extend([0x07, 0x07]);
+        symbol(&mut out, key);
+        out.extend(value);
+    }
+    out.push(0x06);
+    out
+}
+
+/// One segment's header: `cf32` items at `rate`, `bytes` of them, starting at
+/// `seconds` past the epoch, tuned to `frequency`.
+fn header(rate: f64, seconds: u64, fraction: f64, bytes: u64, frequency: f64) -> Vec<u8> {
+    let extras = dictionary(&[("rx_freq", double(frequency))]);
+    let mut time = vec![0x0c];
+    time.extend(2u32.to_be_bytes());
+    time.extend(uint64(seconds));
+    time.extend(double(fraction));
+    let mut header = dictionary(&[
+        ("version", long(0)),
+        ("rx_rate", double(rate)),
+        ("rx_time", time),
+        ("size", long(8)),
+        ("type", long(5)),
+        ("cplx", vec![0x00]),
+        ("strt", uint64(149 + extras.len() as u64)),
+        ("bytes", uint64(bytes)),
+    ]);
+    assert_eq!(header.len(), 149, "GNU Radio's METADATA_HEADER_SIZE");
+    header.extend(extras);
+    header
+}
+
+fn samples(values: &[f32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+#[test]
+fn detached_headers_become_captures_segments() {
+    let dir = TempDir::new().expect("a temp dir");
+    let mut hdr = header(32_000.0, 1_784_193_262, 0.5, 16, 2_187_500.0);
+    hdr.extend(header(32_000.0, 1_784_193_300, 0.0, 8, 8_414_500.0));
+    fs::write(dir.path().join("dsc.dat.hdr"), hdr).expect("the headers");
+    fs::write(
+        dir.path().join("dsc.dat"),
+        samples(&[1.0, -1.0, 2.0, -2.0, 3.0, -3.0]),
+    )
+    .expect("the samples");
+
+    let metadata =
+        gnuradio::detached_metadata(dir.path().join("dsc.dat.hdr")).expect("the headers parse");
+    assert_eq!(metadata.global.datatype.to_string(), "cf32_le");
+    assert_eq!(metadata.global.sample_rate, Some(32_000.0));
+    let starts: Vec<u64> = metadata.captures.iter().map(|c| c.sample_start).collect();
+    assert_eq!(starts, [0, 2]);
+    assert_eq!(metadata.captures[0].frequency, Some(2_187_500.0));
+    assert_eq!(
+        metadata.captures[0].datetime.as_deref(),
+        Some("2026-07-16T09:14:22.500Z")
+    );
+    assert_eq!(metadata.captures[1].header_bytes, None);
+
+    let recording =
+        gnuradio::describe(dir.path().join("dsc.dat"), metadata).expect("describing must succeed");
+    assert_eq!(
+        recording.metadata.global.dataset.as_deref(),
+        Some("dsc.dat")
+    );
+    let reopened = SigMF::from_file(dir.path().join("dsc.sigmf-meta")).expect("it opens");
+    assert_eq!(
+        reopened.samples::<Complex<f32>>().expect("reads back")[2],
+        Complex::new(3.0, -3.0)
+    );
+}
+
+/// Inline headers sit in front of their samples, so each is that segment's
+/// `core:header_bytes`, and reading skips them.
+#[test]
+fn inline_headers_become_header_bytes() {
+    let dir = TempDir::new().expect("a temp dir");
+    let mut data = header(48_000.0, 0, 0.0, 8, 100e6);
+    data.extend(samples(&[1.0, -1.0]));
+    let second = header(48_000.0, 0, 0.0, 16, 101e6);
+    let second_len = second.len() as u64;
+    data.extend(second);
+    data.extend(samples(&[2.0, -2.0, 3.0, -3.0]));
+    fs::write(dir.path().join("fm.bin"), data).expect("the data file");
+
+    let metadata = gnuradio::inline_metadata(dir.path().join("fm.bin")).expect("the headers parse");
+    assert_eq!(metadata.captures[1].sample_start, 1);
+    assert_eq!(metadata.captures[1].header_bytes, Some(second_len));
+    assert_eq!(
+        metadata.captures[0].datetime, None,
+        "the sink was never told a time"
+    );
+
+    let recording =
+        gnuradio::describe(dir.path().join("fm.bin"), metadata).expect("describing must succeed");
+    assert_eq!(
+        recording.samples::<Complex<f32>>().expect("reads back"),
+        [
+            Complex::new(1.0, -1.0),
+            Complex::new(2.0, -2.0),
+            Complex::new(3.0, -3.0)
+        ]
+    );
+}
+
+/// A sink killed mid-segment leaves `bytes` at 0: its samples run to the end
+/// of the file.
+#[test]
+fn an_unfinished_inline_segment_runs_to_the_end_of_the_file() {
+    let dir = TempDir::new().expect("a temp dir");
+    let mut data = header(48_000.0, 0, 0.0, 0, 100e6);
+    data.extend(samples(&[1.0, -1.0, 2.0, -2.0]));
+    fs::write(dir.path().join("killed.bin"), data).expect("the data file");
+
+    let metadata =
+        gnuradio::inline_metadata(dir.path().join("killed.bin")).expect("the header parses");
+    let recording = gnuradio::describe(dir.path().join("killed.bin"), metadata)
+        .expect("describing must succeed");
+    assert_eq!(
+        recording
+            .samples::<Complex<f32>>()
+            .expect("reads back")
+            .len(),
+        2
+    );
+}
+
+/// One Recording has one `core:sample_rate`.
+#[test]
+fn segments_at_two_rates_are_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    let mut hdr = header(32_000.0, 0, 0.0, 8, 100e6);
+    hdr.extend(header(48_000.0, 0, 0.0, 8, 100e6));
+    fs::write(dir.path().join("two.hdr"), hdr).expect("the headers");
+
+    gnuradio::detached_metadata(dir.path().join("two.hdr")).expect_err("two rates");
+}
+
+#[test]
+fn a_file_that_is_not_a_header_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::write(dir.path().join("noise.hdr"), [0x5Au8; 400]).expect("not a header");
+
+    let err = gnuradio::detached_metadata(dir.path().join("noise.hdr")).expect_err("noise");
+    assert!(matches!(err, sigmf::Error::Io { .. }));
+}
+
+/// `header` with its `strt` replaced by `strt`.
+fn with_strt(mut header: Vec<u8>, strt: u64) -> Vec<u8> {
+    let at = header
+        .windows(4)
+        .position(|window| window == b"strt")
+        .expect("a `strt` field")
+        + 4;
+    assert_eq!(header[at], 0x0b, "a uint64");
+    header[at + 1..at + 9].copy_from_slice(&strt.to_be_bytes());
+    header
+}
+
+/// A `strt` shorter than the header itself would have the reader go round
+/// the same header for ever; one longer than the file would have it allocate
+/// whatever the file says. Both are malformed, not believed.
+#[test]
+fn a_header_length_the_file_cannot_have_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    let first = header(48_000.0, 0, 0.0, 8, 100e6);
+    for (name, strt) in [
+        ("zero", 0),
+        ("short", 148),
+        ("huge", 1 << 40),
+        ("max", u64::MAX),
+    ] {
+        let hdr = dir.path().join(format!("{name}.hdr"));
+        fs::write(&hdr, with_strt(first.clone(), strt)).expect("the header");
+
+        let err = gnuradio::detached_metadata(&hdr).expect_err("a `strt` the file cannot have");
+        assert!(
+            matches!(err, sigmf::Error::Io { ref path, .. } if *path == hdr),
+            "{name}"
+        );
+    }
+}
+
+/// An inline header whose `bytes` runs past the end of the file is refused
+/// rather than stepped over into nothing.
+#[test]
+fn an_inline_segment_longer_than_the_file_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    for (name, bytes) in [("long", 24), ("max", u64::MAX)] {
+        let mut data = header(48_000.0, 0, 0.0, bytes, 100e6);
+        data.extend(samples(&[1.0, -1.0]));
+        let path = dir.path().join(format!("{name}.bin"));
+        fs::write(&path, data).expect("the data file");
+
+        gnuradio::inline_metadata(&path).expect_err("more samples than the file holds");
+    }
+}
@@ -0,0 +1,144 @@
+//! Importing raw sample files as conforming Recordings.
+
+use std::fs;
+
+use sigmf::num_complex::Complex;
+use sigmf::{Error, MetadataError, RawImport};
+use tempfile::TempDir;
+
+/// A header and trailer are left behind, so the Dataset holds samples and
+/// nothing else, and the document does not mention either.
+#[test]
+fn a_header_and_trailer_are_left_behind() {
+    let dir = TempDir::new().expect("a temp dir");
+    let mut raw = b"HEADER".to_vec();
+    for i in 0..4i16 {
+        raw.extend(i.to_le_bytes());
+        raw.extend((-i).to_le_bytes());
+    }
+    raw.extend(b"END");
+    fs::write(dir.path().join("vendor.dat"), &raw).expect("a raw capture");
+
+    let recording = RawImport::new("ci16_le".parse().expect("a datatype"), 48_000.0)
+        .header_bytes(6)
+        .trailing_bytes(3)
+        .import(dir.path().join("vendor.dat"), dir.path().join("watch"))
+        .expect("importing must succeed");
+
+    assert_eq!(
+        fs::read(dir.path().join("watch.sigmf-data")).expect("the Dataset"),
+        raw[6..raw.len() - 3]
+    );
+    let samples = recording.samples::<Complex<i16>>().expect("reads back");
+    assert_eq!(samples[3], Complex::new(3, -3));
+    let global = &recording.metadata.global;
+    assert_eq!(global.trailing_bytes, None);
+    assert_eq!(global.dataset, None);
+    assert_eq!(recording.metadata.captures[0].header_bytes, None);
+    recording
+        .verify_sha512()
+        .expect("the hash is of the Dataset");
+}
+
+/// Five bytes of a two-byte datatype is a wrong datatype, a wrong header, or
+/// a truncated file — in no case something to import.
+#[test]
+fn a_partial_sample_is_refused_before_anything_is_written() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::write(dir.path().join("odd.dat"), [0u8; 5]).expect("a raw capture");
+
+    let err = RawImport::new("ri16_le".parse().expect("a datatype"), 48_000.0)
+        .import(dir.path().join("odd.dat"), dir.path().join("odd"))
+        .expect_err("2½ samples");
+    assert!(matches!(
+        err,
+        Error::Metadata(MetadataError::PartialSample { bytes: 5, .. })
+    ));
+    assert!(!dir.path().join("odd.sigmf-data").exists());
+    assert!(!dir.path().join("odd.sigmf-meta").exists());
+}
+
+#[test]
+fn a_header_and_trailer_longer_than_the_file_are_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::write(dir.path().join("short.dat"), [0u8; 8]).expect("a raw capture");
+
+    let import = || RawImport::new("ri8".parse().expect("a datatype"), 48_000.0);
+    import()
+        .trailing_bytes(9)
+        .import(dir.path().join("short.dat"), dir.path().join("a"))
+        .expect_err("the trailer is longer than the file");
+    import()
+        .header_bytes(6)
+        .trailing_bytes(4)
+        .import(dir.path().join("short.dat"), dir.path().join("b"))
+        .expect_err("the header runs into the trailer");
+}
+
+/// A linked Dataset is the raw file itself, so it costs no second copy.
+#[cfg(unix)]
+#[test]
+fn a_hard_link_shares_the_raw_file() {
+    use std::os::unix::fs::MetadataExt;
+
+    let dir = TempDir::new().expect("a temp dir");
+    fs::write(dir.path().join("rtl.bin"), [127u8, 128, 0, 255]).expect("a raw capture");
+
+    let recording = RawImport::new("cu8".parse().expect("a datatype"), 2_048_000.0)
+        .hard_link(true)
+        .import(dir.path().join("rtl.bin"), dir.path().join("ais"))
+        .expect("importing must succeed");
+
+    let inode = |name: &str| fs::metadata(dir.path().join(name)).expect("a file").ino();
+    assert_eq!(inode("rtl.bin"), inode("ais.sigmf-data"));
+    recording
+        .verify_sha512()
+        .expect("the linked Dataset is hashed too");
+}
+
+/// Importing with a link a second time finds the first link in the way, and
+/// must not fall back to copying the raw file onto that link — which is the
+/// raw file, and would be truncated.
+#[cfg(unix)]
+#[test]
+fn linking_twice_leaves_the_raw_file_alone() {
+    let dir = TempDir::new().expect("a temp dir");
+    let raw = [127u8, 128, 0, 255];
+    fs::write(dir.path().join("rtl.bin"), raw).expect("a raw capture");
+    let import = || {
+        RawImport::new("cu8".parse().expect("a datatype"), 2_048_000.0)
+            .hard_link(true)
+            .import(dir.path().join("rtl.bin"), dir.path().join("ais"))
+    };
+
+    import().expect("the first import links");
+    let err = import().expect_err("the second would copy the file onto itself");
+    assert!(
+        matches!(err, Error::Io { ref path, .. } if path.ends_with("ais.sigmf-data")),
+        "{err}"
+    );
+    assert_eq!(
+        fs::read(dir.path().join("rtl.bin")).expect("the raw file"),
+        raw
+    );
+}
+
+/// Importing `watch.sigmf-data` as `watch` would truncate the file being read.
+#[test]
+fn importing_a_file_over_itself_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::write(dir.path().join("watch.sigmf-data"), [1u8, 2, 3, 4]).expect("a raw capture");
+
+    let err = RawImport::new("ri8".parse().expect("a datatype"), 48_000.0)
+        .header_bytes(1)
+        .import(
+            dir.path().join("watch.sigmf-data"),
+            dir.path().join("watch"),
+        )
+        .expect_err("the source is the destination");
+    assert!(matches!(err, Error::Io { .. }));
+    assert_eq!(
+        fs::read(dir.path().join("watch.sigmf-data")).expect("the raw capture"),
+        [1, 2, 3, 4]
+    );
+}
@@ -0,0 +1,189 @@
+//! NumPy `.npy` files, compared byte for byte against what `numpy.save`
+//! writes.
+
+use std::fs;
+
+use sigmf::num_complex::Complex;
+use sigmf::{npy, CaptureMetadata, Endianness, Error, MetadataError, RecordingWriter};
+use tempfile::TempDir;
+
+/// An `.npy` file as `numpy.save` writes one: version 1.0, the dictionary
+/// padded with spaces to a 64-byte boundary, then `data`.
+fn npy_file(dictionary: &str, data: &[u8]) -> Vec<u8> {
+    let padded = (10 + dictionary.len() + 1).next_multiple_of(64);
+    let mut file = b"\x93NUMPY\x01\x00".to_vec();
+    file.extend(((padded - 10) as u16).to_le_bytes());
+    file.extend(dictionary.as_bytes());
+    file.resize(padded - 1, b' ');
+    file.push(b'\n');
+    file.extend(data);
+    file
+}
+
+#[test]
+fn an_export_is_what_numpy_save_writes() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [Complex::new(0.5f32, -0.5), Complex::new(1.0, -1.0)];
+    let recording = RecordingWriter::new(&samples, 32_000.0)
+        .to_file(dir.path().join("dsc"))
+        .expect("writing must succeed");
+
+    recording
+        .export_npy(dir.path().join("dsc.npy"))
+        .expect("exporting must succeed");
+
+    let data: Vec<u8> = [0.5f32, -0.5, 1.0, -1.0]
+        .iter()
+        .flat_map(|c| c.to_le_bytes())
+        .collect();
+    assert_eq!(
+        fs::read(dir.path().join("dsc.npy")).expect("the export"),
+        npy_file(
+            "{'descr': '<c8', 'fortran_order': False, 'shape': (2,), }",
+            &data
+        )
+    );
+}
+
+/// One Captures segment, in the Recording's own byte order.
+#[test]
+fn a_capture_exports_on_its_own() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [1i16, 2, 3, 4, 5];
+    let mut writer = RecordingWriter::new(&samples, 4.0).endianness(Endianness::BigEndian);
+    writer.captures_mut().push(CaptureMetadata::new(0));
+    writer.captures_mut().push(CaptureMetadata::new(3));
+    let recording = writer
+        .to_file(dir.path().join("retune"))
+        .expect("writing must succeed");
+
+    let second =
+        recording.metadata.captures[1].sample_start..recording.sample_count().expect("a count");
+    recording
+        .export_npy_range(second, dir.path().join("second.npy"))
+        .expect("exporting must succeed");
+
+    assert_eq!(
+        fs::read(dir.path().join("second.npy")).expect("the export"),
+        npy_file(
+            "{'descr': '>i2', 'fortran_order': False, 'shape': (2,), }",
+            &[0, 4, 0, 5]
+        )
+    );
+}
+
+#[test]
+fn an_array_imports_as_a_recording() {
+    let dir = TempDir::new().expect("a temp dir");
+    let data: Vec<u8> = [0.25f64, -0.25, 1.0]
+        .iter()
+        .flat_map(|c| c.to_le_bytes())
+        .collect();
+    fs::write(
+        dir.path().join("psd.npy"),
+        npy_file(
+            "{'descr': '<f8', 'fortran_order': False, 'shape': (3,), }",
+            &data,
+        ),
+    )
+    .expect("an array");
+
+    let recording = npy::import(dir.path().join("psd.npy"), dir.path().join("psd"), 32_000.0)
+        .expect("importing must succeed");
+
+    assert_eq!(recording.metadata.global.datatype.to_string(), "rf64_le");
+    assert_eq!(recording.metadata.global.sample_rate, Some(32_000.0));
+    assert_eq!(
+        recording.samples::<f64>().expect("reads back"),
+        [0.25, -0.25, 1.0]
+    );
+    recording
+        .verify_sha512()
+        .expect("the hash is of the Dataset");
+}
+
+/// NumPy has no complex integer type; the export says so rather than writing
+/// a `complex64` array of reinterpreted bits.
+#[test]
+fn complex_integers_are_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [Complex::new(1i16, -1)];
+    let recording = RecordingWriter::new(&samples, 4.0)
+        .to_file(dir.path().join("ci16"))
+        .expect("writing must succeed");
+
+    let err = recording
+        .export_npy(dir.path().join("ci16.npy"))
+        .expect_err("ci16_le has no dtype");
+    assert!(matches!(
+        err,
+        Error::Metadata(MetadataError::NoEquivalentFormat { .. })
+    ));
+    assert!(!dir.path().join("ci16.npy").exists());
+}
+
+/// Arrays a Dataset cannot be: a dtype with no SigMF datatype, a structured
+/// dtype, and a second dimension.
+#[test]
+fn arrays_sigmf_cannot_represent_are_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    for (name, dictionary) in [
+        (
+            "int64",
+            "{'descr': '<i8', 'fortran_order': False, 'shape': (1,), }",
+        ),
+        (
+            "structured",
+            "{'descr': [('re', '<i2'), ('im', '<i2')], 'fortran_order': False, 'shape': (2,), }",
+        ),
+        (
+            "matrix",
+            "{'descr': '<f4', 'fortran_order': False, 'shape': (1, 2), }",
+        ),
+    ] {
+        let path = dir.path().join(format!("{name}.npy"));
+        fs::write(&path, npy_file(dictionary, &[0; 8])).expect("an array");
+
+        let err = npy::import(&path, dir.path().join(name), 1.0)
+            .expect_err("SigMF has no Dataset of this shape");
+        assert!(matches!(err, Error::Io { .. }), "{name}: {err}");
+        assert!(!dir.path().join(format!("{name}.sigmf-data")).exists());
+    }
+}
+
+#[test]
+fn an_array_cut_short_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::write(
+        dir.path().join("short.npy"),
+        npy_file(
+            "{'descr': '<f4', 'fortran_order': False, 'shape': (4,), }",
+            &[0; 12],
+        ),
+    )
+    .expect("an array");
+
+    let err = npy::import(dir.path().join("short.npy"), dir.path().join("short"), 1.0)
+        .expect_err("twelve bytes of a sixteen-byte array");
+    assert!(matches!(err, Error::Io { .. }));
+}
+
+/// A shape so long that the array would end past the last byte any file
+/// can have is refused, not wrapped round to a short one.
+#[test]
+fn an_array_longer_than_any_file_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::write(
+        dir.path().join("huge.npy"),
+        npy_file(
+            "{'descr': '|u1', 'fortran_order': False, 'shape': (18446744073709551615,), }",
+            &[0; 4],
+        ),
+    )
+    .expect("an array");
+
+    let err = npy::import(dir.path().join("huge.npy"), dir.path().join("huge"), 1.0)
+        .expect_err("no file holds that many bytes after its header");
+    assert!(matches!(err, Error::Io { .. }));
+    assert!(!dir.path().join("huge.sigmf-data").exists());
+}
@@ -0,0 +1,149 @@
+//! Whole-Dataset decoding, encoding and hashing, on Datasets large enough to
+//! be split into many pieces. CI runs these with the `rayon` feature and
+//! without it, and the expected values are computed the slow, obvious way, so
+//! both paths are held to the same answer.
+
+use std::fs;
+
+use sha2::{Digest, Sha512};
+use sigmf::num_complex::Complex;
+use sigmf::{CaptureMetadata, Endianness, RecordingWriter};
+use tempfile::TempDir;
+
+/// Several mebibytes of samples that differ from one another, so a piece put
+/// in the wrong place cannot go unnoticed.
+fn distinct_samples(len: usize) -> Vec<Complex<i16>> {
+    (0..len)
+        .map(|n| Complex::new(n as i16, (n / 7) as i16 ^ 0x5A5A))
+        .collect()
+}
+
+/// The Dataset `to_file` writes is the samples' bytes in order, byte for
+/// byte, and `core:sha512` is their hash — in both byte orders, and at a
+/// length that is not a whole number of pieces.
+#[test]
+fn encoding_matches_the_samples_in_order() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = distinct_samples(1_500_001);
+
+    for (endianness, name) in [
+        (Endianness::LittleEndian, "little"),
+        (Endianness::BigEndian, "big"),
+    ] {
+        let recording = RecordingWriter::new(&samples, 1e6)
+            .endianness(endianness)
+            .to_file(dir.path().join(name))
+            .expect("writing must succeed");
+
+        let expected: Vec<u8> = samples
+            .iter()
+            .flat_map(|sample| match endianness {
+                Endianness::LittleEndian => [sample.re.to_le_bytes(), sample.im.to_le_bytes()],
+                Endianness::BigEndian => [sample.re.to_be_bytes(), sample.im.to_be_bytes()],
+            })
+            .flatten()
+            .collect();
+        let written = fs::read(dir.path().join(format!("{name}.sigmf-data"))).expect("written");
+        assert!(written == expected, "the {name}-endian Dataset differs");
+
+        let hash: String = Sha512::digest(&expected)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        assert_eq!(recording.metadata.global.sha512.as_deref(), Some(&*hash));
+        assert_eq!(recording.dataset_sha512().expect("hashes"), hash);
+
+        let read = recording.samples::<Complex<i16>>().expect("reads back");
+        assert!(read == samples, "the {name}-endian samples differ");
+    }
+}
+
+/// Decoding goes segment by segment and each segment is split on its own, so
+/// segments of awkward lengths still come back joined in order.
+#[test]
+fn decoding_across_segments_keeps_their_order() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = distinct_samples(700_003);
+    let mut writer = RecordingWriter::new(&samples, 1e6);
+    writer.captures_mut().extend([
+        CaptureMetadata::new(0),
+        CaptureMetadata::new(1),
+        CaptureMetadata::new(262_145),
+        CaptureMetadata::new(700_002),
+    ]);
+    let recording = writer
+        .to_file(dir.path().join("segments"))
+        .expect("writing must succeed");
+
+    let read = recording.samples::<Complex<i16>>().expect("reads back");
+    assert!(read == samples);
+}
+
+/// Every sample type, in both byte orders, is written as its components'
+/// bytes one after another and read back as itself — the bulk conversion
+/// checked type by type against the per-component one.
+#[test]
+fn every_sample_type_converts_in_both_orders() {
+    let dir = TempDir::new().expect("a temp dir");
+
+    macro_rules! check {
+        ($component:ty, $values:expr) => {{
+            let real: Vec<$component> = $values;
+            let complex: Vec<Complex<$component>> = real
+                .iter()
+                .zip(real.iter().rev())
+                .map(|(&re, &im)| Complex::new(re, im))
+                .collect();
+            for (endianness, order) in [
+                (Endianness::LittleEndian, "le"),
+                (Endianness::BigEndian, "be"),
+            ] {
+                let bytes = |component: $component| match endianness {
+                    Endianness::LittleEndian => component.to_le_bytes(),
+                    Endianness::BigEndian => component.to_be_bytes(),
+                };
+                let name = format!("{}_{order}", stringify!($component));
+
+                let recording = RecordingWriter::new(&real, 1e3)
+                    .endianness(endianness)
+                    .to_file(dir.path().join(format!("r{name}")))
+                    .expect("writing must succeed");
+                let expected: Vec<u8> = real.iter().flat_map(|&c| bytes(c)).collect();
+                assert_eq!(
+                    fs::read(dir.path().join(format!("r{name}.sigmf-data"))).expect("written"),
+                    expected,
+                    "r{name}"
+                );
+                assert_eq!(recording.samples::<$component>().expect("reads"), real);
+
+                let recording = RecordingWriter::new(&complex, 1e3)
+                    .endianness(endianness)
+                    .to_file(dir.path().join(format!("c{name}")))
+                    .expect("writing must succeed");
+                let expected: Vec<u8> = complex
+                    .iter()
+                    .flat_map(|c| [bytes(c.re), bytes(c.im)])
+                    .flatten()
+                    .collect();
+                assert_eq!(
+                    fs::read(dir.path().join(format!("c{name}.sigmf-data"))).expect("written"),
+                    expected,
+                    "c{name}"
+                );
+                assert_eq!(
+                    recording.samples::<Complex<$component>>().expect("reads"),
+                    complex
+                );
+            }
+        }};
+    }
+
+    check!(f32, vec![0.0, -1.5, f32::MAX, f32::MIN_POSITIVE, 3.25]);
+    check!(f64, vec![0.0, -1.5, f64::MAX, f64::MIN_POSITIVE, 3.25]);
+    check!(i32, vec![0, -1, i32::MAX, i32::MIN, 0x0102_0304]);
+    check!(i16, vec![0, -1, i16::MAX, i16::MIN, 0x0102]);
+    check!(u32, vec![0, 1, u32::MAX, 0x8000_0000, 0x0102_0304]);
+    check!(u16, vec![0, 1, u16::MAX, 0x8000, 0x0102]);
+    check!(i8, vec![0, -1, i8::MAX, i8::MIN, 3]);
+    check!(u8, vec![0, 1, u8::MAX, 0x80, 3]);
+}
@@ -0,0 +1,246 @@
+//! Spectra and spectrograms of Recordings, for a look before a listen.
+//!
+//! The numbers are checked against what a tone must produce: its power in
+//! dB relative to full scale, in the bin of its frequency, on the air where
+//! the segment says where it was tuned.
+
+mod common;
+
+use std::f32::consts::PI;
+use std::fs;
+use std::path::Path;
+
+use common::annotation;
+use serde_json::json;
+use sigmf::num_complex::Complex;
+use sigmf::preview::Preview;
+use sigmf::{CaptureMetadata, Error, MetadataError, RecordingWriter, SigMF};
+use tempfile::TempDir;
+
+/// `len` samples of a complex tone of amplitude 1.0, `bin` bins of a 64-point
+/// FFT above the centre.
+fn tone(bin: f32, len: usize) -> Vec<Complex<f32>> {
+    (0..len)
+        .map(|n| Complex::from_polar(1.0, 2.0 * PI * bin * n as f32 / 64.0))
+        .collect()
+}
+
+/// 100 frames of a tone at 1 kSa/s, tuned to 100 MHz.
+fn a_long_tone(basename: &Path) -> SigMF {
+    let samples = tone(16.0, 64 * 100);
+    let mut writer = RecordingWriter::new(&samples, 1_000.0);
+    let mut capture = CaptureMetadata::new(0);
+    capture.frequency = Some(100e6);
+    writer.captures_mut().push(capture);
+    writer.to_file(basename).expect("writing must succeed")
+}
+
+fn peak(power_db: &[f64]) -> usize {
+    (0..power_db.len())
+        .max_by(|&a, &b| power_db[a].total_cmp(&power_db[b]))
+        .expect("a spectrum")
+}
+
+#[test]
+fn a_full_scale_tone_reads_0_dbfs_in_its_bin() {
+    let dir = TempDir::new().expect("a temp dir");
+    let recording = RecordingWriter::new(&tone(16.0, 64 * 8), 1_000.0)
+        .to_file(dir.path().join("tone"))
+        .expect("writing must succeed");
+
+    let spectra = Preview::new(64).compute(&recording).expect("a preview");
+
+    let psd = &spectra.psds[0];
+    assert_eq!(psd.frames, 8);
+    assert_eq!(psd.frequencies.len(), 64);
+    assert_eq!(
+        psd.frequencies[0], -500.0,
+        "complex: the whole band, lowest first"
+    );
+    let bin = peak(&psd.power_db);
+    assert_eq!(psd.frequencies[bin], 250.0);
+    assert!(psd.power_db[bin].abs() < 0.01, "{}", psd.power_db[bin]);
+    assert!(
+        psd.power_db[32] < -100.0,
+        "nothing at DC: {}",
+        psd.power_db[32]
+    );
+}
+
+/// Integers are scaled to full scale, so a tone at the top of `ci16` reads as
+/// a tone of amplitude 1.0 does.
+#[test]
+fn integer_samples_are_scaled_to_full_scale() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples: Vec<Complex<i16>> = tone(-8.0, 64 * 4)
+        .iter()
+        .map(|s| Complex::new((s.re * 32767.0) as i16, (s.im * 32767.0) as i16))
+        .collect();
+    let recording = RecordingWriter::new(&samples, 1_000.0)
+        .to_file(dir.path().join("tone"))
+        .expect("writing must succeed");
+
+    let psd = &Preview::new(64)
+        .compute(&recording)
+        .expect("a preview")
+        .psds[0];
+
+    let bin = peak(&psd.power_db);
+    assert_eq!(psd.frequencies[bin], -125.0);
+    assert!(psd.power_db[bin].abs() < 0.01, "{}", psd.power_db[bin]);
+}
+
+/// Each segment has its own spectrum, on the air at its own tuning, of whole
+/// frames only: a frame never spans a retune.
+#[test]
+fn each_segment_has_its_own_spectrum() {
+    let dir = TempDir::new().expect("a temp dir");
+    let mut samples = tone(16.0, 200);
+    samples.extend(tone(-16.0, 130));
+    let mut writer = RecordingWriter::new(&samples, 1_000.0);
+    let mut first = CaptureMetadata::new(0);
+    first.frequency = Some(100e6);
+    let mut second = CaptureMetadata::new(200);
+    second.frequency = Some(200e6);
+    writer.captures_mut().extend([first, second]);
+    let recording = writer
+        .to_file(dir.path().join("hop"))
+        .expect("writing must succeed");
+
+    let spectra = Preview::new(64).compute(&recording).expect("a preview");
+
+    let [first, second] = &spectra.psds[..] else {
+        panic!("two segments, two spectra: {:?}", spectra.psds);
+    };
+    assert_eq!((first.capture, first.frames), (0, 3));
+    assert_eq!((second.capture, second.frames), (1, 2));
+    assert_eq!(first.frequencies[peak(&first.power_db)], 100e6 + 250.0);
+    assert_eq!(second.frequencies[peak(&second.power_db)], 200e6 - 250.0);
+    assert_eq!(spectra.spectrogram.row_starts, [0, 64, 128, 200, 264]);
+}
+
+/// However long the Recording, the spectrogram has at most the rows asked
+/// for, each averaging an even share of the frames.
+#[test]
+fn the_spectrogram_is_downsampled_to_its_rows() {
+    let dir = TempDir::new().expect("a temp dir");
+    let recording = a_long_tone(&dir.path().join("tone"));
+
+    let spectrogram = Preview::new(64)
+        .rows(10)
+        .compute(&recording)
+        .expect("a preview")
+        .spectrogram;
+    assert_eq!(spectrogram.power_db.len(), 10);
+    assert_eq!(
+        spectrogram.row_starts,
+        (0..10).map(|row| row * 640).collect::<Vec<u64>>()
+    );
+    assert!(spectrogram.power_db.iter().all(|row| peak(row) == 48));
+
+    let spectrogram = Preview::new(64)
+        .rows(1000)
+        .compute(&recording)
+        .expect("a preview")
+        .spectrogram;
+    assert_eq!(spectrogram.power_db.len(), 100, "a row per frame, no more");
+}
+
+/// Edges are placed relative to the tuning of the segment an annotation
+/// starts in; an annotation without edges spans the band, and one past the
+/// end of the Dataset is not drawn.
+#[test]
+fn annotations_are_boxed_where_they_fall() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = tone(16.0, 64 * 100);
+    let mut writer = RecordingWriter::new(&samples, 1_000.0);
+    let mut capture = CaptureMetadata::new(0);
+    capture.frequency = Some(100e6);
+    writer.captures_mut().push(capture);
+    writer.annotations_mut().extend([
+        annotation(json!({
+            "core:sample_start": 640,
+            "core:sample_count": 640,
+            "core:freq_lower_edge": 100e6 + 200.0,
+            "core:freq_upper_edge": 100e6 + 300.0,
+        })),
+        annotation(json!({"core:sample_start": 3000, "core:sample_count": 2000})),
+        annotation(json!({"core:sample_start": 7000})),
+    ]);
+    let recording = writer
+        .to_file(dir.path().join("tone"))
+        .expect("writing must succeed");
+
+    let boxes = Preview::new(64)
+        .rows(10)
+        .compute(&recording)
+        .expect("a preview")
+        .spectrogram
+        .boxes;
+
+    let extents: Vec<(usize, _, _)> = boxes
+        .iter()
+        .map(|b| (b.annotation, b.rows.clone(), b.columns.clone()))
+        .collect();
+    assert_eq!(extents, [(0, 1..2, 44..52), (1, 4..8, 0..64)]);
+}
+
+#[test]
+fn the_spectrogram_renders_to_png() {
+    let dir = TempDir::new().expect("a temp dir");
+    let recording = a_long_tone(&dir.path().join("tone"));
+    let spectrogram = Preview::new(64)
+        .rows(10)
+        .compute(&recording)
+        .expect("a preview")
+        .spectrogram;
+
+    spectrogram
+        .to_png(dir.path().join("tone.png"))
+        .expect("rendering must succeed");
+
+    let png = fs::read(dir.path().join("tone.png")).expect("the picture");
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(&png[16..20], 64u32.to_be_bytes(), "a column per bin");
+    assert_eq!(&png[20..24], 10u32.to_be_bytes(), "a row per row");
+    assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+}
+
+/// A Recording shorter than one frame has nothing to draw, and says so
+/// rather than writing an empty picture.
+#[test]
+fn a_recording_shorter_than_a_frame_has_no_picture() {
+    let dir = TempDir::new().expect("a temp dir");
+    let recording = RecordingWriter::new(&tone(1.0, 10), 1_000.0)
+        .to_file(dir.path().join("short"))
+        .expect("writing must succeed");
+
+    let spectra = Preview::new(64).compute(&recording).expect("a preview");
+    assert_eq!(spectra.psds[0].frames, 0);
+    assert!(spectra.psds[0].power_db.is_empty());
+
+    let err = spectra
+        .spectrogram
+        .to_png(dir.path().join("short.png"))
+        .expect_err("no frames");
+    assert!(matches!(err, Error::Io { .. }));
+    assert!(!dir.path().join("short.png").exists());
+}
+
+/// Without a rate there is no frequency axis to put a spectrum on.
+#[test]
+fn a_recording_without_a_rate_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = tone(16.0, 64);
+    let mut writer = RecordingWriter::new(&samples, 1_000.0);
+    writer.global_mut().sample_rate = None;
+    let recording = writer
+        .to_file(dir.path().join("tone"))
+        .expect("writing must succeed");
+
+    let err = Preview::new(64)
+        .compute(&recording)
+        .expect_err("no sample rate");
+    assert!(matches!(err, Error::Metadata(MetadataError::NoSampleRate)));
+}
@@ -0,0 +1,117 @@
+//! Reading into a caller's buffer: the same samples `samples` returns, from
+//! any index, whatever the segments and headers between them.
+
+use std::fs;
+
+use serde_json::json;
+use sigmf::num_complex::Complex;
+use sigmf::{Error, MetadataError, RecordingWriter, SigMF};
+use tempfile::TempDir;
+
+/// A Non-Conforming `ci16_le` Dataset of three segments, two and three and
+/// one samples long, each behind a header, and with trailing bytes.
+fn a_headered_recording(dir: &TempDir) -> SigMF {
+    let mut data = Vec::new();
+    for (header, samples) in [(3, &[1i16, 2][..]), (5, &[3, 4, 5]), (1, &[6])] {
+        data.extend(vec![0xEE; header]);
+        data.extend(
+            samples
+                .iter()
+                .flat_map(|&i| [i.to_le_bytes(), (-i).to_le_bytes()])
+                .flatten(),
+        );
+    }
+    data.extend([0xEE; 2]);
+    fs::write(dir.path().join("capture.raw"), data).expect("the data file");
+    let metadata = json!({
+        "global": {
+            "core:datatype": "ci16_le",
+            "core:version": "1.2.0",
+            "core:dataset": "capture.raw",
+            "core:trailing_bytes": 2
+        },
+        "captures": [
+            { "core:sample_start": 0, "core:header_bytes": 3 },
+            { "core:sample_start": 2, "core:header_bytes": 5 },
+            { "core:sample_start": 5, "core:header_bytes": 1 }
+        ],
+        "annotations": []
+    });
+    let path = dir.path().join("capture.sigmf-meta");
+    fs::write(&path, serde_json::to_vec(&metadata).expect("serializes")).expect("the metadata");
+    SigMF::from_file(path).expect("opens")
+}
+
+/// Every start and every buffer length agrees with slicing what `samples`
+/// returns, across segment boundaries and the headers between them.
+#[test]
+fn every_window_matches_samples() {
+    let dir = TempDir::new().expect("a temp dir");
+    let recording = a_headered_recording(&dir);
+    let all = recording.samples::<Complex<i16>>().expect("reads");
+    assert_eq!(all.len(), 6);
+
+    for start in 0..=all.len() {
+        for len in 0..=all.len() + 1 {
+            let mut out = vec![Complex::new(99, 99); len];
+            let read = recording
+                .read_samples_into(start as u64, &mut out)
+                .expect("within the Dataset");
+            let expected = &all[start..(start + len).min(all.len())];
+            assert_eq!(read, expected.len(), "{start} +{len}");
+            assert_eq!(&out[..read], expected, "{start} +{len}");
+            assert!(
+                out[read..].iter().all(|&s| s == Complex::new(99, 99)),
+                "what was not read is left alone"
+            );
+        }
+    }
+}
+
+/// One buffer, reused block by block, reads a Dataset longer than the
+/// function's own buffer — so the reads inside a segment are chained too.
+#[test]
+fn a_reused_buffer_reads_a_long_dataset() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples: Vec<f64> = (0..10_007).map(|n| n as f64 / 3.0).collect();
+    let recording = RecordingWriter::new(&samples, 1e3)
+        .to_file(dir.path().join("long"))
+        .expect("writing must succeed");
+
+    let mut block = [0.0; 4_096];
+    let mut read_back = Vec::new();
+    loop {
+        let read = recording
+            .read_samples_into(read_back.len() as u64, &mut block)
+            .expect("reads");
+        if read == 0 {
+            break;
+        }
+        read_back.extend_from_slice(&block[..read]);
+    }
+    assert_eq!(read_back, samples);
+}
+
+/// The checks are `samples`' own, and a start past the end is an error
+/// rather than an empty read.
+#[test]
+fn refuses_the_wrong_type_and_a_start_past_the_end() {
+    let dir = TempDir::new().expect("a temp dir");
+    let recording = a_headered_recording(&dir);
+
+    let mut wrong = [Complex::new(0.0f32, 0.0); 2];
+    assert!(matches!(
+        recording.read_samples_into(0, &mut wrong),
+        Err(Error::Metadata(MetadataError::DatatypeMismatch { .. }))
+    ));
+
+    let mut out = [Complex::new(0i16, 0); 2];
+    assert!(matches!(
+        recording.read_samples_into(7, &mut out),
+        Err(Error::Metadata(MetadataError::SampleRangeOutOfBounds {
+            start: 7,
+            end: 9,
+            sample_count: 6
+        }))
+    ));
+}
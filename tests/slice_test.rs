@@ -0,0 +1,181 @@
+//! Cutting a Recording down to a span of its samples.
+//!
+//! A slice is a new Recording whose samples keep their absolute indices:
+//! `core:offset` moves to the first one kept, and `core:global_index` goes on
+//! saying where in the original stream the rest came from.
+
+mod common;
+
+use common::annotation;
+use serde_json::json;
+use sigmf::num_complex::Complex;
+use sigmf::{CaptureMetadata, Error, MetadataError, RecordingWriter, SigMF};
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Sample `i` is `i + 0i`, so a sample read back says where it came from.
+fn counting(n: i32) -> Vec<Complex<i16>> {
+    (0..n).map(|i| Complex::new(i as i16, 0)).collect()
+}
+
+/// Ten samples at 10 Sa/s in two segments — the second retuned, and recorded
+/// after 90 samples were dropped — with one annotation in each and one running
+/// to the end.
+fn two_segments(dir: &Path) -> SigMF {
+    let samples = counting(10);
+    let mut writer = RecordingWriter::new(&samples, 10.0);
+
+    let mut first = CaptureMetadata::new(0);
+    first.frequency = Some(16_804_500.0);
+    first.datetime = Some("2026-07-16T23:59:59.500Z".to_string());
+    let mut second = CaptureMetadata::new(6);
+    second.frequency = Some(8_414_500.0);
+    second.global_index = Some(96);
+    writer.captures_mut().extend([first, second]);
+
+    writer.annotations_mut().extend([
+        annotation(json!({"core:sample_start": 1, "core:sample_count": 3, "core:label": "a"})),
+        annotation(json!({"core:sample_start": 5, "core:sample_count": 3, "core:label": "b"})),
+        annotation(json!({"core:sample_start": 8, "core:label": "tail"})),
+    ]);
+    writer
+        .to_file(dir.join("source"))
+        .expect("writing the source must succeed")
+}
+
+#[test]
+fn a_slice_holds_exactly_the_samples_asked_for() {
+    let dir = TempDir::new().expect("a temp dir");
+    let source = two_segments(dir.path());
+
+    let slice = source
+        .slice(3..8, dir.path().join("slice"))
+        .expect("slicing must succeed");
+    assert_eq!(
+        slice
+            .samples::<Complex<i16>>()
+            .expect("the slice reads back"),
+        &counting(10)[3..8]
+    );
+
+    let reopened =
+        SigMF::from_file(dir.path().join("slice.sigmf-meta")).expect("the slice reopens");
+    assert_eq!(
+        reopened.metadata.global.sha512,
+        slice.metadata.global.sha512
+    );
+    assert!(reopened.metadata.global.sha512.is_some(), "and is hashed");
+}
+
+/// Both segments survive a slice straddling the retune, the first moved up to
+/// the slice's first sample, and each still says where it sat in the original
+/// stream.
+#[test]
+fn captures_are_cut_and_keep_their_place_in_the_stream() {
+    let dir = TempDir::new().expect("a temp dir");
+    let source = two_segments(dir.path());
+
+    let slice = source
+        .slice(3..8, dir.path().join("slice"))
+        .expect("slicing must succeed");
+    let captures = &slice.metadata.captures;
+
+    assert_eq!(slice.metadata.global.offset, Some(3));
+    assert_eq!(captures.len(), 2);
+    assert_eq!(captures[0].sample_start, 3);
+    assert_eq!(
+        captures[0].global_index, None,
+        "the first segment is exactly where core:offset says"
+    );
+    assert_eq!(captures[1].sample_start, 6);
+    assert_eq!(
+        captures[1].global_index,
+        Some(96),
+        "the dropped samples are still on record"
+    );
+    assert_eq!(captures[1].frequency, Some(8_414_500.0));
+}
+
+/// The first segment's timestamp moves with its first sample, across midnight
+/// if need be.
+#[test]
+fn the_first_captures_datetime_moves_with_its_first_sample() {
+    let dir = TempDir::new().expect("a temp dir");
+    let source = two_segments(dir.path());
+
+    let slice = source
+        .slice(5..6, dir.path().join("slice"))
+        .expect("slicing must succeed");
+    assert_eq!(
+        slice.metadata.captures[0].datetime.as_deref(),
+        Some("2026-07-17T00:00:00.000Z"),
+        "five samples at 10 Sa/s is half a second"
+    );
+}
+
+#[test]
+fn annotations_are_clipped_to_the_slice() {
+    let dir = TempDir::new().expect("a temp dir");
+    let source = two_segments(dir.path());
+
+    let middle = source
+        .slice(3..7, dir.path().join("middle"))
+        .expect("slicing must succeed");
+    let spans: Vec<(u64, Option<u64>)> = middle
+        .metadata
+        .annotations
+        .iter()
+        .map(|a| (a.sample_start, a.sample_count))
+        .collect();
+    assert_eq!(
+        spans,
+        [(3, Some(1)), (5, Some(2))],
+        "`a` keeps its last sample, `b` its first two, and `tail` is gone"
+    );
+
+    let end = source
+        .slice(9..10, dir.path().join("end"))
+        .expect("slicing must succeed");
+    assert_eq!(
+        end.metadata.annotations[0].sample_count, None,
+        "an annotation running to the end still does, when the slice does"
+    );
+}
+
+/// A range outside the Dataset is refused before anything is written.
+#[test]
+fn a_range_past_the_end_is_refused_and_writes_nothing() {
+    let dir = TempDir::new().expect("a temp dir");
+    let source = two_segments(dir.path());
+
+    let err = source
+        .slice(8..11, dir.path().join("slice"))
+        .expect_err("there are only ten samples");
+    assert!(matches!(
+        err,
+        Error::Metadata(MetadataError::SampleRangeOutOfBounds {
+            start: 8,
+            end: 11,
+            sample_count: 10
+        })
+    ));
+    assert!(!dir.path().join("slice.sigmf-data").exists());
+}
+
+/// Slicing a Recording onto its own basename would truncate the Dataset being
+/// read before reading it.
+#[test]
+fn a_slice_cannot_overwrite_its_own_source() {
+    let dir = TempDir::new().expect("a temp dir");
+    let source = two_segments(dir.path());
+
+    source
+        .slice(0..5, dir.path().join("source"))
+        .expect_err("the source's Dataset is the target");
+    assert_eq!(
+        source
+            .samples::<Complex<i16>>()
+            .expect("the source survives"),
+        counting(10)
+    );
+}
@@ -23,11 +23,12 @@
 //!
 //!     cargo test --test spec_oracle_test -- --ignored
 
+mod common;
+
 use std::collections::BTreeSet;
 use std::fmt::Write as _;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::OnceLock;
 
 use serde_json::{json, Value};
 use sha2::{Digest, Sha512};
@@ -35,6 +36,8 @@ use sigmf::num_complex::Complex;
 use sigmf::*;
 use tempfile::TempDir;
 
+use common::{assert_valid, schema};
+
 /// The SigMF version vendored at `tests/spec/sigmf-schema.json`, as it appears in
 /// the schema's own `$id`.
 ///
@@ -75,35 +78,6 @@ fn read_json(path: &Path) -> Value {
     serde_json::from_str(&raw).unwrap_or_else(|e| panic!("parsing {}: {e}", path.display()))
 }
 
-fn spec_schema() -> &'static Value {
-    static SCHEMA: OnceLock<Value> = OnceLock::new();
-    SCHEMA.get_or_init(|| {
-        read_json(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/spec/sigmf-schema.json"))
-    })
-}
-
-fn validator() -> &'static jsonschema::Validator {
-    static VALIDATOR: OnceLock<jsonschema::Validator> = OnceLock::new();
-    VALIDATOR.get_or_init(|| {
-        jsonschema::validator_for(spec_schema())
-            .expect("the vendored schema must itself be a valid JSON Schema")
-    })
-}
-
-/// Assert `instance` satisfies the SigMF schema, reporting *every* violation with
-/// its JSON path rather than just the first.
-fn assert_valid(instance: &Value, what: &str) {
-    let errors: Vec<String> = validator()
-        .iter_errors(instance)
-        .map(|e| format!("  at {}: {e}", e.instance_path()))
-        .collect();
-    assert!(
-        errors.is_empty(),
-        "{what} does not satisfy the SigMF {EXPECTED_SPEC_VERSION} schema:\n{}",
-        errors.join("\n")
-    );
-}
-
 /// Collect every key path in a JSON document, e.g. `global/core:datatype` and
 /// `captures/0/core:geolocation/coordinates`.
 ///
@@ -269,7 +243,7 @@ fn assert_round_trip_is_lossless(name: &str) {
 /// decision rather than an accident.
 #[test]
 fn vendored_schema_is_the_pinned_version() {
-    let id = spec_schema()["$id"]
+    let id = schema()["$id"]
         .as_str()
         .expect("the vendored schema must carry an $id recording its version");
     assert!(
@@ -281,7 +255,7 @@ fn vendored_schema_is_the_pinned_version() {
     );
 
     assert_eq!(
-        spec_schema()["$schema"],
+        schema()["$schema"],
         json!("https://json-schema.org/draft/2020-12/schema"),
         "the validator is built for draft 2020-12"
     );
@@ -330,12 +304,31 @@ fn a_recording_built_through_the_constructor_validates() {
 
 /// Our test data is honest before it is allowed to judge the crate.
 #[test]
-fn every_fixture_validates_against_the_spec_schema() {
+fn every_fixture_validates_against_the_schema() {
     for name in FIXTURES {
         assert_valid(&read_json(&fixture_path(name)), name);
     }
 }
 
+/// `Metadata::validate` checks the rules the schema cannot express, so it must
+/// never call an error what the schema and the corpus agree is conforming.
+/// Warnings are allowed: several fixtures omit `core:sample_rate` on purpose.
+#[test]
+fn the_validator_finds_no_error_in_any_fixture() {
+    for name in FIXTURES {
+        let sigmf = SigMF::from_file(fixture_path(name))
+            .unwrap_or_else(|e| panic!("{name} must open, but did not: {e}"));
+        let errors: Vec<String> = sigmf
+            .metadata
+            .validate()
+            .into_iter()
+            .filter(|finding| finding.severity == Severity::Error)
+            .map(|finding| finding.to_string())
+            .collect();
+        assert!(errors.is_empty(), "{name}: {errors:#?}");
+    }
+}
+
 /// The smallest legal recording: the two required globals and nothing else.
 #[test]
 fn minimal_recording_opens() {
@@ -694,7 +687,7 @@ mod write_path {
 
     /// The specification's own schema, asked about a recording this crate made.
     #[test]
-    fn a_written_recording_validates_against_the_spec_schema() {
+    fn a_written_recording_validates_against_the_schema() {
         let dir = TempDir::new().expect("a temp dir");
         let basename = dir.path().join("dsc_watch");
 
@@ -709,6 +702,292 @@ mod write_path {
         );
     }
 
+    /// A slice is written by rewriting a document rather than building one, and the
+    /// fields it moves — `core:offset`, `core:global_index`, a shifted
+    /// `core:datetime` — are exactly those a constructed Recording never sets.
+    #[test]
+    fn a_slice_validates_against_the_schema() {
+        let dir = TempDir::new().expect("a temp dir");
+        let basename = dir.path().join("dsc_watch");
+
+        let samples = dsc_samples();
+        RecordingWriter::with_metadata(&samples, a_dsc_watch_metadata("cf32_le"))
+            .to_file(&basename)
+            .expect("writing must succeed")
+            .slice(1..3, dir.path().join("burst"))
+            .expect("slicing must succeed");
+
+        assert_valid(
+            &read_json(&dir.path().join("burst.sigmf-meta")),
+            "a slice of a recording written by this crate",
+        );
+    }
+
+    /// The same, for the Recording that is the join of two.
+    #[test]
+    fn a_concatenation_validates_against_the_schema() {
+        let dir = TempDir::new().expect("a temp dir");
+
+        let samples = dsc_samples();
+        let write = |name: &str| {
+            RecordingWriter::with_metadata(&samples, a_dsc_watch_metadata("cf32_le"))
+                .to_file(dir.path().join(name))
+                .expect("writing must succeed")
+        };
+        let (first, second) = (write("0914"), write("0915"));
+        first
+            .concat(&[&second], dir.path().join("watch"))
+            .expect("joining must succeed");
+
+        assert_valid(
+            &read_json(&dir.path().join("watch.sigmf-meta")),
+            "a concatenation of recordings written by this crate",
+        );
+    }
+
+    /// And for one whose `core:datatype` was rewritten rather than derived.
+    #[test]
+    fn a_transcoded_recording_validates_against_the_schema() {
+        let dir = TempDir::new().expect("a temp dir");
+
+        let samples = dsc_samples();
+        RecordingWriter::with_metadata(&samples, a_dsc_watch_metadata("cf32_le"))
+            .to_file(dir.path().join("dsc_watch"))
+            .expect("writing must succeed")
+            .transcode::<Complex<i16>, _>(
+                dir.path().join("ci16"),
+                Scaling::Factor(1_000.0),
+                Endianness::BigEndian,
+            )
+            .expect("transcoding must succeed");
+
+        assert_valid(
+            &read_json(&dir.path().join("ci16.sigmf-meta")),
+            "a recording transcoded by this crate",
+        );
+    }
+
+    /// And for one whose datatype was stated by a caller rather than derived,
+    /// over bytes this crate did not write.
+    #[test]
+    fn an_imported_recording_validates_against_the_schema() {
+        let dir = TempDir::new().expect("a temp dir");
+        fs::write(dir.path().join("capture.bin"), [0u8; 40]).expect("a raw capture");
+
+        let mut import =
+            RawImport::new("ci16_le".parse().expect("a datatype"), 32_000.0).header_bytes(8);
+        import.captures_mut()[0].frequency = Some(2_187_500.0);
+        import
+            .import(dir.path().join("capture.bin"), dir.path().join("dsc_watch"))
+            .expect("importing must succeed");
+
+        assert_valid(
+            &read_json(&dir.path().join("dsc_watch.sigmf-meta")),
+            "a raw capture imported by this crate",
+        );
+    }
+
+    /// And for one imported from a WAV file, whose `core:datetime` was built
+    /// from a Windows `SYSTEMTIME` rather than parsed.
+    #[test]
+    fn a_wav_import_validates_against_the_schema() {
+        let dir = TempDir::new().expect("a temp dir");
+
+        let samples = dsc_samples();
+        let recording = RecordingWriter::with_metadata(&samples, a_dsc_watch_metadata("cf32_le"))
+            .to_file(dir.path().join("dsc_watch"))
+            .expect("writing must succeed");
+        wav::export(&recording, dir.path().join("dsc_watch.wav")).expect("exporting must succeed");
+        wav::import(
+            dir.path().join("dsc_watch.wav"),
+            dir.path().join("imported"),
+        )
+        .expect("importing must succeed");
+
+        assert_valid(
+            &read_json(&dir.path().join("imported.sigmf-meta")),
+            "a WAV file imported by this crate",
+        );
+    }
+
+    /// And for the description of a GNU Radio capture, which has a
+    /// `core:dataset` and a `core:header_bytes` on every segment.
+    #[test]
+    fn a_gnu_radio_description_validates_against_the_schema() {
+        let dir = TempDir::new().expect("a temp dir");
+
+        // One inline header, as GNU Radio's File Meta Sink writes it, for
+        // four bytes of `ri16` samples at 32 kSa/s, and no extras.
+        let mut header = Vec::new();
+        let mut entry = |key: &str, value: &[u8]| {
+            header.extend([0x07, 0x07, 0x02, 0x00, key.len() as u8]);
+            header.extend(key.as_bytes());
+            header.extend(value);
+        };
+        entry("bytes", &[0x0b, 0, 0, 0, 0, 0, 0, 0, 4]);
+        entry("strt", &[0x0b, 0, 0, 0, 0, 0, 0, 0, 149]);
+        entry("cplx", &[0x01]);
+        entry("type", &[0x03, 0, 0, 0, 1]);
+        entry("size", &[0x03, 0, 0, 0, 2]);
+        entry(
+            "rx_time",
+            &[
+                0x0c, 0, 0, 0, 2, 0x0b, 0, 0, 0, 0, 0x6a, 0x58, 0xa0, 0xee, 0x04, 0, 0, 0, 0, 0, 0,
+                0, 0,
+            ],
+        );
+        entry("rx_rate", &[0x04, 0x40, 0xdf, 0x40, 0, 0, 0, 0, 0]);
+        entry("version", &[0x03, 0, 0, 0, 0]);
+        header.push(0x06);
+        header.extend([1, 0, 2, 0]);
+        fs::write(dir.path().join("capture.dat"), header).expect("a GNU Radio capture");
+
+        let metadata =
+            gnuradio::inline_metadata(dir.path().join("capture.dat")).expect("the header parses");
+        gnuradio::describe(dir.path().join("capture.dat"), metadata)
+            .expect("describing must succeed");
+
+        assert_valid(
+            &read_json(&dir.path().join("capture.sigmf-meta")),
+            "the description of a GNU Radio capture",
+        );
+    }
+
+    /// And for a VITA 49 stream, whose segments and timestamps come from
+    /// Context and Data packets rather than from a caller.
+    #[test]
+    fn a_vita49_import_validates_against_the_schema() {
+        let dir = TempDir::new().expect("a temp dir");
+
+        // A Context packet — stream 1, CIF0 announcing the RF reference
+        // frequency and sample rate, 2.1875 MHz and 32 kSa/s — then an IF Data
+        // packet with a UTC timestamp and two `ci16` samples.
+        let words: [u32; 15] = [
+            0x4000_0007,
+            1,
+            1 << 27 | 1 << 21,
+            0x0000_0216,
+            0x0EC0_0000,
+            0x0000_0007,
+            0xD000_0000,
+            0x1460_0008,
+            1,
+            1_784_193_262,
+            0,
+            0,
+            0x0001_FFFF,
+            0x0002_FFFE,
+            0,
+        ];
+        let bytes: Vec<u8> = words.iter().flat_map(|w| w.to_be_bytes()).collect();
+        fs::write(dir.path().join("digitizer.vrt"), bytes).expect("a packet stream");
+
+        vita49::import(
+            dir.path().join("digitizer.vrt"),
+            dir.path().join("watch"),
+            "ci16_be".parse().expect("a datatype"),
+            None,
+        )
+        .expect("importing must succeed");
+
+        assert_valid(
+            &read_json(&dir.path().join("watch.sigmf-meta")),
+            "a VITA 49 stream imported by this crate",
+        );
+    }
+
+    /// And for a Bluefile's, which carries its keywords in the crate's own
+    /// `bluefile` extension — declared in `core:extensions` as the schema
+    /// requires of any namespace beyond `core`.
+    #[test]
+    fn a_bluefile_import_validates_against_the_schema() {
+        let dir = TempDir::new().expect("a temp dir");
+
+        let samples = dsc_samples();
+        let mut writer = RecordingWriter::with_metadata(&samples, a_dsc_watch_metadata("cf32_le"));
+        let mut keywords = bluefile::BluefileGlobal::default();
+        keywords
+            .keywords
+            .insert("SITE".to_string(), json!("North Head"));
+        writer
+            .global_mut()
+            .set_extension(keywords)
+            .expect("the extension serializes");
+        let recording = writer
+            .to_file(dir.path().join("dsc_watch"))
+            .expect("writing must succeed");
+        bluefile::export(&recording, dir.path().join("dsc_watch.tmp"))
+            .expect("exporting must succeed");
+        bluefile::import(
+            dir.path().join("dsc_watch.tmp"),
+            dir.path().join("imported"),
+        )
+        .expect("importing must succeed");
+
+        assert_valid(
+            &read_json(&dir.path().join("imported.sigmf-meta")),
+            "a Bluefile imported by this crate",
+        );
+    }
+
+    /// And for one imported from a NumPy array, whose document is no more
+    /// than a datatype, a sample rate and an empty Captures segment.
+    #[test]
+    fn an_npy_import_validates_against_the_schema() {
+        let dir = TempDir::new().expect("a temp dir");
+
+        let samples = dsc_samples();
+        let recording = RecordingWriter::with_metadata(&samples, a_dsc_watch_metadata("cf32_le"))
+            .to_file(dir.path().join("dsc_watch"))
+            .expect("writing must succeed");
+        recording
+            .export_npy(dir.path().join("dsc_watch.npy"))
+            .expect("exporting must succeed");
+        npy::import(
+            dir.path().join("dsc_watch.npy"),
+            dir.path().join("imported"),
+            32_000.0,
+        )
+        .expect("importing must succeed");
+
+        assert_valid(
+            &read_json(&dir.path().join("imported.sigmf-meta")),
+            "a NumPy array imported by this crate",
+        );
+    }
+
+    /// And for annotations that have been out to a CSV file and back, whose
+    /// numbers and strings were text in between.
+    #[test]
+    fn annotations_imported_from_csv_validate_against_the_schema() {
+        let dir = TempDir::new().expect("a temp dir");
+
+        let samples = dsc_samples();
+        let mut metadata = a_dsc_watch_metadata("cf32_le");
+        metadata.annotations = vec![serde_json::from_value(json!({
+            "core:sample_start": 1,
+            "core:sample_count": 2,
+            "core:freq_lower_edge": 2_187_200.0,
+            "core:freq_upper_edge": 2_187_800.0,
+            "core:label": "DSC distress",
+            "dsc:mmsi": "211000000",
+        }))
+        .expect("an annotation")];
+        annotations::export_csv(&metadata, dir.path().join("calls.csv"))
+            .expect("exporting must succeed");
+        let mut edited = metadata.clone();
+        edited.annotations =
+            annotations::import_csv(dir.path().join("calls.csv")).expect("importing must succeed");
+        RecordingWriter::with_metadata(&samples, edited)
+            .to_file(dir.path().join("relabelled"))
+            .expect("writing must succeed");
+
+        assert_valid(
+            &read_json(&dir.path().join("relabelled.sigmf-meta")),
+            "annotations imported from CSV by this crate",
+        );
+    }
+
     /// Samples in, samples out: what was written is what was handed over.
     #[test]
     fn a_written_recording_reads_back_with_its_metadata_and_samples_intact() {
@@ -0,0 +1,140 @@
+//! The ingest QA pass: each statistic checked against samples whose answer
+//! is known, in the datatypes where it matters.
+
+use std::f64::consts::PI;
+
+use sigmf::num_complex::Complex;
+use sigmf::{CaptureMetadata, RecordingWriter};
+use tempfile::TempDir;
+
+/// `len` samples of I = cos, Q = `gain`·sin skewed by `skew` radians, over
+/// whole periods so the moments come out exact.
+fn a_skewed_tone(len: usize, gain: f64, skew: f64) -> Vec<Complex<f64>> {
+    (0..len)
+        .map(|n| {
+            let phase = 2.0 * PI * 4.0 * n as f64 / len as f64;
+            Complex::new(phase.cos(), gain * (phase + skew).sin())
+        })
+        .collect()
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+}
+
+/// Two segments, one clean and one with a NaN and an infinity: each segment
+/// reports on itself, and the damage is counted without spoiling the
+/// averages.
+#[test]
+fn each_segment_and_the_whole_are_reported() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [
+        Complex::new(0.5f32, 0.5),
+        Complex::new(0.5, -0.5),
+        Complex::new(1.0, 0.0),
+        Complex::new(f32::NAN, 0.0),
+        Complex::new(0.0, f32::INFINITY),
+    ];
+    let mut writer = RecordingWriter::new(&samples, 1_000.0);
+    writer
+        .captures_mut()
+        .extend([CaptureMetadata::new(0), CaptureMetadata::new(2)]);
+    let recording = writer
+        .to_file(dir.path().join("watch"))
+        .expect("writing must succeed");
+
+    let statistics = recording.statistics().expect("statistics");
+
+    let [clean, damaged] = &statistics.captures[..] else {
+        panic!("two segments: {:?}", statistics.captures);
+    };
+    assert_eq!(clean.samples, 2);
+    assert_eq!(clean.mean_power, 0.5);
+    assert_eq!(clean.dc_offset, Complex::new(0.5, 0.0));
+    assert_eq!((clean.nan, clean.infinite), (0, 0));
+
+    assert_eq!(damaged.samples, 3);
+    assert_eq!((damaged.nan, damaged.infinite), (1, 1));
+    assert_eq!(damaged.mean_power, 1.0, "over the one finite sample");
+    assert_eq!(damaged.peak_magnitude, 1.0);
+
+    let overall = &statistics.overall;
+    assert_eq!(overall.samples, 5);
+    assert!(close(overall.mean_power, 2.0 / 3.0));
+    assert!(close(overall.rms, (2.0f64 / 3.0).sqrt()));
+    assert_eq!((overall.nan, overall.infinite, overall.clipped), (1, 1, 0));
+}
+
+/// An RTL-SDR's `cu8`: its offset shows as DC, and a component pinned at 0
+/// or 255 is clipped.
+#[test]
+fn integer_datasets_report_clipping_and_dc() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [
+        Complex::new(128u8, 128),
+        Complex::new(255, 128),
+        Complex::new(0, 255),
+        Complex::new(129, 127),
+    ];
+    let recording = RecordingWriter::new(&samples, 2.4e6)
+        .to_file(dir.path().join("rtl"))
+        .expect("writing must succeed");
+
+    let overall = recording.statistics().expect("statistics").overall;
+
+    assert_eq!(overall.clipped, 3);
+    assert_eq!((overall.nan, overall.infinite), (0, 0));
+    assert!(close(overall.dc_offset.re, 128.0 / 256.0));
+    assert!(close(overall.dc_offset.im, 638.0 / 4.0 / 256.0));
+}
+
+/// A 2:1 amplitude mismatch reads as 6 dB, and Q lagging quadrature by 5°
+/// reads as 5° — the two estimated independently.
+#[test]
+fn iq_imbalance_is_estimated() {
+    let dir = TempDir::new().expect("a temp dir");
+    let balanced = a_skewed_tone(1024, 1.0, 0.0);
+    let gain = a_skewed_tone(1024, 0.5, 0.0);
+    let skew = a_skewed_tone(1024, 1.0, 5f64.to_radians());
+
+    let imbalance = |samples: &[Complex<f64>], name: &str| {
+        RecordingWriter::new(samples, 1_000.0)
+            .to_file(dir.path().join(name))
+            .expect("writing must succeed")
+            .statistics()
+            .expect("statistics")
+            .overall
+            .iq_imbalance
+            .expect("a complex Recording with variation in I and Q")
+    };
+
+    let balanced = imbalance(&balanced, "balanced");
+    assert!(close(balanced.gain_db, 0.0) && close(balanced.phase_degrees, 0.0));
+    let gain = imbalance(&gain, "gain");
+    assert!(close(gain.gain_db, 20.0 * 2f64.log10()), "{gain:?}");
+    assert!(close(gain.phase_degrees, 0.0), "{gain:?}");
+    let skew = imbalance(&skew, "skew");
+    assert!(close(skew.gain_db, 0.0), "{skew:?}");
+    assert!(close(skew.phase_degrees, 5.0), "{skew:?}");
+}
+
+/// A real Recording has no Q: no DC in it, and no balance to estimate.
+#[test]
+fn real_datasets_have_no_iq_imbalance() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [-16_384i16, 16_384, 32_767, 0];
+    let recording = RecordingWriter::new(&samples, 48_000.0)
+        .to_file(dir.path().join("audio"))
+        .expect("writing must succeed");
+
+    let overall = recording.statistics().expect("statistics").overall;
+
+    assert_eq!(overall.iq_imbalance, None);
+    assert_eq!(overall.dc_offset.im, 0.0);
+    assert_eq!(overall.clipped, 1);
+    assert!(close(overall.peak_magnitude, 32_767.0 / 32_768.0));
+    assert!(close(
+        overall.mean_power,
+        (0.25 + 0.25 + (32_767.0f64 / 32_768.0).powi(2)) / 4.0
+    ));
+}
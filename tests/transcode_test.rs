@@ -0,0 +1,176 @@
+//! Rewriting a Recording's samples in another datatype or byte order.
+//!
+//! The new `core:datatype` must describe the new bytes, the numbers must mean
+//! what the chosen [`Scaling`] says they mean, and every value an integer type
+//! could not hold must be counted rather than silently wrapped.
+
+use serde_json::json;
+use sigmf::num_complex::Complex;
+use sigmf::{
+    AnnotationMetadata, CaptureMetadata, Endianness, Error, MetadataError, RecordingWriter, Scaling,
+};
+use std::fs;
+use tempfile::TempDir;
+
+/// The case the conversion exists for: a vendor's big-endian integers, into the
+/// little-endian floats a pipeline wants, with the document intact.
+#[test]
+fn a_big_endian_integer_recording_becomes_little_endian_floats() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [Complex::new(8_192i16, -16_384), Complex::new(32_767, 0)];
+
+    let mut writer = RecordingWriter::new(&samples, 2.0e6).endianness(Endianness::BigEndian);
+    let mut capture = CaptureMetadata::new(0);
+    capture.frequency = Some(16_804_500.0);
+    writer.captures_mut().push(capture);
+    writer.annotations_mut().push(
+        serde_json::from_value::<AnnotationMetadata>(
+            json!({"core:sample_start": 1, "core:sample_count": 1, "core:label": "DSC"}),
+        )
+        .expect("the annotation literal must deserialize"),
+    );
+    let vendor = writer
+        .to_file(dir.path().join("vendor"))
+        .expect("writing the source must succeed");
+
+    let ours = vendor
+        .transcode::<Complex<f32>, _>(
+            dir.path().join("ours"),
+            Scaling::FullScale,
+            Endianness::LittleEndian,
+        )
+        .expect("transcoding must succeed");
+
+    assert_eq!(
+        ours.recording.metadata.global.datatype.to_string(),
+        "cf32_le"
+    );
+    assert_eq!(
+        ours.recording
+            .samples::<Complex<f32>>()
+            .expect("the transcoded Dataset reads back"),
+        [
+            Complex::new(0.25, -0.5),
+            Complex::new(32_767.0 / 32_768.0, 0.0)
+        ]
+    );
+    assert_eq!(ours.clipped, 0);
+    assert_eq!(ours.recording.metadata.captures, vendor.metadata.captures);
+    assert_eq!(
+        ours.recording.metadata.annotations,
+        vendor.metadata.annotations
+    );
+}
+
+/// Only the byte order changes, so every sample is the same number and every
+/// byte pair is reversed.
+#[test]
+fn changing_only_the_byte_order_swaps_every_component() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [0x0102i16, -2, 0x7f00];
+    let little = RecordingWriter::new(&samples, 32_000.0)
+        .to_file(dir.path().join("little"))
+        .expect("writing the source must succeed");
+
+    let big = little
+        .transcode::<i16, _>(
+            dir.path().join("big"),
+            Scaling::Preserve,
+            Endianness::BigEndian,
+        )
+        .expect("transcoding must succeed")
+        .recording;
+
+    assert_eq!(big.metadata.global.datatype.to_string(), "ri16_be");
+    assert_eq!(big.samples::<i16>().expect("reads back"), samples);
+    let before = fs::read(dir.path().join("little.sigmf-data")).expect("the source Dataset");
+    let after = fs::read(dir.path().join("big.sigmf-data")).expect("the new Dataset");
+    let swapped: Vec<u8> = before.chunks(2).flat_map(|c| [c[1], c[0]]).collect();
+    assert_eq!(after, swapped);
+}
+
+/// Narrowing to an integer rounds, saturates at the type's range, and counts
+/// every component that did not fit — NaN among them.
+#[test]
+fn narrowing_to_an_integer_clips_and_counts_what_did_not_fit() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [
+        Complex::new(0.5f32, -0.25),
+        Complex::new(1.5, -2.0),
+        Complex::new(f32::NAN, 0.0),
+    ];
+    let floats = RecordingWriter::new(&samples, 32_000.0)
+        .to_file(dir.path().join("floats"))
+        .expect("writing the source must succeed");
+
+    let narrowed = floats
+        .transcode::<Complex<i8>, _>(
+            dir.path().join("narrowed"),
+            Scaling::FullScale,
+            Endianness::LittleEndian,
+        )
+        .expect("transcoding must succeed");
+
+    assert_eq!(
+        narrowed
+            .recording
+            .samples::<Complex<i8>>()
+            .expect("reads back"),
+        [
+            Complex::new(64, -32),
+            Complex::new(127, -128),
+            Complex::new(0, 0)
+        ]
+    );
+    assert_eq!(
+        narrowed.clipped, 3,
+        "1.5 and NaN do not fit, and -2.0 does not either; -1.0 would have"
+    );
+}
+
+/// `Preserve` keeps the number, so an unsigned value past the signed range is
+/// clipped rather than reinterpreted.
+#[test]
+fn preserving_values_clips_rather_than_wrapping() {
+    let dir = TempDir::new().expect("a temp dir");
+    let unsigned = RecordingWriter::new(&[5u8, 127, 200], 32_000.0)
+        .to_file(dir.path().join("unsigned"))
+        .expect("writing the source must succeed");
+
+    let signed = unsigned
+        .transcode::<i8, _>(
+            dir.path().join("signed"),
+            Scaling::Preserve,
+            Endianness::LittleEndian,
+        )
+        .expect("transcoding must succeed");
+
+    assert_eq!(
+        signed.recording.samples::<i8>().expect("reads back"),
+        [5, 127, 127]
+    );
+    assert_eq!(signed.clipped, 1);
+}
+
+/// Real and complex differ in how many components a sample has, and no
+/// datatype conversion can make up the difference.
+#[test]
+fn real_and_complex_cannot_be_transcoded_into_each_other() {
+    let dir = TempDir::new().expect("a temp dir");
+    let real = RecordingWriter::new(&[1.0f32, 2.0], 32_000.0)
+        .to_file(dir.path().join("real"))
+        .expect("writing the source must succeed");
+
+    let err = real
+        .transcode::<Complex<f32>, _>(
+            dir.path().join("complex"),
+            Scaling::Preserve,
+            Endianness::LittleEndian,
+        )
+        .expect_err("a real Dataset has no quadrature to carry");
+    assert!(matches!(
+        err,
+        Error::Metadata(MetadataError::NumberTypeMismatch { .. })
+    ));
+    assert!(!dir.path().join("complex.sigmf-data").exists());
+}
@@ -0,0 +1,171 @@
+//! Validation: the rules of the specification that parsing cannot enforce.
+//!
+//! Each test breaks one rule in an otherwise conforming document and asserts
+//! that exactly that rule is reported, where it is broken — a validator that
+//! reports the right problem in the wrong place sends its reader hunting.
+
+use std::fs;
+
+use sigmf::{Finding, Metadata, RecordingWriter, Severity, SigMF};
+use tempfile::TempDir;
+
+/// A conforming document, with `captures` and `annotations` spliced in.
+fn document(captures: &str, annotations: &str) -> Metadata {
+    Metadata::from_json(&format!(
+        r#"{{
+            "global": {{
+                "core:datatype": "cf32_le",
+                "core:version": "1.2.6",
+                "core:sample_rate": 32000
+            }},
+            "captures": {captures},
+            "annotations": {annotations}
+        }}"#
+    ))
+    .expect("the document must parse")
+}
+
+fn locations(findings: &[Finding]) -> Vec<(Severity, &str)> {
+    findings
+        .iter()
+        .map(|f| (f.severity, f.location.as_str()))
+        .collect()
+}
+
+#[test]
+fn a_conforming_document_has_no_findings() {
+    let metadata = document(
+        r#"[{"core:sample_start": 0, "core:datetime": "2026-07-16T09:14:22.000Z"}]"#,
+        r#"[{"core:sample_start": 0, "core:freq_lower_edge": 1, "core:freq_upper_edge": 2}]"#,
+    );
+    assert_eq!(metadata.validate(), []);
+}
+
+#[test]
+fn captures_out_of_order_are_an_error_at_the_later_one() {
+    let metadata = document(
+        r#"[{"core:sample_start": 0}, {"core:sample_start": 100}, {"core:sample_start": 50}]"#,
+        "[]",
+    );
+    assert_eq!(
+        locations(&metadata.validate()),
+        [(Severity::Error, "captures/2/core:sample_start")]
+    );
+}
+
+#[test]
+fn annotations_out_of_order_are_an_error() {
+    let metadata = document(
+        "[]",
+        r#"[{"core:sample_start": 10}, {"core:sample_start": 5}]"#,
+    );
+    assert_eq!(
+        locations(&metadata.validate()),
+        [(Severity::Error, "annotations/1/core:sample_start")]
+    );
+}
+
+/// Sample indices are absolute, so one below `core:offset` is before the
+/// Dataset began — readable, but not what the specification asks for.
+#[test]
+fn a_sample_index_before_the_offset_is_a_warning() {
+    let mut metadata = document(
+        r#"[{"core:sample_start": 0}, {"core:sample_start": 1000}]"#,
+        r#"[{"core:sample_start": 999}, {"core:sample_start": 1000}]"#,
+    );
+    metadata.global.offset = Some(1000);
+    assert_eq!(
+        locations(&metadata.validate()),
+        [
+            (Severity::Warning, "captures/0/core:sample_start"),
+            (Severity::Warning, "annotations/0/core:sample_start"),
+        ]
+    );
+}
+
+/// The schema types `core:datetime` as a string; the specification's grammar
+/// is what makes `yesterday` wrong.
+#[test]
+fn a_datetime_that_is_not_rfc_3339_in_utc_is_an_error() {
+    for datetime in [
+        "yesterday",
+        "2026-07-16T09:14:22+02:00",
+        "2026-02-30T00:00:00Z",
+    ] {
+        let metadata = document(
+            &format!(r#"[{{"core:sample_start": 0, "core:datetime": "{datetime}"}}]"#),
+            "[]",
+        );
+        assert_eq!(
+            locations(&metadata.validate()),
+            [(Severity::Error, "captures/0/core:datetime")],
+            "{datetime}"
+        );
+    }
+}
+
+#[test]
+fn a_band_must_have_both_edges_in_order() {
+    let metadata = document(
+        "[]",
+        r#"[
+            {"core:sample_start": 0, "core:freq_upper_edge": 2},
+            {"core:sample_start": 1, "core:freq_lower_edge": 3, "core:freq_upper_edge": 2}
+        ]"#,
+    );
+    assert_eq!(
+        locations(&metadata.validate()),
+        [
+            (Severity::Error, "annotations/0"),
+            (Severity::Error, "annotations/1")
+        ]
+    );
+}
+
+/// Errors first, so a reader who stops at the first line stops at the worst.
+#[test]
+fn errors_come_before_warnings() {
+    let mut metadata = document(
+        r#"[{"core:sample_start": 0, "core:datetime": "yesterday"}]"#,
+        "[]",
+    );
+    metadata.global.sample_rate = None;
+    metadata
+        .global
+        .other
+        .insert("antenna:gain".to_string(), serde_json::json!(3.0));
+
+    assert_eq!(
+        locations(&metadata.validate()),
+        [
+            (Severity::Error, "captures/0/core:datetime"),
+            (Severity::Warning, "global/core:sample_rate"),
+            (Severity::Warning, "global/antenna:gain"),
+        ]
+    );
+}
+
+/// The Dataset is part of the Recording, and a Captures array that does not
+/// fit it is a finding like any other.
+#[test]
+fn a_dataset_that_does_not_fit_its_captures_is_an_error() {
+    let dir = TempDir::new().expect("a temp dir");
+    RecordingWriter::new(&[1.0f32, 2.0, 3.0], 32_000.0)
+        .to_file(dir.path().join("watch"))
+        .expect("writing must succeed");
+    let recording =
+        SigMF::from_file(dir.path().join("watch.sigmf-meta")).expect("the Recording opens");
+    assert_eq!(recording.validate(), []);
+
+    fs::write(dir.path().join("watch.sigmf-data"), [0u8; 10]).expect("truncating a sample");
+    assert_eq!(
+        locations(&recording.validate()),
+        [(Severity::Error, "dataset")]
+    );
+
+    fs::remove_file(dir.path().join("watch.sigmf-data")).expect("removing the Dataset");
+    assert_eq!(
+        locations(&recording.validate()),
+        [(Severity::Error, "dataset")]
+    );
+}
@@ -0,0 +1,328 @@
+//! VITA 49 packet streams, built word by word as a digitizer would send them.
+
+use std::fs;
+
+use sigmf::num_complex::Complex;
+use sigmf::{vita49, Error, MetadataError};
+use tempfile::TempDir;
+
+const STREAM: u32 = 0x0000_0B0E;
+
+/// A header word: type, class ID, trailer, TSI, TSF, count and size.
+fn header(packet_type: u32, trailer: bool, tsi: u32, tsf: u32, count: u32, words: usize) -> u32 {
+    packet_type << 28
+        | u32::from(trailer) << 26
+        | tsi << 22
+        | tsf << 20
+        | (count & 0xF) << 16
+        | words as u32
+}
+
+fn fixed(hertz: f64) -> [u32; 2] {
+    let raw = (hertz * f64::from(1 << 20)) as i64 as u64;
+    [(raw >> 32) as u32, raw as u32]
+}
+
+/// A Context packet on `stream` stating an RF frequency and a sample rate —
+/// and a bandwidth, which comes between them and must be stepped over.
+fn context(stream: u32, frequency: f64, rate: f64) -> Vec<u32> {
+    let cif0 = 1 << 29 | 1 << 27 | 1 << 21;
+    let mut words = vec![0, stream, cif0];
+    words.extend(fixed(rate / 1.25));
+    words.extend(fixed(frequency));
+    words.extend(fixed(rate));
+    words[0] = header(0b0100, false, 0, 0, 0, words.len());
+    words
+}
+
+/// An IF Data packet on `stream` of `samples` as `ci16`, with a UTC timestamp
+/// in picoseconds and a trailer.
+fn data(stream: u32, seconds: u32, picoseconds: u64, samples: &[(i16, i16)]) -> Vec<u32> {
+    let mut words = vec![
+        0,
+        stream,
+        seconds,
+        (picoseconds >> 32) as u32,
+        picoseconds as u32,
+    ];
+    words.extend(
+        samples
+            .iter()
+            .map(|&(i, q)| (i as u16 as u32) << 16 | q as u16 as u32),
+    );
+    words.push(0);
+    words[0] = header(0b0001, true, 1, 2, 0, words.len());
+    words
+}
+
+fn write(dir: &TempDir, name: &str, packets: &[Vec<u32>]) {
+    let bytes: Vec<u8> = packets
+        .iter()
+        .flatten()
+        .flat_map(|word| word.to_be_bytes())
+        .collect();
+    fs::write(dir.path().join(name), bytes).expect("the packet stream");
+}
+
+#[test]
+fn payloads_become_the_dataset_and_context_the_metadata() {
+    let dir = TempDir::new().expect("a temp dir");
+    write(
+        &dir,
+        "digitizer.vrt",
+        &[
+            context(STREAM, 2_187_500.0, 4.0),
+            data(STREAM, 1_784_193_262, 0, &[(1, -1), (2, -2)]),
+            data(STREAM, 1_784_193_262, 500_000_000_000, &[(3, -3)]),
+        ],
+    );
+
+    let recording = vita49::import(
+        dir.path().join("digitizer.vrt"),
+        dir.path().join("watch"),
+        "ci16_be".parse().expect("a datatype"),
+        None,
+    )
+    .expect("importing must succeed");
+
+    assert_eq!(recording.metadata.global.sample_rate, Some(4.0));
+    assert_eq!(
+        recording.metadata.captures.len(),
+        1,
+        "the stream is unbroken"
+    );
+    let capture = &recording.metadata.captures[0];
+    assert_eq!(capture.frequency, Some(2_187_500.0));
+    assert_eq!(
+        capture.datetime.as_deref(),
+        Some("2026-07-16T09:14:22.000Z")
+    );
+    assert_eq!(
+        recording.samples::<Complex<i16>>().expect("reads back"),
+        [
+            Complex::new(1, -1),
+            Complex::new(2, -2),
+            Complex::new(3, -3)
+        ]
+    );
+    recording
+        .verify_sha512()
+        .expect("the hash is of the Dataset");
+}
+
+/// A retune is a new segment, starting at the first sample after it.
+#[test]
+fn a_retune_starts_a_segment() {
+    let dir = TempDir::new().expect("a temp dir");
+    write(
+        &dir,
+        "retune.vrt",
+        &[
+            context(STREAM, 2_187_500.0, 4.0),
+            data(STREAM, 100, 0, &[(1, 1), (2, 2)]),
+            context(STREAM, 8_414_500.0, 4.0),
+            data(STREAM, 100, 500_000_000_000, &[(3, 3)]),
+        ],
+    );
+
+    let recording = vita49::import(
+        dir.path().join("retune.vrt"),
+        dir.path().join("watch"),
+        "ci16_be".parse().expect("a datatype"),
+        None,
+    )
+    .expect("importing must succeed");
+
+    let segments: Vec<(u64, Option<f64>)> = recording
+        .metadata
+        .captures
+        .iter()
+        .map(|c| (c.sample_start, c.frequency))
+        .collect();
+    assert_eq!(segments, [(0, Some(2_187_500.0)), (2, Some(8_414_500.0))]);
+    assert_eq!(
+        recording.metadata.captures[1].datetime.as_deref(),
+        Some("1970-01-01T00:01:40.500Z")
+    );
+}
+
+/// A packet lost in transit leaves a jump in the timestamps, which is a new
+/// segment — and its `core:datetime` says where the samples resume.
+#[test]
+fn a_dropped_packet_starts_a_segment() {
+    let dir = TempDir::new().expect("a temp dir");
+    write(
+        &dir,
+        "dropped.vrt",
+        &[
+            context(STREAM, 2_187_500.0, 4.0),
+            data(STREAM, 100, 0, &[(1, 1), (2, 2)]),
+            data(STREAM, 101, 500_000_000_000, &[(3, 3)]),
+        ],
+    );
+
+    let recording = vita49::import(
+        dir.path().join("dropped.vrt"),
+        dir.path().join("watch"),
+        "ci16_be".parse().expect("a datatype"),
+        None,
+    )
+    .expect("importing must succeed");
+
+    assert_eq!(recording.metadata.captures.len(), 2);
+    assert_eq!(recording.metadata.captures[1].sample_start, 2);
+    assert_eq!(
+        recording.metadata.captures[1].datetime.as_deref(),
+        Some("1970-01-01T00:01:41.500Z")
+    );
+}
+
+/// Packets of another stream, data or context, do not reach the Recording.
+#[test]
+fn only_the_chosen_stream_is_imported() {
+    let dir = TempDir::new().expect("a temp dir");
+    write(
+        &dir,
+        "two.vrt",
+        &[
+            context(STREAM, 2_187_500.0, 4.0),
+            context(7, 100e6, 4.0),
+            data(7, 100, 0, &[(9, 9)]),
+            data(STREAM, 100, 0, &[(1, 1)]),
+        ],
+    );
+
+    let recording = vita49::import(
+        dir.path().join("two.vrt"),
+        dir.path().join("watch"),
+        "ci16_be".parse().expect("a datatype"),
+        Some(STREAM),
+    )
+    .expect("importing must succeed");
+
+    assert_eq!(
+        recording.samples::<Complex<i16>>().expect("reads back"),
+        [Complex::new(1, 1)]
+    );
+    assert_eq!(recording.metadata.captures[0].frequency, Some(2_187_500.0));
+}
+
+/// With no stream named, the first data packet's stream is imported, and
+/// Context packets that came before it are held until then — so another
+/// stream's Context packet, sent first, does not tune this one.
+#[test]
+fn context_before_the_first_data_packet_waits_for_its_stream() {
+    let dir = TempDir::new().expect("a temp dir");
+    write(
+        &dir,
+        "two.vrt",
+        &[
+            context(7, 100e6, 8.0),
+            context(STREAM, 2_187_500.0, 4.0),
+            data(7, 100, 0, &[(9, 9)]),
+            data(STREAM, 100, 0, &[(1, 1)]),
+            context(STREAM, 3e6, 4.0),
+            data(7, 100, 125_000_000_000, &[(9, 9)]),
+        ],
+    );
+
+    let recording = vita49::import(
+        dir.path().join("two.vrt"),
+        dir.path().join("watch"),
+        "ci16_be".parse().expect("a datatype"),
+        None,
+    )
+    .expect("importing must succeed");
+
+    assert_eq!(
+        recording.samples::<Complex<i16>>().expect("reads back"),
+        [Complex::new(9, 9), Complex::new(9, 9)]
+    );
+    assert_eq!(recording.metadata.global.sample_rate, Some(8.0));
+    assert_eq!(recording.metadata.captures.len(), 1);
+    assert_eq!(recording.metadata.captures[0].frequency, Some(100e6));
+}
+
+/// Data packets without a stream identifier leave nothing to match Context
+/// packets against, so every Context packet applies to them.
+#[test]
+fn data_without_a_stream_identifier_takes_every_context() {
+    let dir = TempDir::new().expect("a temp dir");
+    let bare = |picoseconds: u64, sample: u32| {
+        let mut words = vec![0, 100, (picoseconds >> 32) as u32, picoseconds as u32];
+        words.extend([sample << 16 | sample, 0]);
+        words[0] = header(0b0000, true, 1, 2, 0, words.len());
+        words
+    };
+    write(
+        &dir,
+        "bare.vrt",
+        &[
+            bare(0, 1),
+            context(STREAM, 2_187_500.0, 4.0),
+            bare(250_000_000_000, 2),
+        ],
+    );
+
+    let recording = vita49::import(
+        dir.path().join("bare.vrt"),
+        dir.path().join("watch"),
+        "ci16_be".parse().expect("a datatype"),
+        None,
+    )
+    .expect("importing must succeed");
+
+    assert_eq!(
+        recording.samples::<Complex<i16>>().expect("reads back"),
+        [Complex::new(1, 1), Complex::new(2, 2)]
+    );
+    assert_eq!(recording.metadata.global.sample_rate, Some(4.0));
+    let frequencies: Vec<_> = recording
+        .metadata
+        .captures
+        .iter()
+        .map(|c| (c.sample_start, c.frequency))
+        .collect();
+    assert_eq!(frequencies, [(0, None), (1, Some(2_187_500.0))]);
+}
+
+#[test]
+fn a_payload_of_part_of_a_sample_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    write(
+        &dir,
+        "odd.vrt",
+        &[data(STREAM, 100, 0, &[(1, 1), (2, 2), (3, 3)])],
+    );
+
+    let err = vita49::import(
+        dir.path().join("odd.vrt"),
+        dir.path().join("odd"),
+        "cf32_be".parse().expect("a datatype"),
+        None,
+    )
+    .expect_err("twelve bytes of eight-byte samples");
+    assert!(matches!(
+        err,
+        Error::Metadata(MetadataError::PartialSample { bytes: 12, .. })
+    ));
+}
+
+#[test]
+fn a_packet_cut_short_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    let mut packet = data(STREAM, 100, 0, &[(1, 1)]);
+    packet.pop();
+    packet.pop();
+    packet[0] = header(0b0001, true, 1, 2, 0, 7);
+    write(&dir, "short.vrt", &[packet]);
+
+    let err = vita49::import(
+        dir.path().join("short.vrt"),
+        dir.path().join("short"),
+        "ci16_be".parse().expect("a datatype"),
+        None,
+    )
+    .expect_err("the header promises more words than there are");
+    assert!(matches!(err, Error::Io { .. }));
+}
@@ -0,0 +1,276 @@
+//! WAV import and export.
+//!
+//! The WAV files here are built byte by byte rather than by [`wav::export`], so
+//! that the reader is judged against the format and not against the writer.
+
+use std::fs;
+
+use sigmf::num_complex::Complex;
+use sigmf::{wav, Error, MetadataError, RecordingWriter};
+use tempfile::TempDir;
+
+/// A WAV file of `channels` channels of `bits`-bit samples in `format`, with
+/// `extra` chunks between `fmt ` and `data`.
+fn wav_file(
+    format: u16,
+    channels: u16,
+    rate: u32,
+    bits: u16,
+    extra: &[u8],
+    data: &[u8],
+) -> Vec<u8> {
+    let frame = channels * bits / 8;
+    let mut body = b"WAVE".to_vec();
+    body.extend(b"fmt ");
+    body.extend(16u32.to_le_bytes());
+    body.extend(format.to_le_bytes());
+    body.extend(channels.to_le_bytes());
+    body.extend(rate.to_le_bytes());
+    body.extend((rate * u32::from(frame)).to_le_bytes());
+    body.extend(frame.to_le_bytes());
+    body.extend(bits.to_le_bytes());
+    body.extend(extra);
+    body.extend(b"data");
+    body.extend((data.len() as u32).to_le_bytes());
+    body.extend(data);
+    if data.len() % 2 == 1 {
+        body.push(0);
+    }
+    let mut file = b"RIFF".to_vec();
+    file.extend((body.len() as u32).to_le_bytes());
+    file.extend(body);
+    file
+}
+
+/// An `auxi` chunk as HDSDR writes it: start and stop `SYSTEMTIME`s, then the
+/// centre frequency.
+fn auxi(start: [u16; 8], frequency: u32) -> Vec<u8> {
+    let mut chunk = b"auxi".to_vec();
+    chunk.extend(164u32.to_le_bytes());
+    let mut body = Vec::new();
+    for field in start.iter().chain(&[0; 8]) {
+        body.extend(field.to_le_bytes());
+    }
+    body.extend(frequency.to_le_bytes());
+    body.resize(164, 0);
+    chunk.extend(body);
+    chunk
+}
+
+#[test]
+fn a_stereo_sdr_capture_is_i_and_q_with_its_frequency_and_start() {
+    let dir = TempDir::new().expect("a temp dir");
+    let mut data = Vec::new();
+    for i in 0..4i16 {
+        data.extend(i.to_le_bytes());
+        data.extend((-i).to_le_bytes());
+    }
+    let extra = auxi([2026, 7, 4, 16, 9, 14, 22, 500], 8_414_500);
+    fs::write(
+        dir.path().join("hdsdr.wav"),
+        wav_file(1, 2, 192_000, 16, &extra, &data),
+    )
+    .expect("a WAV file");
+
+    let recording = wav::import(dir.path().join("hdsdr.wav"), dir.path().join("watch"))
+        .expect("importing must succeed");
+
+    assert_eq!(recording.metadata.global.datatype.to_string(), "ci16_le");
+    assert_eq!(recording.metadata.global.sample_rate, Some(192_000.0));
+    let capture = &recording.metadata.captures[0];
+    assert_eq!(capture.frequency, Some(8_414_500.0));
+    assert_eq!(
+        capture.datetime.as_deref(),
+        Some("2026-07-16T09:14:22.500Z")
+    );
+    assert_eq!(
+        recording.samples::<Complex<i16>>().expect("reads back")[3],
+        Complex::new(3, -3)
+    );
+    recording
+        .verify_sha512()
+        .expect("the hash is of the Dataset");
+}
+
+/// SigMF has no 24-bit type: each sample lands in the top three bytes of an
+/// `i32`, so full scale stays full scale.
+#[test]
+fn twenty_four_bit_samples_are_widened_to_thirty_two() {
+    let dir = TempDir::new().expect("a temp dir");
+    let data = [0x01, 0x02, 0x03, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x80];
+    fs::write(
+        dir.path().join("mono24.wav"),
+        wav_file(1, 1, 48_000, 24, &[], &data),
+    )
+    .expect("a WAV file");
+
+    let recording = wav::import(dir.path().join("mono24.wav"), dir.path().join("mono"))
+        .expect("importing must succeed");
+
+    assert_eq!(recording.metadata.global.datatype.to_string(), "ri32_le");
+    assert_eq!(
+        recording.samples::<i32>().expect("reads back"),
+        [0x0302_0100, -256, i32::MIN]
+    );
+}
+
+#[test]
+fn a_float_wav_is_a_float_recording() {
+    let dir = TempDir::new().expect("a temp dir");
+    let data: Vec<u8> = [0.5f32, -0.25]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    fs::write(
+        dir.path().join("float.wav"),
+        wav_file(3, 2, 48_000, 32, &[], &data),
+    )
+    .expect("a WAV file");
+
+    let recording = wav::import(dir.path().join("float.wav"), dir.path().join("float"))
+        .expect("importing must succeed");
+    assert_eq!(
+        recording.samples::<Complex<f32>>().expect("reads back"),
+        [Complex::new(0.5, -0.25)]
+    );
+}
+
+/// Neither real nor I and Q: there is no datatype for it.
+#[test]
+fn a_surround_wav_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::write(
+        dir.path().join("surround.wav"),
+        wav_file(1, 6, 48_000, 16, &[], &[0; 12]),
+    )
+    .expect("a WAV file");
+
+    let err = wav::import(dir.path().join("surround.wav"), dir.path().join("surround"))
+        .expect_err("six channels");
+    assert!(matches!(err, Error::Io { .. }));
+    assert!(!dir.path().join("surround.sigmf-meta").exists());
+}
+
+#[test]
+fn a_truncated_frame_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    fs::write(
+        dir.path().join("cut.wav"),
+        wav_file(1, 2, 48_000, 16, &[], &[0; 6]),
+    )
+    .expect("a WAV file");
+
+    let err = wav::import(dir.path().join("cut.wav"), dir.path().join("cut"))
+        .expect_err("one and a half frames");
+    assert!(matches!(
+        err,
+        Error::Metadata(MetadataError::PartialSample { bytes: 6, .. })
+    ));
+}
+
+/// WAV's wider integers are signed, so `u16` moves by half its range — the
+/// midpoint of the Recording is the silence of the WAV file.
+#[test]
+fn exporting_unsigned_big_endian_samples_makes_signed_little_endian_ones() {
+    let dir = TempDir::new().expect("a temp dir");
+    let recording = RecordingWriter::new(&[0u16, 0x8000, 0xFFFF], 48_000.0)
+        .endianness(sigmf::Endianness::BigEndian)
+        .to_file(dir.path().join("unsigned"))
+        .expect("writing must succeed");
+
+    wav::export(&recording, dir.path().join("unsigned.wav")).expect("exporting must succeed");
+
+    let bytes = fs::read(dir.path().join("unsigned.wav")).expect("the WAV file");
+    assert_eq!(&bytes[..4], b"RIFF");
+    assert_eq!(
+        &bytes[bytes.len() - 6..],
+        [0x00, 0x80, 0x00, 0x00, 0xFF, 0x7F]
+    );
+    let back = wav::import(dir.path().join("unsigned.wav"), dir.path().join("back"))
+        .expect("importing must succeed");
+    assert_eq!(
+        back.samples::<i16>().expect("reads back"),
+        [i16::MIN, 0, i16::MAX]
+    );
+}
+
+/// The `auxi` chunk carries the start time out and back.
+#[test]
+fn the_start_time_survives_a_round_trip() {
+    let dir = TempDir::new().expect("a temp dir");
+    let samples = [Complex::new(0.5f32, -0.5); 4];
+    let mut writer = RecordingWriter::new(&samples, 4.0);
+    let mut capture = sigmf::CaptureMetadata::new(0);
+    capture.datetime = Some("2026-07-16T09:14:22.250Z".to_string());
+    writer.captures_mut().push(capture);
+    let recording = writer
+        .to_file(dir.path().join("watch"))
+        .expect("writing must succeed");
+
+    wav::export(&recording, dir.path().join("watch.wav")).expect("exporting must succeed");
+    let back = wav::import(dir.path().join("watch.wav"), dir.path().join("back"))
+        .expect("importing must succeed");
+    assert_eq!(
+        back.metadata.captures[0].datetime,
+        recording.metadata.captures[0].datetime
+    );
+    assert_eq!(back.metadata.captures[0].frequency, None);
+    assert_eq!(
+        back.samples::<Complex<f32>>().expect("reads back"),
+        recording.samples::<Complex<f32>>().expect("reads back")
+    );
+}
+
+/// A WAV header holds its rates as whole 32-bit numbers; one that does not fit
+/// is refused rather than rounded or wrapped.
+#[test]
+fn a_rate_a_wav_header_cannot_hold_is_refused() {
+    let dir = TempDir::new().expect("a temp dir");
+    let fractional = RecordingWriter::new(&[0i16; 2], 44_100.5)
+        .to_file(dir.path().join("fractional"))
+        .expect("writing must succeed");
+    let fast = RecordingWriter::new(&[Complex::new(0.0f64, 0.0); 2], 1e9)
+        .to_file(dir.path().join("fast"))
+        .expect("writing must succeed");
+
+    for recording in [fractional, fast] {
+        let wav = dir.path().join("refused.wav");
+        let err = wav::export(&recording, &wav).expect_err("the header cannot hold it");
+        assert!(
+            matches!(err, Error::Io { ref path, .. } if *path == wav),
+            "{err}"
+        );
+        assert!(!wav.exists());
+    }
+}
+
+/// The samples do not depend on the centre frequency, so one the `auxi`
+/// chunk cannot hold is left out of it, not a reason to refuse the export —
+/// and not a different frequency written in its place.
+#[test]
+fn a_frequency_the_auxi_chunk_cannot_hold_is_left_out() {
+    let dir = TempDir::new().expect("a temp dir");
+    let mut writer = RecordingWriter::new(&[1i16, 2], 48_000.0);
+    let mut capture = sigmf::CaptureMetadata::new(0);
+    capture.frequency = Some(5.8e9);
+    capture.datetime = Some("2026-07-16T09:14:22.000Z".to_string());
+    writer.captures_mut().push(capture);
+    let microwave = writer
+        .to_file(dir.path().join("microwave"))
+        .expect("writing must succeed");
+
+    let wav = dir.path().join("microwave.wav");
+    wav::export(&microwave, &wav).expect("the samples fit a WAV file");
+    let back = wav::import(&wav, dir.path().join("back")).expect("the export reads back");
+    assert_eq!(
+        back.samples::<i16>().expect("the samples read back"),
+        [1, 2]
+    );
+    let capture = &back.metadata.captures[0];
+    assert_eq!(capture.frequency, None);
+    assert_eq!(
+        capture.datetime.as_deref(),
+        Some("2026-07-16T09:14:22.000Z"),
+        "the rest of the chunk is still written"
+    );
+}